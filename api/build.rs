@@ -0,0 +1,27 @@
+use std::{path::Path, process::Command};
+
+/// Passes the commit being built to `/version`. A `GIT_HASH` set for the
+/// build, as in an image built without the `.git` directory, wins.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    if std::env::var("GIT_HASH").is_ok() {
+        return;
+    }
+
+    for path in ["../.git/HEAD", "../.git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=GIT_HASH={}", hash.trim());
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use axum::Router;
+use service::{
+    config::Config,
+    mail::{LogMailer, Mailer, SmtpMailer},
+    sea_orm::DatabaseConnection,
+    search::backend::SearchBackend,
+};
+
+use crate::{route, search_backend, AppState, Metrics};
+
+/// The API as a router, to be served as is or nested into another axum app.
+/// The database has to be migrated already, and binding a socket and running
+/// the daily jobs are left to the caller.
+pub struct App;
+
+impl App {
+    pub fn builder(conn: DatabaseConnection, config: Config) -> AppBuilder {
+        AppBuilder {
+            conn,
+            config,
+            mailer: None,
+            search: None,
+            metrics: None,
+        }
+    }
+}
+
+pub struct AppBuilder {
+    conn: DatabaseConnection,
+    config: Config,
+    mailer: Option<Arc<dyn Mailer>>,
+    search: Option<Arc<dyn SearchBackend>>,
+    metrics: Option<Metrics>,
+}
+
+impl AppBuilder {
+    /// Sends mails through `mailer` instead of the one in the config.
+    pub fn mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = Some(mailer);
+        self
+    }
+
+    /// Searches entries with `search` instead of the configured backend.
+    pub fn search(mut self, search: Arc<dyn SearchBackend>) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    /// Counts requests into `metrics`, to serve them on a listener of their
+    /// own with [`Metrics::router`].
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Fails if the SMTP settings in the config can't be used.
+    pub fn build(self) -> service::Result<Router> {
+        let mailer = match (self.mailer, &self.config.mail.smtp_url) {
+            (Some(mailer), _) => mailer,
+            (None, Some(smtp_url)) => {
+                let mail_from = self.config.mail.from.as_deref().unwrap_or_default();
+                Arc::new(SmtpMailer::new(smtp_url, mail_from)?)
+            }
+            (None, None) => Arc::new(LogMailer),
+        };
+
+        let search = match self.search {
+            Some(search) => search,
+            None => search_backend(&self.config, &self.conn),
+        };
+
+        Ok(route::build(AppState {
+            conn: self.conn,
+            config: Arc::new(self.config),
+            mailer,
+            search,
+            metrics: self.metrics.unwrap_or_default(),
+        }))
+    }
+}
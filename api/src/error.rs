@@ -45,11 +45,33 @@ impl IntoErrorResponse for service::Error {
                 "Yetkisiz erişim".to_string(),
                 Some(e.to_string()),
             ),
+            service::Error::Forbidden(e) => (
+                StatusCode::FORBIDDEN,
+                "Erişim izni yok".to_string(),
+                Some(e.to_string()),
+            ),
             service::Error::NotFound(e) => (
                 StatusCode::NOT_FOUND,
                 "Bulunamadı".to_string(),
                 Some(e.to_string()),
             ),
+            service::Error::Conflict(e) => (
+                StatusCode::CONFLICT,
+                "Çakışma".to_string(),
+                Some(e.to_string()),
+            ),
+            service::Error::TooManyRequests(e) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Çok fazla istek".to_string(),
+                Some(e.to_string()),
+            ),
+            service::Error::TooManyAttempts(seconds) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Hesap geçici olarak kilitlendi".to_string(),
+                Some(format!(
+                    "Çok fazla başarısız giriş denemesi. {seconds} saniye sonra tekrar deneyin."
+                )),
+            ),
         };
 
         (status, Json(ErrorBody::new(error, details)))
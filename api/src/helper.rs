@@ -1,15 +1,26 @@
-use axum::http::HeaderMap;
-use service::auth::AuthHeader;
+use std::net::SocketAddr;
 
-use crate::traits::HeaderToken;
+use axum::{extract::ConnectInfo, http::HeaderMap};
 
-pub fn get_user_id_from_headers(
+/// Returns the client address. `X-Forwarded-For` is only honored when the
+/// service runs behind a trusted reverse proxy.
+pub fn client_ip(
     headers: &HeaderMap,
-    auth_from: AuthHeader,
-    jwt_secret: &str,
-) -> Option<i32> {
-    let token = headers.token(auth_from)?;
-    let user_id = service::token::get_id(&token, jwt_secret)?;
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    trust_proxy: bool,
+) -> Option<String> {
+    if trust_proxy {
+        let forwarded = headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map(|ip| ip.trim().to_string())
+            .filter(|ip| !ip.is_empty());
 
-    Some(user_id)
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+
+    connect_info.map(|ConnectInfo(addr)| addr.ip().to_string())
 }
@@ -1,73 +1,229 @@
-use axum::Router;
 use migration::{Migrator, MigratorTrait};
 use service::{
-    auth::AuthHeader,
-    sea_orm::{ConnectOptions, Database, DatabaseConnection},
+    config::{Config, DatabaseConfig, SearchEngine},
+    debe::DebeConfig,
+    mail::Mailer,
+    sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection},
+    search::{
+        backend::{PostgresSearch, SearchBackend},
+        index::TantivySearch,
+    },
+    time::Tz,
 };
-use std::env;
+use std::{env, net::SocketAddr, sync::Arc, time::Duration};
 
+mod app;
 mod error;
 mod helper;
+mod metrics;
 mod middleware;
 mod route;
 mod traits;
 
+pub use app::{App, AppBuilder};
+pub use metrics::Metrics;
+
 #[derive(Clone)]
 struct AppState {
     conn: DatabaseConnection,
-    jwt_secret: String,
-    auth_from: AuthHeader,
+    config: Arc<Config>,
+    mailer: Arc<dyn Mailer>,
+    search: Arc<dyn SearchBackend>,
+    metrics: Metrics,
 }
 
-#[tokio::main]
-async fn start() -> anyhow::Result<()> {
-    env::set_var("RUST_LOG", "debug");
-    tracing_subscriber::fmt::init();
-
+/// Reads the configuration, with the variables in `.env` applied over the
+/// configuration file.
+fn config() -> anyhow::Result<Config> {
     dotenvy::dotenv().ok();
 
-    let host = env::var("HOST").expect("HOST is not set in .env file");
-    let port = env::var("PORT").expect("PORT is not set in .env file");
-    let server_url = format!("{host}:{port}");
+    Ok(Config::load()?)
+}
+
+/// Opens the embedded search index when the search backend is `tantivy`.
+/// Returns `None` when the database searches itself.
+fn search_index(config: &Config) -> service::Result<Option<TantivySearch>> {
+    match config.search.backend {
+        SearchEngine::Tantivy => TantivySearch::open(&config.search.index_path).map(Some),
+        SearchEngine::Postgres => Ok(None),
+    }
+}
+
+/// Picks the configured search backend. Falls back to the database when the
+/// index can't be opened, and fills a new index in the background.
+fn search_backend(config: &Config, conn: &DatabaseConnection) -> Arc<dyn SearchBackend> {
+    match search_index(config) {
+        Ok(Some(index)) => {
+            if index.num_entries() == 0 {
+                let (conn, index) = (conn.clone(), index.clone());
+                tokio::spawn(async move {
+                    match index.rebuild(&conn).await {
+                        Ok(count) => println!("Indexed {count} entries for search."),
+                        Err(err) => println!("Error: {err}"),
+                    }
+                });
+            }
+            Arc::new(index)
+        }
+        Ok(None) => Arc::new(PostgresSearch),
+        Err(err) => {
+            println!("Error: {err}. Searching in the database instead.");
+            Arc::new(PostgresSearch)
+        }
+    }
+}
+
+/// Freezes yesterday's debe. Runs every hour, so that a restart or a failed
+/// run only delays the snapshot.
+async fn debe_job(conn: DatabaseConnection, config: DebeConfig, tz: Tz) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
 
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set in .env file");
-    let db_schema = env::var("DATABASE_SCHEMA").expect("DATABASE_SCHEMA is not set in .env file");
-    let mut opt = ConnectOptions::new(db_url);
-    opt.set_schema_search_path(db_schema);
+    loop {
+        interval.tick().await;
+
+        let date = service::debe::yesterday(tz);
+        match service::debe::create_snapshot(&conn, &config, tz, date).await {
+            Ok(Some(count)) => println!("Created the debe of {date} with {count} entries."),
+            Ok(None) => {}
+            Err(err) => println!("Error: {err}"),
+        }
+    }
+}
+
+/// Forgets who searched once no trending list can count them anymore.
+async fn prune_searchers_job(conn: DatabaseConnection) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = service::search::analytics::prune_searchers(&conn).await {
+            println!("Error: {err}");
+        }
+    }
+}
+
+async fn connect(config: &DatabaseConfig) -> DatabaseConnection {
+    // Tables are created in the schema of the connection, so that instances
+    // can share a database by using schemas of their own. Connections that
+    // were opened before the schema existed can't create anything in it.
+    let setup = Database::connect(&config.url)
+        .await
+        .expect("Database connection failed");
+    setup
+        .execute_unprepared(&format!(
+            r#"CREATE SCHEMA IF NOT EXISTS "{}""#,
+            config.schema
+        ))
+        .await
+        .expect("Database schema could not be created");
+    setup.close().await.ok();
+
+    let mut opt = ConnectOptions::new(&config.url);
+    opt.set_schema_search_path(&config.schema)
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout));
 
     let conn = Database::connect(opt)
         .await
         .expect("Database connection failed");
     Migrator::up(&conn, None).await.unwrap();
 
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET is not set in .env file");
+    conn
+}
 
-    let auth_from = match env::var("AUTH_FROM")
-        .expect("AUTH_FROM is not set in .env file")
-        .as_str()
-    {
-        "cookie" => AuthHeader::Cookie,
-        "authorization" => AuthHeader::Authorization,
-        _ => AuthHeader::Cookie,
-    };
+#[tokio::main]
+async fn start() -> anyhow::Result<()> {
+    env::set_var("RUST_LOG", "debug");
+    tracing_subscriber::fmt::init();
 
-    let state = AppState {
-        conn,
-        jwt_secret,
-        auth_from,
-    };
+    let config = config()?;
+    let server_url = format!("{}:{}", config.server.host, config.server.port);
+
+    let conn = connect(&config.database).await;
 
-    let router = route::build(state);
-    let app = Router::new().nest("/", router);
+    tokio::spawn(debe_job(conn.clone(), config.debe.clone(), config.timezone));
+    tokio::spawn(prune_searchers_job(conn.clone()));
+
+    let metrics = Metrics::new();
+    if let Some(listen) = &config.metrics.listen {
+        let listener = tokio::net::TcpListener::bind(listen).await?;
+        let router = metrics.router(conn.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+    }
+
+    let app = App::builder(conn, config).metrics(metrics).build()?;
     let listener = tokio::net::TcpListener::bind(&server_url).await.unwrap();
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Recomputes the vote and favorite counters of entries, for example after
+/// they were changed by hand in the database.
+#[tokio::main]
+async fn reconcile_counters() -> anyhow::Result<()> {
+    let config = config()?;
+
+    let conn = connect(&config.database).await;
+    let fixed = service::counter::reconcile_counters(&conn).await?;
+    println!("Fixed the counters of {fixed} entries.");
+
+    Ok(())
+}
+
+/// Creates the debe of the given day, or of yesterday, unless it exists.
+#[tokio::main]
+async fn debe_snapshot(date: Option<&str>) -> anyhow::Result<()> {
+    let config = config()?;
+
+    let tz = config.timezone;
+    let date = match date {
+        Some(date) => date.parse()?,
+        None => service::debe::yesterday(tz),
+    };
+
+    let conn = connect(&config.database).await;
+    match service::debe::create_snapshot(&conn, &config.debe, tz, date).await? {
+        Some(count) => println!("Created the debe of {date} with {count} entries."),
+        None => println!("The debe of {date} already exists."),
+    }
+
+    Ok(())
+}
+
+/// Builds the search index again from the database. The server must not be
+/// running, as only one process can have the index open.
+#[tokio::main]
+async fn rebuild_search_index() -> anyhow::Result<()> {
+    let config = config()?;
+
+    let Some(index) = search_index(&config)? else {
+        println!("The search backend is not tantivy; the database keeps its own index.");
+        return Ok(());
+    };
+
+    let conn = connect(&config.database).await;
+    let count = index.rebuild(&conn).await?;
+    println!("Indexed {count} entries for search.");
 
     Ok(())
 }
 
 pub fn main() {
-    let result = start();
+    let args: Vec<String> = env::args().collect();
+    let result = match args.get(1).map(String::as_str) {
+        Some("reconcile-counters") => reconcile_counters(),
+        Some("debe-snapshot") => debe_snapshot(args.get(2).map(String::as_str)),
+        Some("rebuild-search-index") => rebuild_search_index(),
+        _ => start(),
+    };
 
     if let Some(err) = result.err() {
         println!("Error: {err}");
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use axum::{routing::get, Router};
+use entity::prelude::Rating;
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use service::sea_orm::DatabaseConnection;
+
+use crate::route;
+
+/// The counters of one server, rendered in the Prometheus text format at
+/// `/metrics`. Clones share the counters, so that the API and a separate
+/// metrics listener report the same numbers.
+#[derive(Clone)]
+pub struct Metrics(Arc<Inner>);
+
+struct Inner {
+    registry: Registry,
+    requests: IntCounterVec,
+    durations: HistogramVec,
+    connections: IntGaugeVec,
+    max_connections: IntGauge,
+    entries_created: IntCounter,
+    votes: IntCounterVec,
+    logins: IntCounter,
+    failed_logins: IntCounter,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("arpacik".to_string()), None)
+            .expect("the prefix is a valid metric name");
+
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status."),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let durations = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to answer HTTP requests, by route.",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let connections = IntGaugeVec::new(
+            Opts::new(
+                "db_connections",
+                "Open database connections, by whether they are idle or in use.",
+            ),
+            &["state"],
+        )
+        .unwrap();
+        let max_connections = IntGauge::new(
+            "db_max_connections",
+            "The most database connections the pool opens.",
+        )
+        .unwrap();
+        let entries_created = IntCounter::new("entries_created_total", "Entries written.").unwrap();
+        let votes = IntCounterVec::new(
+            Opts::new("votes_total", "Votes given to entries, by rating."),
+            &["rating"],
+        )
+        .unwrap();
+        let logins = IntCounter::new("logins_total", "Successful logins.").unwrap();
+        let failed_logins = IntCounter::new(
+            "failed_logins_total",
+            "Logins refused for wrong credentials, 2FA codes or too many attempts.",
+        )
+        .unwrap();
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 8] = [
+            Box::new(requests.clone()),
+            Box::new(durations.clone()),
+            Box::new(connections.clone()),
+            Box::new(max_connections.clone()),
+            Box::new(entries_created.clone()),
+            Box::new(votes.clone()),
+            Box::new(logins.clone()),
+            Box::new(failed_logins.clone()),
+        ];
+        for collector in collectors {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self(Arc::new(Inner {
+            registry,
+            requests,
+            durations,
+            connections,
+            max_connections,
+            entries_created,
+            votes,
+            logins,
+            failed_logins,
+        }))
+    }
+
+    /// Only serves `/metrics`, without a token. Meant for a listener that
+    /// only Prometheus can reach.
+    pub fn router(&self, conn: DatabaseConnection) -> Router {
+        Router::new()
+            .route("/metrics", get(route::metrics::metrics))
+            .with_state((self.clone(), conn))
+    }
+
+    /// The metrics in the Prometheus text format, with the pool of `conn`
+    /// measured as of now.
+    pub fn render(&self, conn: &DatabaseConnection) -> String {
+        self.observe_pool(conn);
+
+        TextEncoder::new()
+            .encode_to_string(&self.0.registry.gather())
+            .unwrap_or_default()
+    }
+
+    fn observe_pool(&self, conn: &DatabaseConnection) {
+        // Other connections, such as the one of tests that can't reach a
+        // database, have no pool to measure.
+        let DatabaseConnection::SqlxPostgresPoolConnection(_) = conn else {
+            return;
+        };
+
+        let pool = conn.get_postgres_connection_pool();
+        let idle = pool.num_idle() as i64;
+        let open = pool.size() as i64;
+
+        self.0.connections.with_label_values(&["idle"]).set(idle);
+        self.0
+            .connections
+            .with_label_values(&["in_use"])
+            .set(open - idle);
+        self.0
+            .max_connections
+            .set(pool.options().get_max_connections() as i64);
+    }
+
+    pub(crate) fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        self.0
+            .requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.0
+            .durations
+            .with_label_values(&[method, route])
+            .observe(seconds);
+    }
+
+    pub(crate) fn entry_created(&self) {
+        self.0.entries_created.inc();
+    }
+
+    pub(crate) fn voted(&self, rating: Rating) {
+        let rating = match rating {
+            Rating::Up => "up",
+            Rating::Down => "down",
+        };
+        self.0.votes.with_label_values(&[rating]).inc();
+    }
+
+    pub(crate) fn logged_in(&self) {
+        self.0.logins.inc();
+    }
+
+    pub(crate) fn login_failed(&self) {
+        self.0.failed_logins.inc();
+    }
+}
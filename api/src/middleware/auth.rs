@@ -4,33 +4,100 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use service::auth::{authorize, Role};
+use service::{
+    access_token::{authenticate, is_access_token, AccessTokenOwner},
+    dto::{access_token::Scope, role::Permission},
+    role::has_permission,
+    token::{get_id, has_two_factor, validate_token},
+};
 
-use crate::{traits::HeaderToken, AppState};
+use crate::{
+    traits::{HeaderToken, HeadersAuth},
+    AppState,
+};
 
-pub async fn auth(
+/// The user a request is made on behalf of. Inserted into the request
+/// extensions by the auth middlewares.
+#[derive(Debug, Clone, Copy)]
+pub struct CurrentUser {
+    pub id: i32,
+}
+
+enum Credential {
+    Session(String),
+    AccessToken(AccessTokenOwner),
+}
+
+/// Reads the credential of the request. Personal access tokens are only
+/// accepted as bearer tokens, regardless of where sessions are read from.
+async fn credential(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<Credential>, StatusCode> {
+    let bearer = headers
+        .authorization()
+        .and_then(|authorization| authorization.split_whitespace().last().map(str::to_string))
+        .filter(|token| is_access_token(token));
+
+    if let Some(token) = bearer {
+        return match authenticate(&state.conn, &token).await {
+            Ok(Some(owner)) => Ok(Some(Credential::AccessToken(owner))),
+            Ok(None) => Err(StatusCode::UNAUTHORIZED),
+            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        };
+    }
+
+    Ok(headers
+        .token(state.config.auth.from)
+        .filter(|token| validate_token(token, &state.config.auth.jwt_secret))
+        .map(Credential::Session))
+}
+
+fn session_user(token: &str, jwt_secret: &str) -> Result<CurrentUser, StatusCode> {
+    get_id(token, jwt_secret)
+        .map(|id| CurrentUser { id })
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+/// Resolves the user if the request carries a valid credential, but lets
+/// anonymous requests through. Used on public routes. Access tokens without
+/// the read scope are treated as anonymous, as anyone may read these routes.
+pub async fn identify(
     State(state): State<AppState>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    match headers.token(state.auth_from) {
-        Some(token) if authorize(&token, &state.jwt_secret, Role::User) => {
-            let response = next.run(request).await;
-            Ok(response)
+    let user = match credential(&state, &headers).await? {
+        Some(Credential::Session(token)) => {
+            session_user(&token, &state.config.auth.jwt_secret).ok()
         }
-        _ => Err(StatusCode::UNAUTHORIZED),
+        Some(Credential::AccessToken(owner)) if owner.has_scope(Scope::Read) => {
+            Some(CurrentUser { id: owner.user_id })
+        }
+        Some(Credential::AccessToken(_)) | None => None,
+    };
+
+    if let Some(user) = user {
+        request.extensions_mut().insert(user);
     }
+
+    Ok(next.run(request).await)
 }
 
-pub async fn _auth_moderator(
+/// Only accepts sessions. Used for account management, so that a leaked
+/// access token can't be used to take over the account.
+pub async fn auth(
     State(state): State<AppState>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    match headers.token(state.auth_from) {
-        Some(token) if authorize(&token, &state.jwt_secret, Role::Moderator) => {
+    match headers.token(state.config.auth.from) {
+        Some(token) if validate_token(&token, &state.config.auth.jwt_secret) => {
+            let user = session_user(&token, &state.config.auth.jwt_secret)?;
+            request.extensions_mut().insert(user);
+
             let response = next.run(request).await;
             Ok(response)
         }
@@ -38,35 +105,67 @@ pub async fn _auth_moderator(
     }
 }
 
-pub async fn _auth_admin(
-    State(state): State<AppState>,
+/// Accepts sessions and access tokens that were granted `scope`.
+pub async fn auth_scope(
+    State((state, scope)): State<(AppState, Scope)>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    match headers.token(state.auth_from) {
-        Some(token) if authorize(&token, &state.jwt_secret, Role::Admin) => {
-            let response = next.run(request).await;
-            Ok(response)
+    let user = match credential(&state, &headers).await? {
+        Some(Credential::Session(token)) => session_user(&token, &state.config.auth.jwt_secret)?,
+        Some(Credential::AccessToken(owner)) if owner.has_scope(scope) => {
+            CurrentUser { id: owner.user_id }
         }
-        _ => Err(StatusCode::UNAUTHORIZED),
-    }
+        Some(Credential::AccessToken(_)) => return Err(StatusCode::FORBIDDEN),
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    request.extensions_mut().insert(user);
+
+    let response = next.run(request).await;
+    Ok(response)
 }
 
-pub async fn auth_crew(
-    State(state): State<AppState>,
+/// Accepts sessions and access tokens with the moderation scope of users
+/// that were granted `permission` through one of their roles. When the crew
+/// must use 2FA, sessions have to have passed it and token owners have to have
+/// set it up.
+pub async fn auth_permission(
+    State((state, permission)): State<(AppState, Permission)>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    match headers.token(state.auth_from) {
-        Some(token)
-            if authorize(&token, &state.jwt_secret, Role::Moderator)
-                || authorize(&token, &state.jwt_secret, Role::Admin) =>
-        {
-            let response = next.run(request).await;
-            Ok(response)
+    let user = match credential(&state, &headers).await? {
+        Some(Credential::Session(token)) => {
+            if state.config.auth.require_crew_two_factor
+                && !has_two_factor(&token, &state.config.auth.jwt_secret)
+            {
+                return Err(StatusCode::FORBIDDEN);
+            }
+
+            session_user(&token, &state.config.auth.jwt_secret)?
         }
-        _ => Err(StatusCode::UNAUTHORIZED),
+        Some(Credential::AccessToken(owner)) if owner.has_scope(Scope::Moderation) => {
+            if state.config.auth.require_crew_two_factor && !owner.has_two_factor {
+                return Err(StatusCode::FORBIDDEN);
+            }
+
+            CurrentUser { id: owner.user_id }
+        }
+        Some(Credential::AccessToken(_)) => return Err(StatusCode::FORBIDDEN),
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    match has_permission(&state.conn, user.id, permission).await {
+        Ok(true) => (),
+        Ok(false) => return Err(StatusCode::FORBIDDEN),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
+
+    request.extensions_mut().insert(user);
+
+    let response = next.run(request).await;
+    Ok(response)
 }
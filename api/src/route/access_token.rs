@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use service::{
+    dto::access_token::{AccessTokenDto, CreateAccessTokenRequest, CreateAccessTokenResponse},
+    token::has_two_factor,
+};
+
+use crate::{
+    error::{ErrorBody, IntoErrorResponse},
+    middleware::auth::CurrentUser,
+    traits::HeaderToken,
+    AppState,
+};
+
+pub async fn create_access_token(
+    state: State<AppState>,
+    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+    json_data: Json<CreateAccessTokenRequest>,
+) -> Result<(StatusCode, Json<CreateAccessTokenResponse>), (StatusCode, Json<ErrorBody>)> {
+    let two_factor = headers
+        .token(state.config.auth.from)
+        .is_some_and(|token| has_two_factor(&token, &state.config.auth.jwt_secret));
+
+    match service::access_token::create_access_token(
+        &state.conn,
+        &state.config.auth,
+        user_id,
+        two_factor,
+        json_data.0,
+    )
+    .await
+    {
+        Ok(r) => Ok((StatusCode::CREATED, Json(r))),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn access_tokens(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+) -> Result<Json<Vec<AccessTokenDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::access_token::list_access_tokens(&state.conn, user_id).await {
+        Ok(tokens) => Ok(Json(tokens)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn revoke_access_token(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::access_token::revoke_access_token(&state.conn, user_id, id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
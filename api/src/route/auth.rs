@@ -1,17 +1,36 @@
+use std::net::SocketAddr;
+
 use crate::error::{ErrorBody, IntoErrorResponse};
+use crate::helper::client_ip;
+use crate::middleware::auth::CurrentUser;
 use crate::traits::HeaderToken;
 use crate::AppState;
 use axum::http::header::SET_COOKIE;
 use axum::http::HeaderMap;
-use axum::{extract::State, http::StatusCode, Json};
-use service::dto::auth::{LoginRequest, LoginResponse, RegisterRequest};
+use axum::response::{IntoResponse, Response};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use service::dto::auth::{
+    LoginRequest, LoginResponse, LoginResult, RegisterRequest, VerifyEmailRequest,
+};
+use service::dto::two_factor::TwoFactorLoginRequest;
 use service::Error;
 
 pub async fn register(
     state: State<AppState>,
     json_data: Json<RegisterRequest>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    match service::auth::register(&state.conn, json_data.0).await {
+    match service::auth::register(
+        &state.conn,
+        state.mailer.as_ref(),
+        &state.config.mail,
+        json_data.0,
+    )
+    .await
+    {
         Ok(_) => Ok(StatusCode::CREATED),
         Err(e) => Err(e.into_error_response()),
     }
@@ -19,24 +38,75 @@ pub async fn register(
 
 pub async fn login(
     state: State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     json_data: Json<LoginRequest>,
+) -> Result<Response, (StatusCode, Json<ErrorBody>)> {
+    let ip = client_ip(&headers, connect_info, state.config.server.trust_proxy);
+    let result =
+        service::auth::login(&state.conn, &state.config.auth, json_data.0, ip.as_deref()).await;
+    count_login(&state, &result, |result| {
+        matches!(result, LoginResult::Success(_))
+    });
+
+    match result {
+        Ok(LoginResult::Success(resp)) => Ok(login_response(resp).into_response()),
+        Ok(LoginResult::TwoFactorRequired(challenge)) => Ok(Json(challenge).into_response()),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn login_two_factor(
+    state: State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    json_data: Json<TwoFactorLoginRequest>,
 ) -> Result<(HeaderMap, Json<LoginResponse>), (StatusCode, Json<ErrorBody>)> {
-    match service::auth::login(&state.conn, json_data.0).await {
-        Ok(resp) => {
-            let mut headers = HeaderMap::new();
-            headers.insert(SET_COOKIE, resp.cookie.to_string().parse().unwrap());
-            Ok((headers, Json(resp)))
-        }
+    let ip = client_ip(&headers, connect_info, state.config.server.trust_proxy);
+    let result = service::auth::login_two_factor(
+        &state.conn,
+        &state.config.auth,
+        json_data.0,
+        ip.as_deref(),
+    )
+    .await;
+    count_login(&state, &result, |_| true);
+
+    match result {
+        Ok(resp) => Ok(login_response(resp)),
         Err(e) => Err(e.into_error_response()),
     }
 }
 
+/// Counts logins that issued a session, and those refused for the
+/// credentials rather than for a malformed request or a server error. The
+/// password step of a login with 2FA counts as neither.
+fn count_login<T>(state: &AppState, result: &Result<T, Error>, has_session: fn(&T) -> bool) {
+    match result {
+        Ok(result) if has_session(result) => state.metrics.logged_in(),
+        Ok(_) => {}
+        Err(
+            Error::InvalidCredentials
+            | Error::InvalidToken
+            | Error::Unauthorized(_)
+            | Error::TooManyAttempts(_),
+        ) => state.metrics.login_failed(),
+        Err(_) => {}
+    }
+}
+
+fn login_response(resp: LoginResponse) -> (HeaderMap, Json<LoginResponse>) {
+    let mut headers = HeaderMap::new();
+    headers.insert(SET_COOKIE, resp.cookie.to_string().parse().unwrap());
+    (headers, Json(resp))
+}
+
 pub async fn logout(
     state: State<AppState>,
     headers: HeaderMap,
 ) -> Result<(HeaderMap, StatusCode), (StatusCode, Json<ErrorBody>)> {
     let token = headers
-        .token(state.auth_from)
+        .token(state.config.auth.from)
         .ok_or(Error::Unauthorized("Geçersiz token".to_string()).into_error_response())?;
     match service::auth::logout(&state.conn, &token).await {
         Ok(cookie) => {
@@ -47,3 +117,30 @@ pub async fn logout(
         Err(e) => Err(e.into_error_response()),
     }
 }
+
+pub async fn verify_email(
+    state: State<AppState>,
+    json_data: Json<VerifyEmailRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::verification::verify_email(&state.conn, &json_data.token).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn resend_verification(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::verification::resend_verification(
+        &state.conn,
+        state.mailer.as_ref(),
+        &state.config.mail,
+        user_id,
+    )
+    .await
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
@@ -1,29 +1,24 @@
 use axum::{
     extract::{Query, State},
-    http::{HeaderMap, StatusCode},
-    Json,
+    http::StatusCode,
+    Extension, Json,
 };
-use service::{
-    dto::{
-        entry::EntryDto,
-        pagination::{PaginationQuery, PaginationResponse},
-    },
-    Error,
+use service::dto::{
+    entry::EntryDto,
+    pagination::{CursorPaginationQuery, PaginationResponse},
 };
 
 use crate::{
     error::{ErrorBody, IntoErrorResponse},
-    helper::get_user_id_from_headers,
+    middleware::auth::CurrentUser,
     AppState,
 };
 
 pub async fn get_user_bin(
     state: State<AppState>,
-    headers: HeaderMap,
-    query: Query<PaginationQuery>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+    query: Query<CursorPaginationQuery>,
 ) -> Result<Json<PaginationResponse<EntryDto>>, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
     match service::bin::get_user_bin(&state.conn, user_id, query.0).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
@@ -32,10 +27,8 @@ pub async fn get_user_bin(
 
 pub async fn empty_user_bin(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
     match service::bin::empty_user_bin(&state.conn, user_id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
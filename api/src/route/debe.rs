@@ -0,0 +1,25 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use service::dto::debe::{DebeDto, DebeQuery};
+
+use crate::{
+    error::{ErrorBody, IntoErrorResponse},
+    middleware::auth::CurrentUser,
+    AppState,
+};
+
+pub async fn debe(
+    state: State<AppState>,
+    user: Option<Extension<CurrentUser>>,
+    query: Query<DebeQuery>,
+) -> Result<Json<DebeDto>, (StatusCode, Json<ErrorBody>)> {
+    let user_id = user.map(|Extension(user)| user.id);
+    let tz = query.tz.unwrap_or(state.config.timezone);
+    match service::debe::get_debe(&state.conn, tz, query.0.date, user_id).await {
+        Ok(debe) => Ok(Json(debe)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
@@ -1,47 +1,53 @@
 use axum::{
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    Json,
+    http::StatusCode,
+    Extension, Json,
 };
 use entity::prelude::Rating;
-use service::{
-    dto::{
-        entry::{
-            CreateEntryRequest, CreateEntryResponse, EntryDto, GetTitleEntriesQuery,
-            UpdateEntryRequest,
-        },
-        pagination::{PaginationQuery, PaginationResponse},
+use service::dto::{
+    entry::{
+        CreateEntryRequest, CreateEntryResponse, EntryDto, EntryPositionDto, EntryPositionQuery,
+        GetTitleEntriesQuery, UpdateEntryRequest,
     },
-    Error,
+    pagination::{CursorPaginationQuery, PaginationResponse},
 };
 
 use crate::{
     error::{ErrorBody, IntoErrorResponse},
-    helper::get_user_id_from_headers,
+    middleware::auth::CurrentUser,
     AppState,
 };
 
 pub async fn create_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     json_data: Json<CreateEntryRequest>,
 ) -> Result<Json<CreateEntryResponse>, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::create_entry(&state.conn, user_id, json_data.0).await {
-        Ok(r) => Ok(Json(r)),
+    match service::entry::create_entry(
+        &state.conn,
+        state.search.as_ref(),
+        state.config.timezone,
+        user_id,
+        json_data.0,
+        state.config.auth.require_email_verification,
+    )
+    .await
+    {
+        Ok(r) => {
+            state.metrics.entry_created();
+            Ok(Json(r))
+        }
         Err(e) => Err(e.into_error_response()),
     }
 }
 
 pub async fn delete_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::delete_entry(&state.conn, user_id, id, false).await {
+    match service::entry::delete_entry(&state.conn, state.search.as_ref(), user_id, id, false).await
+    {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -49,12 +55,11 @@ pub async fn delete_entry(
 
 pub async fn soft_delete_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::delete_entry(&state.conn, user_id, id, true).await {
+    match service::entry::delete_entry(&state.conn, state.search.as_ref(), user_id, id, true).await
+    {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -62,12 +67,10 @@ pub async fn soft_delete_entry(
 
 pub async fn recover_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::recover_entry(&state.conn, user_id, id).await {
+    match service::entry::recover_entry(&state.conn, state.search.as_ref(), user_id, id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -75,23 +78,34 @@ pub async fn recover_entry(
 
 pub async fn get_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    user: Option<Extension<CurrentUser>>,
     Path(id): Path<i32>,
 ) -> Result<Json<service::dto::entry::EntryDto>, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret);
+    let user_id = user.map(|Extension(user)| user.id);
     match service::entry::get_entry(&state.conn, id, user_id).await {
         Ok(entry) => Ok(Json(entry)),
         Err(e) => Err(e.into_error_response()),
     }
 }
 
+pub async fn get_entry_position(
+    state: State<AppState>,
+    Path(id): Path<i32>,
+    query: Query<EntryPositionQuery>,
+) -> Result<Json<EntryPositionDto>, (StatusCode, Json<ErrorBody>)> {
+    match service::entry::get_entry_position(&state.conn, id, query.0).await {
+        Ok(position) => Ok(Json(position)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
 pub async fn get_title_entries_by_name(
     state: State<AppState>,
-    headers: HeaderMap,
+    user: Option<Extension<CurrentUser>>,
     Path(name): Path<String>,
     query: Query<GetTitleEntriesQuery>,
 ) -> Result<Json<PaginationResponse<EntryDto>>, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret);
+    let user_id = user.map(|Extension(user)| user.id);
     match service::entry::get_title_entries_by_name(&state.conn, &name, query.0, user_id).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
@@ -100,11 +114,11 @@ pub async fn get_title_entries_by_name(
 
 pub async fn get_user_entries(
     state: State<AppState>,
-    headers: HeaderMap,
+    user: Option<Extension<CurrentUser>>,
     Path(nickname): Path<String>,
-    query: Query<PaginationQuery>,
+    query: Query<CursorPaginationQuery>,
 ) -> Result<Json<PaginationResponse<EntryDto>>, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret);
+    let user_id = user.map(|Extension(user)| user.id);
     match service::entry::get_user_entries(&state.conn, nickname, query.0, user_id).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
@@ -113,13 +127,20 @@ pub async fn get_user_entries(
 
 pub async fn update_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
     json_data: Json<UpdateEntryRequest>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::update_entry(&state.conn, user_id, id, json_data.0).await {
+    match service::entry::update_entry(
+        &state.conn,
+        state.search.as_ref(),
+        state.config.timezone,
+        user_id,
+        id,
+        json_data.0,
+    )
+    .await
+    {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -129,7 +150,7 @@ pub async fn migrate_entry(
     state: State<AppState>,
     Path((id, title_id)): Path<(i32, i32)>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    match service::entry::migrate_entry(&state.conn, id, title_id).await {
+    match service::entry::migrate_entry(&state.conn, state.search.as_ref(), id, title_id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -137,12 +158,18 @@ pub async fn migrate_entry(
 
 pub async fn favorite_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::favorite_entry(&state.conn, user_id, id).await {
+    match service::entry::favorite_entry(
+        &state.conn,
+        state.search.as_ref(),
+        state.config.timezone,
+        user_id,
+        id,
+    )
+    .await
+    {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -150,12 +177,10 @@ pub async fn favorite_entry(
 
 pub async fn unfavorite_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::unfavorite_entry(&state.conn, user_id, id).await {
+    match service::entry::unfavorite_entry(&state.conn, state.search.as_ref(), user_id, id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
@@ -163,26 +188,45 @@ pub async fn unfavorite_entry(
 
 pub async fn vote_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path((id, rating)): Path<(i32, Rating)>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::vote_entry(&state.conn, user_id, id, rating).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
+    match service::entry::vote_entry(
+        &state.conn,
+        state.search.as_ref(),
+        state.config.timezone,
+        user_id,
+        id,
+        rating,
+    )
+    .await
+    {
+        Ok(_) => {
+            state.metrics.voted(rating);
+            Ok(StatusCode::NO_CONTENT)
+        }
         Err(e) => Err(e.into_error_response()),
     }
 }
 
 pub async fn unvote_entry(
     state: State<AppState>,
-    headers: HeaderMap,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
     Path(id): Path<i32>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret)
-        .ok_or(Error::Unauthorized("Geçersiz çerez".to_string()).into_error_response())?;
-    match service::entry::unvote(&state.conn, user_id, id).await {
+    match service::entry::unvote(&state.conn, state.search.as_ref(), user_id, id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e.into_error_response()),
     }
 }
+
+pub async fn get_user_favorites(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+    query: Query<CursorPaginationQuery>,
+) -> Result<Json<PaginationResponse<EntryDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::entry::get_user_favorites(&state.conn, user_id, query.0).await {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
@@ -1,21 +1,17 @@
-use axum::{
-    extract::State,
-    http::{HeaderMap, StatusCode},
-    Json,
-};
+use axum::{extract::State, http::StatusCode, Extension, Json};
 use service::dto::entry::EntryDto;
 
 use crate::{
     error::{ErrorBody, IntoErrorResponse},
-    helper::get_user_id_from_headers,
+    middleware::auth::CurrentUser,
     AppState,
 };
 
 pub async fn feed(
     state: State<AppState>,
-    headers: HeaderMap,
+    user: Option<Extension<CurrentUser>>,
 ) -> Result<Json<Vec<EntryDto>>, (StatusCode, Json<ErrorBody>)> {
-    let user_id = get_user_id_from_headers(&headers, state.auth_from, &state.jwt_secret);
+    let user_id = user.map(|Extension(user)| user.id);
     match service::feed::feed(&state.conn, user_id).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, Json};
+use migration::{Migrator, MigratorTrait};
+use service::dto::health::{CheckDto, HealthDto, HealthStatus, ReadinessDto, VersionDto};
+
+use crate::AppState;
+
+/// How long each readiness check may take before it counts as failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Answers as long as the process serves requests; doesn't touch the
+/// database, so that a database outage doesn't get the server restarted.
+pub async fn healthz() -> Json<HealthDto> {
+    Json(HealthDto {
+        status: HealthStatus::Ok,
+    })
+}
+
+fn check(result: Result<(), String>) -> CheckDto {
+    match result {
+        Ok(()) => CheckDto {
+            status: HealthStatus::Ok,
+            details: None,
+        },
+        Err(details) => CheckDto {
+            status: HealthStatus::Unavailable,
+            details: Some(details),
+        },
+    }
+}
+
+fn timed_out() -> String {
+    format!("{} saniye içinde yanıt alınamadı.", CHECK_TIMEOUT.as_secs())
+}
+
+async fn check_database(state: &AppState) -> Result<(), String> {
+    match tokio::time::timeout(CHECK_TIMEOUT, state.conn.ping()).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(format!("Veritabanına bağlanılamadı: {e}")),
+        Err(_) => Err(timed_out()),
+    }
+}
+
+async fn check_migrations(state: &AppState) -> Result<(), String> {
+    let pending =
+        tokio::time::timeout(CHECK_TIMEOUT, Migrator::get_pending_migrations(&state.conn))
+            .await
+            .map_err(|_| timed_out())?
+            .map_err(|e| format!("Migration'lar okunamadı: {e}"))?;
+
+    match pending.is_empty() {
+        true => Ok(()),
+        false => Err(format!(
+            "Bekleyen migration'lar: {}",
+            pending
+                .iter()
+                .map(|migration| migration.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Answers `503` until the database can be reached and is migrated, so that
+/// no traffic is sent to the server before it can serve it.
+pub async fn readyz(state: State<AppState>) -> (StatusCode, Json<ReadinessDto>) {
+    let database = check(check_database(&state).await);
+    let migrations = match database.status {
+        HealthStatus::Ok => check(check_migrations(&state).await),
+        HealthStatus::Unavailable => check(Err("Veritabanı kullanılamıyor.".to_string())),
+    };
+
+    let (code, status) = match migrations.status {
+        HealthStatus::Ok => (StatusCode::OK, HealthStatus::Ok),
+        HealthStatus::Unavailable => (StatusCode::SERVICE_UNAVAILABLE, HealthStatus::Unavailable),
+    };
+
+    (
+        code,
+        Json(ReadinessDto {
+            status,
+            database,
+            migrations,
+        }),
+    )
+}
+
+pub async fn version() -> Json<VersionDto> {
+    Json(VersionDto {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: option_env!("GIT_HASH").map(str::to_string),
+    })
+}
@@ -3,6 +3,11 @@ use axum::{extract::State, http::StatusCode};
 use crate::AppState;
 
 pub async fn hello_world(state: State<AppState>) -> (StatusCode, &'static str) {
-    state.conn.ping().await.unwrap();
-    (StatusCode::OK, "Hello, World!")
+    match state.conn.ping().await {
+        Ok(()) => (StatusCode::OK, "Hello, World!"),
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Veritabanına bağlanılamadı.",
+        ),
+    }
 }
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use service::dto::{
+    login_attempt::{LoginAttemptDto, LoginAttemptsQuery},
+    pagination::PaginationResponse,
+};
+
+use crate::{
+    error::{ErrorBody, IntoErrorResponse},
+    AppState,
+};
+
+pub async fn login_attempts(
+    state: State<AppState>,
+    query: Query<LoginAttemptsQuery>,
+) -> Result<Json<PaginationResponse<LoginAttemptDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::login_attempt::login_attempts(&state.conn, query.0).await {
+        Ok(attempts) => Ok(Json(attempts)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
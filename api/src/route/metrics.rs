@@ -0,0 +1,42 @@
+use axum::{
+    extract::State,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use service::sea_orm::DatabaseConnection;
+
+use crate::{metrics::Metrics, traits::HeadersAuth, AppState};
+
+fn render(metrics: &Metrics, conn: &DatabaseConnection) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics.render(conn),
+    )
+}
+
+/// Served on the metrics listener, which only Prometheus should reach.
+pub async fn metrics(
+    State((metrics, conn)): State<(Metrics, DatabaseConnection)>,
+) -> impl IntoResponse {
+    render(&metrics, &conn)
+}
+
+/// Served on the API listener to requests carrying the metrics token as a
+/// bearer token.
+pub async fn metrics_with_token(
+    state: State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    let expected = state.config.metrics.token.as_deref();
+    let given = headers.authorization();
+    let given = given
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match (expected, given) {
+        (Some(expected), Some(given)) if expected == given => {
+            Ok(render(&state.metrics, &state.conn))
+        }
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
@@ -1,30 +1,43 @@
 use crate::AppState;
 use axum::{
     middleware,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
-use tower_http::cors::CorsLayer;
+use service::dto::{access_token::Scope, role::Permission};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
+pub mod access_token;
 pub mod auth;
 pub mod bin;
+pub mod debe;
 pub mod entry;
 pub mod feed;
+pub mod health;
 pub mod hello;
+pub mod login_attempt;
+pub mod metrics;
+pub mod role;
 pub mod search;
 pub mod statistics;
 pub mod title;
 pub mod today;
 pub mod trends;
+pub mod two_factor;
+pub mod user;
 
 pub fn build(state: AppState) -> Router {
     let prefix = "/api/v1";
+    let cors = cors(&state.config.server.cors_origins);
 
     let public = Router::new()
         .route("/hello", get(hello::hello_world))
         .route("/auth/register", post(auth::register))
         .route("/auth/login", post(auth::login))
+        .route("/auth/verify-email", post(auth::verify_email))
+        .route("/auth/login/2fa", post(auth::login_two_factor))
         .route("/entries/:id", get(entry::get_entry))
+        .route("/entries/:id/position", get(entry::get_entry_position))
         .route("/users/:id/entries", get(entry::get_user_entries))
         .route(
             "/titles/:name/entries",
@@ -33,46 +46,160 @@ pub fn build(state: AppState) -> Router {
         .route("/today", get(today::today))
         .route("/trends", get(trends::trends))
         .route("/feed", get(feed::feed))
-        .route("/search", get(search::search));
+        .route("/debe", get(debe::debe))
+        .route("/search", get(search::search))
+        .route("/search/entries", get(search::search_entries))
+        .route("/search/titles", get(search::autocomplete_titles))
+        .route("/search/titles/did-you-mean", get(search::did_you_mean))
+        .route("/search/trending", get(search::trending))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::identify,
+        ));
 
+    // Account management is limited to sessions.
     let user = Router::new()
         .route("/auth/logout", post(auth::logout))
+        .route("/auth/resend-verification", post(auth::resend_verification))
+        .route("/self/2fa/enroll", post(two_factor::enroll))
+        .route("/self/2fa/confirm", post(two_factor::confirm))
+        .route("/self/2fa/disable", post(two_factor::disable))
+        .route("/self/tokens", get(access_token::access_tokens))
+        .route("/self/tokens", post(access_token::create_access_token))
+        .route(
+            "/self/tokens/:id",
+            delete(access_token::revoke_access_token),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth,
+        ));
+
+    let read = Router::new()
+        .route("/self/bin", get(bin::get_user_bin))
+        .route("/self/favorites", get(entry::get_user_favorites))
+        .route_layer(middleware::from_fn_with_state(
+            (state.clone(), Scope::Read),
+            crate::middleware::auth::auth_scope,
+        ));
+
+    let write_entries = Router::new()
         .route("/entries", post(entry::create_entry))
         .route("/entries/:id", delete(entry::delete_entry))
         .route("/entries/:id/recover", patch(entry::recover_entry))
         .route("/entries/:id/soft-delete", delete(entry::soft_delete_entry))
         .route("/entries/:id", patch(entry::update_entry))
+        .route("/self/bin", delete(bin::empty_user_bin))
+        .route_layer(middleware::from_fn_with_state(
+            (state.clone(), Scope::WriteEntries),
+            crate::middleware::auth::auth_scope,
+        ));
+
+    let vote = Router::new()
         .route("/entries/:id/favorite", post(entry::favorite_entry))
         .route("/entries/:id/unfavorite", post(entry::unfavorite_entry))
         .route("/entries/:id/vote/:rating", post(entry::vote_entry))
         .route("/entries/:id/unvote", post(entry::unvote_entry))
-        .route("/self/bin", get(bin::get_user_bin))
-        .route("/self/bin", delete(bin::empty_user_bin))
         .route_layer(middleware::from_fn_with_state(
-            state.clone(),
-            crate::middleware::auth::auth,
+            (state.clone(), Scope::Vote),
+            crate::middleware::auth::auth_scope,
         ));
 
+    // Each crew route names the permission it needs; which roles carry that
+    // permission is up to the admins.
+    let require = |permission| {
+        middleware::from_fn_with_state(
+            (state.clone(), permission),
+            crate::middleware::auth::auth_permission,
+        )
+    };
+
     let crew = Router::new()
         .route(
             "/entries/:id/to-title/:title_id",
-            patch(entry::migrate_entry),
+            patch(entry::migrate_entry).route_layer(require(Permission::MigrateEntry)),
         )
         .route(
             "/titles/:id/set-visibility/:is_visible",
-            patch(title::set_title_visibility),
+            patch(title::set_title_visibility).route_layer(require(Permission::HideTitle)),
         )
-        .route("/statistics", get(statistics::statistics))
-        .layer(middleware::from_fn_with_state(
-            state.clone(),
-            crate::middleware::auth::auth_crew,
-        ));
+        .route(
+            "/statistics",
+            get(statistics::statistics).route_layer(require(Permission::ViewStatistics)),
+        )
+        .route(
+            "/search/zero-results",
+            get(search::zero_result_searches).route_layer(require(Permission::ViewStatistics)),
+        )
+        .route(
+            "/login-attempts",
+            get(login_attempt::login_attempts).route_layer(require(Permission::ViewLoginAttempts)),
+        )
+        .route(
+            "/users/:id/silence",
+            put(user::silence_user)
+                .delete(user::unsilence_user)
+                .route_layer(require(Permission::SilenceUser)),
+        )
+        .route(
+            "/users/:id/set-bot/:is_bot",
+            patch(user::set_bot).route_layer(require(Permission::ManageBots)),
+        )
+        .route(
+            "/roles",
+            get(role::roles)
+                .post(role::create_role)
+                .route_layer(require(Permission::ManageRoles)),
+        )
+        .route(
+            "/roles/:name",
+            put(role::update_role).route_layer(require(Permission::ManageRoles)),
+        )
+        .route(
+            "/users/:id/roles/:name",
+            put(role::grant_role)
+                .delete(role::revoke_role)
+                .route_layer(require(Permission::ManageRoles)),
+        );
+
+    // Probes of container orchestrators, outside the versioned API.
+    let probes = Router::new()
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/version", get(health::version));
+
+    // Without a token, metrics are only served on their own listener.
+    let metrics = match state.config.metrics.token {
+        Some(_) => Router::new().route("/metrics", get(metrics::metrics_with_token)),
+        None => Router::new(),
+    };
 
     Router::new()
+        .merge(probes)
+        .merge(metrics)
         .nest(prefix, public)
         .nest(prefix, user)
+        .nest(prefix, read)
+        .nest(prefix, write_entries)
+        .nest(prefix, vote)
         .nest(prefix, crew)
-        // TODO: Remove this once we have a proper frontend
-        .layer(CorsLayer::permissive())
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            crate::middleware::metrics::track,
+        ))
+        .layer(cors)
         .with_state(state)
 }
+
+/// Lets browsers call the API from the configured origins, or from any
+/// origin when none are configured.
+fn cors(origins: &[String]) -> CorsLayer {
+    let layer = CorsLayer::permissive();
+    if origins.is_empty() {
+        return layer;
+    }
+
+    layer.allow_origin(AllowOrigin::list(
+        origins.iter().filter_map(|origin| origin.parse().ok()),
+    ))
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use service::dto::role::{CreateRoleRequest, RoleDto, UpdateRoleRequest};
+
+use crate::{
+    error::{ErrorBody, IntoErrorResponse},
+    middleware::auth::CurrentUser,
+    AppState,
+};
+
+pub async fn roles(
+    state: State<AppState>,
+) -> Result<Json<Vec<RoleDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::role::roles(&state.conn).await {
+        Ok(roles) => Ok(Json(roles)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn create_role(
+    state: State<AppState>,
+    json_data: Json<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<RoleDto>), (StatusCode, Json<ErrorBody>)> {
+    match service::role::create_role(&state.conn, json_data.0).await {
+        Ok(role) => Ok((StatusCode::CREATED, Json(role))),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn update_role(
+    state: State<AppState>,
+    Path(name): Path<String>,
+    json_data: Json<UpdateRoleRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::role::update_role(&state.conn, &name, json_data.0).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn grant_role(
+    state: State<AppState>,
+    Extension(CurrentUser { id: granted_by }): Extension<CurrentUser>,
+    Path((id, name)): Path<(i32, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::role::grant_role(&state.conn, id, &name, granted_by).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn revoke_role(
+    state: State<AppState>,
+    Path((id, name)): Path<(i32, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::role::revoke_role(&state.conn, id, &name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
@@ -1,21 +1,111 @@
+use std::net::SocketAddr;
+
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    Json,
+    extract::{ConnectInfo, Query, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use service::{
+    dto::{
+        pagination::PaginationResponse,
+        search::{
+            DidYouMeanQuery, EntrySearchHitDto, EntrySearchQuery, SearchItem, SearchQuery,
+            TitleAutocompleteQuery, TitleSuggestionDto, TrendingSearchDto, TrendingSearchQuery,
+            ZeroResultSearchDto, ZeroResultSearchQuery,
+        },
+    },
+    search::analytics::Searcher,
 };
-use service::dto::search::{SearchItem, SearchQuery};
 
 use crate::{
     error::{ErrorBody, IntoErrorResponse},
+    helper::client_ip,
+    middleware::auth::CurrentUser,
     AppState,
 };
 
 pub async fn search(
     state: State<AppState>,
+    user: Option<Extension<CurrentUser>>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     request: Query<SearchQuery>,
 ) -> Result<Json<Vec<SearchItem>>, (StatusCode, Json<ErrorBody>)> {
-    match service::search::search(&state.conn, request.0).await {
+    let ip = client_ip(&headers, connect_info, state.config.server.trust_proxy);
+    let searcher = Searcher::new(
+        &state.config.auth.jwt_secret,
+        user.map(|Extension(user)| user.id),
+        ip.as_deref(),
+    );
+
+    match service::search::search(&state.conn, request.0, searcher).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
     }
 }
+
+pub async fn search_entries(
+    state: State<AppState>,
+    user: Option<Extension<CurrentUser>>,
+    query: Query<EntrySearchQuery>,
+) -> Result<Json<PaginationResponse<EntrySearchHitDto>>, (StatusCode, Json<ErrorBody>)> {
+    let user_id = user.map(|Extension(user)| user.id);
+    match service::search::search_entries(
+        &state.conn,
+        state.search.as_ref(),
+        state.config.timezone,
+        query.0,
+        user_id,
+    )
+    .await
+    {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn autocomplete_titles(
+    state: State<AppState>,
+    query: Query<TitleAutocompleteQuery>,
+) -> Result<Json<Vec<TitleSuggestionDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::search::title::autocomplete(&state.conn, query.0).await {
+        Ok(titles) => Ok(Json(titles)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn did_you_mean(
+    state: State<AppState>,
+    query: Query<DidYouMeanQuery>,
+) -> Result<Json<Vec<TitleSuggestionDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::search::title::did_you_mean(&state.conn, query.0).await {
+        Ok(titles) => Ok(Json(titles)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn trending(
+    state: State<AppState>,
+    query: Query<TrendingSearchQuery>,
+) -> Result<Json<Vec<TrendingSearchDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::search::analytics::trending(
+        &state.conn,
+        &state.config.search.trending_denylist,
+        query.0,
+    )
+    .await
+    {
+        Ok(searches) => Ok(Json(searches)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn zero_result_searches(
+    state: State<AppState>,
+    query: Query<ZeroResultSearchQuery>,
+) -> Result<Json<Vec<ZeroResultSearchDto>>, (StatusCode, Json<ErrorBody>)> {
+    match service::search::analytics::zero_result_searches(&state.conn, query.0).await {
+        Ok(searches) => Ok(Json(searches)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
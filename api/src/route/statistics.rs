@@ -1,5 +1,9 @@
-use axum::{extract::State, http::StatusCode, Json};
-use service::dto::statistics::StatisticsResponse;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use service::dto::{statistics::StatisticsResponse, time::TimezoneQuery};
 
 use crate::{
     error::{ErrorBody, IntoErrorResponse},
@@ -8,8 +12,10 @@ use crate::{
 
 pub async fn statistics(
     state: State<AppState>,
+    query: Query<TimezoneQuery>,
 ) -> Result<Json<StatisticsResponse>, (StatusCode, Json<ErrorBody>)> {
-    match service::statistics::statistics(&state.conn).await {
+    let tz = query.0.tz.unwrap_or(state.config.timezone);
+    match service::statistics::statistics(&state.conn, tz).await {
         Ok(r) => Ok(Json(r)),
         Err(e) => Err(e.into_error_response()),
     }
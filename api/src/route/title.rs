@@ -14,7 +14,9 @@ pub async fn set_title_visibility(
     state: State<AppState>,
     Path((id, is_visible)): Path<(i32, bool)>,
 ) -> Result<Json<TitleDto>, (StatusCode, Json<ErrorBody>)> {
-    match service::title::set_title_visibility(&state.conn, id, is_visible).await {
+    match service::title::set_title_visibility(&state.conn, state.search.as_ref(), id, is_visible)
+        .await
+    {
         Ok(title) => Ok(Json(title)),
         Err(e) => Err(e.into_error_response()),
     }
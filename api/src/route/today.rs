@@ -4,8 +4,8 @@ use axum::{
     Json,
 };
 use service::dto::{
-    pagination::{PaginationQuery, PaginationResponse},
-    today::TodayTitleDto,
+    pagination::PaginationResponse,
+    today::{TodayQuery, TodayTitleDto},
 };
 
 use crate::{
@@ -15,9 +15,9 @@ use crate::{
 
 pub async fn today(
     state: State<AppState>,
-    query: Query<PaginationQuery>,
+    query: Query<TodayQuery>,
 ) -> Result<Json<PaginationResponse<TodayTitleDto>>, (StatusCode, Json<ErrorBody>)> {
-    match service::today::today(&state.conn, query.0).await {
+    match service::today::today(&state.conn, state.config.timezone, query.0).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
     }
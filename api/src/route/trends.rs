@@ -4,8 +4,8 @@ use axum::{
     Json,
 };
 use service::dto::{
-    pagination::{PaginationQuery, PaginationResponse},
-    trends::TrendTitleDto,
+    pagination::PaginationResponse,
+    trends::{TrendTitleDto, TrendsQuery},
 };
 
 use crate::{
@@ -15,9 +15,9 @@ use crate::{
 
 pub async fn trends(
     state: State<AppState>,
-    query: Query<PaginationQuery>,
+    query: Query<TrendsQuery>,
 ) -> Result<Json<PaginationResponse<TrendTitleDto>>, (StatusCode, Json<ErrorBody>)> {
-    match service::trends::trends(&state.conn, query.0).await {
+    match service::trends::trends(&state.conn, &state.config.trends, query.0).await {
         Ok(entries) => Ok(Json(entries)),
         Err(e) => Err(e.into_error_response()),
     }
@@ -0,0 +1,42 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use service::dto::two_factor::{
+    EnrollTwoFactorResponse, RecoveryCodesResponse, TwoFactorCodeRequest,
+};
+
+use crate::{
+    error::{ErrorBody, IntoErrorResponse},
+    middleware::auth::CurrentUser,
+    AppState,
+};
+
+pub async fn enroll(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+) -> Result<Json<EnrollTwoFactorResponse>, (StatusCode, Json<ErrorBody>)> {
+    match service::two_factor::enroll(&state.conn, user_id).await {
+        Ok(r) => Ok(Json(r)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn confirm(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+    json_data: Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, (StatusCode, Json<ErrorBody>)> {
+    match service::two_factor::confirm(&state.conn, user_id, json_data.0).await {
+        Ok(r) => Ok(Json(r)),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn disable(
+    state: State<AppState>,
+    Extension(CurrentUser { id: user_id }): Extension<CurrentUser>,
+    json_data: Json<TwoFactorCodeRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::two_factor::disable(&state.conn, user_id, json_data.0).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
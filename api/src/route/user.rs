@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use service::dto::silence::SilenceUserRequest;
+
+use crate::{
+    error::{ErrorBody, IntoErrorResponse},
+    AppState,
+};
+
+pub async fn set_bot(
+    state: State<AppState>,
+    Path((id, is_bot)): Path<(i32, bool)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::user::set_bot(&state.conn, id, is_bot).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn silence_user(
+    state: State<AppState>,
+    Path(id): Path<i32>,
+    json_data: Json<SilenceUserRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::silence::silence_user(&state.conn, state.config.timezone, id, json_data.0).await
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
+
+pub async fn unsilence_user(
+    state: State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorBody>)> {
+    match service::silence::unsilence_user(&state.conn, id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e.into_error_response()),
+    }
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{TestApp, TestUser};
+use serde_json::{json, Value};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// Sets up 2FA for `user` and returns a session that passed it.
+async fn two_factor_session(app: &TestApp, user: &TestUser, nickname: &str) -> String {
+    let (status, body) = app
+        .request(Method::POST, "/self/2fa/enroll", Some(&user.token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let secret = Secret::Encoded(body["secret"].as_str().unwrap().to_string());
+    let totp = TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret.to_bytes().unwrap(),
+        None,
+        nickname.to_string(),
+    )
+    .unwrap();
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/self/2fa/confirm",
+            Some(&user.token),
+            Some(json!({ "code": totp.generate_current().unwrap() })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let recovery_code = body["recoveryCodes"][0].clone();
+
+    let (_, body) = app.login(nickname).await;
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/auth/login/2fa",
+            None,
+            Some(json!({ "challenge": body["challenge"], "code": recovery_code })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    body["token"].as_str().unwrap().to_string()
+}
+
+async fn create_moderation_token(app: &TestApp, session: &str) -> (StatusCode, Value) {
+    app.request(
+        Method::POST,
+        "/self/tokens",
+        Some(session),
+        Some(json!({ "name": "bot", "scopes": ["moderation"] })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_moderation_tokens_need_a_two_factor_session() {
+    let Some(app) = TestApp::with_config("tokens_moderation_2fa", |config| {
+        config.auth.require_crew_two_factor = true;
+    })
+    .await
+    else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    app.grant_role(&sema, "moderator").await;
+
+    // A password alone can't mint a token for the crew routes.
+    let (status, _) = create_moderation_token(&app, &sema.token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let session = two_factor_session(&app, &sema, "sema").await;
+    let (status, body) = create_moderation_token(&app, &session).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let token = body["token"].as_str().unwrap();
+    let (status, _) = app
+        .request(Method::GET, "/statistics", Some(token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_crew_tokens_need_the_owner_to_keep_two_factor() {
+    let Some(app) = TestApp::with_config("tokens_crew_2fa", |config| {
+        config.auth.require_crew_two_factor = true;
+    })
+    .await
+    else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    app.grant_role(&sema, "moderator").await;
+
+    let session = two_factor_session(&app, &sema, "sema").await;
+    let (_, body) = create_moderation_token(&app, &session).await;
+    let token = body["token"].as_str().unwrap();
+
+    let (status, _) = app
+        .request(Method::GET, "/statistics", Some(token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Once the owner no longer has 2FA, their tokens lose the crew routes.
+    app.execute(r#"UPDATE "user" SET "totp_enabled_at" = NULL, "totp_secret" = NULL"#)
+        .await;
+    let (status, _) = app
+        .request(Method::GET, "/statistics", Some(token), None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_moderation_tokens_without_required_two_factor() {
+    let Some(app) = TestApp::new("tokens_moderation_optional").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    app.grant_role(&sema, "moderator").await;
+
+    // When the crew doesn't have to use 2FA, a password session will do.
+    let (status, body) = create_moderation_token(&app, &sema.token).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let token = body["token"].as_str().unwrap();
+    let (status, _) = app
+        .request(Method::GET, "/statistics", Some(token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_tokens_without_read_scope_browse_anonymously() {
+    let Some(app) = TestApp::new("tokens_public_routes").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let entry = app.entry(&sema, "bot", "oy veren bot").await;
+
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/self/tokens",
+            Some(&sema.token),
+            Some(json!({ "name": "bot", "scopes": ["vote"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let token = body["token"].as_str().unwrap();
+
+    let (status, _) = app
+        .request(Method::GET, &format!("/entries/{entry}"), Some(token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/vote/up"),
+            Some(token),
+            None,
+        )
+        .await;
+    assert_ne!(status, StatusCode::FORBIDDEN);
+    assert_ne!(status, StatusCode::UNAUTHORIZED);
+
+    app.drop().await;
+}
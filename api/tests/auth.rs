@@ -0,0 +1,123 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_register_login_logout() {
+    let Some(app) = TestApp::new("auth_login").await else {
+        return;
+    };
+
+    assert_eq!(app.register("sema").await, StatusCode::CREATED);
+    assert_eq!(app.register("Sema").await, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/auth/login",
+            None,
+            Some(json!({ "nickname": "sema", "password": "Yanlis0!" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Logins ignore the case of the nickname.
+    let (status, body) = app.login("SEMA").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["isEmailVerified"], json!(false));
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let (status, _) = app
+        .request(Method::GET, "/self/tokens", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .request(Method::POST, "/auth/logout", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The token is invalidated, so it can't be logged out twice.
+    let (status, _) = app
+        .request(Method::POST, "/auth/logout", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_private_routes_need_a_session() {
+    let Some(app) = TestApp::new("auth_private").await else {
+        return;
+    };
+
+    let body = json!({ "title": "soğan", "content": "merhaba" });
+    let (status, _) = app
+        .request(Method::POST, "/entries", None, Some(body.clone()))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app
+        .request(Method::POST, "/entries", Some("not-a-token"), Some(body))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app.request(Method::GET, "/self/bin", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = app.request(Method::GET, "/hello", None, None).await;
+    assert_eq!((status, body), (StatusCode::OK, json!("Hello, World!")));
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_verification_tokens_are_used_once() {
+    let Some(app) = TestApp::new("verify_once").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let token = "dogrulama-kodu";
+    app.execute(&format!(
+        r#"INSERT INTO "email_verification" ("user_id", "hash", "expires_at")
+           VALUES ({}, '{}', (now() AT TIME ZONE 'UTC') + interval '1 hour')"#,
+        sema.id,
+        service::token::hash_token(token),
+    ))
+    .await;
+
+    // Only one of two requests racing with the same token gets through.
+    let body = json!({ "token": token });
+    let (first, second) = tokio::join!(
+        app.request(Method::POST, "/auth/verify-email", None, Some(body.clone())),
+        app.request(Method::POST, "/auth/verify-email", None, Some(body.clone())),
+    );
+    let mut statuses = [first.0, second.0];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::NO_CONTENT, StatusCode::UNAUTHORIZED]);
+
+    let (status, _) = app
+        .request(Method::POST, "/auth/verify-email", None, Some(body))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_nicknames_differing_in_case_race() {
+    let Some(app) = TestApp::new("register_race").await else {
+        return;
+    };
+
+    // Both requests pass validation at once; the database keeps one.
+    let (first, second) = tokio::join!(app.register("Deniz"), app.register("deniz"));
+    let mut statuses = [first, second];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::CREATED, StatusCode::BAD_REQUEST]);
+
+    app.drop().await;
+}
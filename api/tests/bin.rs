@@ -0,0 +1,99 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_deleted_entries_go_to_the_bin() {
+    let Some(app) = TestApp::new("bin_recover").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    let first = app.entry(&sema, "kitaplar", "ilk girdi").await;
+    let second = app.entry(&sema, "kitaplar", "ikinci girdi").await;
+
+    // Only the author can delete an entry.
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/entries/{first}/soft-delete"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for id in [first, second] {
+        let (status, _) = app
+            .request(
+                Method::DELETE,
+                &format!("/entries/{id}/soft-delete"),
+                Some(&sema.token),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    let (status, _) = app
+        .request(Method::GET, &format!("/entries/{first}"), None, None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = app
+        .request(
+            Method::GET,
+            "/self/bin?page=1&perPage=10",
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], json!(2));
+
+    let (status, _) = app
+        .request(
+            Method::PATCH,
+            &format!("/entries/{first}/recover"),
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = app
+        .request(Method::GET, &format!("/entries/{first}"), None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["content"], json!("ilk girdi"));
+
+    let (status, _) = app
+        .request(Method::DELETE, "/self/bin", Some(&sema.token), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_, body) = app
+        .request(
+            Method::GET,
+            "/self/bin?page=1&perPage=10",
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(body["total"], json!(0));
+
+    // Emptied entries can't be recovered.
+    let (status, _) = app
+        .request(
+            Method::PATCH,
+            &format!("/entries/{second}/recover"),
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    app.drop().await;
+}
@@ -0,0 +1,229 @@
+//! Serves the API in-process for the integration tests. Every test gets a
+//! schema of its own, which is migrated before and dropped after it.
+//!
+//! Needs a PostgreSQL database: set `DATABASE_URL` to run the tests. They
+//! pass without doing anything otherwise.
+
+// Each test crate uses its own part of the helpers.
+#![allow(dead_code)]
+
+use api::App;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use migration::{Migrator, MigratorTrait};
+use serde_json::{json, Value};
+use service::{
+    auth::AuthHeader,
+    config::Config,
+    sea_orm::{
+        ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+    },
+};
+use tower::ServiceExt;
+
+/// Opens `/metrics` on the API listener.
+pub const METRICS_TOKEN: &str = "kPq0fRy2XbW7sLtN";
+
+pub struct TestApp {
+    pub router: Router,
+    pub db: DatabaseConnection,
+    admin: DatabaseConnection,
+    schema: String,
+}
+
+/// A registered user who is logged in.
+pub struct TestUser {
+    pub id: i32,
+    pub token: String,
+}
+
+impl TestApp {
+    /// Returns `None` when `DATABASE_URL` is not set.
+    pub async fn new(name: &str) -> Option<Self> {
+        Self::with_config(name, |_| {}).await
+    }
+
+    /// Like [`TestApp::new`], with the test configuration changed by
+    /// `configure`.
+    pub async fn with_config(name: &str, configure: impl FnOnce(&mut Config)) -> Option<Self> {
+        let Ok(db_url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL is not set; skipping {name}.");
+            return None;
+        };
+        let schema = format!("arpacik_api_{name}");
+
+        let admin = Database::connect(&db_url).await.unwrap();
+        admin
+            .execute_unprepared(&format!(
+                "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}"
+            ))
+            .await
+            .unwrap();
+
+        let mut opt = ConnectOptions::new(db_url.clone());
+        opt.set_schema_search_path(&schema);
+        let db = Database::connect(opt).await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+
+        let mut config = Config::default();
+        config.database.url = db_url;
+        config.database.schema = schema.clone();
+        config.auth.jwt_secret = "VcHCJhYoXGGL7awzIL6woA==".to_string();
+        config.auth.from = AuthHeader::Authorization;
+        config.mail.verification_url = "http://localhost/verify-email".to_string();
+        config.metrics.token = Some(METRICS_TOKEN.to_string());
+        configure(&mut config);
+
+        let router = App::builder(db.clone(), config).build().unwrap();
+
+        Some(Self {
+            router,
+            db,
+            admin,
+            schema,
+        })
+    }
+
+    pub async fn drop(self) {
+        self.db.close().await.unwrap();
+        self.admin
+            .execute_unprepared(&format!("DROP SCHEMA {} CASCADE", self.schema))
+            .await
+            .unwrap();
+    }
+
+    /// Sends a request to `/api/v1{path}` and returns the status and the
+    /// body: parsed when it is JSON, a string when it is not and `null` when
+    /// there is none.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        self.send(method, &format!("/api/v1{path}"), token, body)
+            .await
+    }
+
+    /// Sends a `GET` request to a path outside the versioned API.
+    pub async fn probe(&self, path: &str) -> (StatusCode, Value) {
+        self.send(Method::GET, path, None, None).await
+    }
+
+    /// Reads `/metrics` with the given bearer token.
+    pub async fn scrape(&self, token: Option<&str>) -> (StatusCode, Value) {
+        self.send(Method::GET, "/metrics", token, None).await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+
+        let response = self.router.clone().oneshot(request.unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = match bytes.is_empty() {
+            true => Value::Null,
+            false => serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into())),
+        };
+
+        (status, body)
+    }
+
+    pub async fn register(&self, nickname: &str) -> StatusCode {
+        let body = json!({
+            "nickname": nickname,
+            "email": format!("{nickname}@example.com"),
+            "password": "Passw0rd!",
+        });
+
+        self.request(Method::POST, "/auth/register", None, Some(body))
+            .await
+            .0
+    }
+
+    pub async fn login(&self, nickname: &str) -> (StatusCode, Value) {
+        let body = json!({ "nickname": nickname, "password": "Passw0rd!" });
+
+        self.request(Method::POST, "/auth/login", None, Some(body))
+            .await
+    }
+
+    /// Registers and logs in a user who is no longer faded, so that they can
+    /// open titles.
+    pub async fn user(&self, nickname: &str) -> TestUser {
+        assert_eq!(self.register(nickname).await, StatusCode::CREATED);
+        self.execute(&format!(
+            r#"UPDATE "user" SET "is_faded" = false WHERE "nickname" = '{nickname}'"#
+        ))
+        .await;
+
+        let (status, body) = self.login(nickname).await;
+        assert_eq!(status, StatusCode::OK);
+
+        TestUser {
+            id: self
+                .scalar(&format!(
+                    r#"SELECT "id" FROM "user" WHERE "nickname" = '{nickname}'"#
+                ))
+                .await,
+            token: body["token"].as_str().unwrap().to_string(),
+        }
+    }
+
+    /// Writes an entry and returns its id.
+    pub async fn entry(&self, user: &TestUser, title: &str, content: &str) -> i32 {
+        let body = json!({ "title": title, "content": content, "forceNewTitle": true });
+        let (status, body) = self
+            .request(Method::POST, "/entries", Some(&user.token), Some(body))
+            .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+
+        body["id"].as_i64().unwrap() as i32
+    }
+
+    /// Gives `user` a role, such as `moderator`, bypassing the API.
+    pub async fn grant_role(&self, user: &TestUser, role: &str) {
+        self.execute(&format!(
+            r#"INSERT INTO "user_role" ("user_id", "role_id")
+               SELECT {}, "id" FROM "role" WHERE "name" = '{role}'"#,
+            user.id
+        ))
+        .await;
+    }
+
+    pub async fn execute(&self, sql: &str) {
+        self.db.execute_unprepared(sql).await.unwrap();
+    }
+
+    /// Runs a query that returns a single number.
+    pub async fn scalar(&self, sql: &str) -> i32 {
+        self.db
+            .query_one(Statement::from_string(DbBackend::Postgres, sql))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get_by_index(0)
+            .unwrap()
+    }
+}
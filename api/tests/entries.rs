@@ -0,0 +1,192 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_write_read_and_update_entries() {
+    let Some(app) = TestApp::new("entries_write").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+
+    let first = app.entry(&sema, "kitaplar", "ilk girdi").await;
+    let second = app.entry(&ali, "kitaplar", "ikinci girdi").await;
+
+    let (status, body) = app
+        .request(Method::GET, &format!("/entries/{first}"), None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["content"], json!("ilk girdi"));
+    assert_eq!(body["title"]["name"], json!("kitaplar"));
+    assert_eq!(body["author"]["nickname"], json!("sema"));
+
+    let (status, body) = app
+        .request(
+            Method::GET,
+            "/titles/kitaplar/entries?page=1&perPage=10",
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], json!(2));
+    assert_eq!(body["items"][0]["id"], json!(first));
+    assert_eq!(body["items"][1]["id"], json!(second));
+
+    let update = json!({ "content": "düzeltilmiş girdi" });
+    let (status, _) = app
+        .request(
+            Method::PATCH,
+            &format!("/entries/{first}"),
+            Some(&sema.token),
+            Some(update.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // Only the author can change an entry.
+    let (status, _) = app
+        .request(
+            Method::PATCH,
+            &format!("/entries/{first}"),
+            Some(&ali.token),
+            Some(update),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = app
+        .request(Method::GET, &format!("/entries/{first}"), None, None)
+        .await;
+    assert_eq!(body["content"], json!("düzeltilmiş girdi"));
+    assert!(body["updatedAt"].is_string());
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_faded_users_cant_open_titles() {
+    let Some(app) = TestApp::new("entries_faded").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    app.entry(&sema, "kitaplar", "ilk girdi").await;
+
+    assert_eq!(app.register("yeni").await, StatusCode::CREATED);
+    let (_, body) = app.login("yeni").await;
+    let token = body["token"].as_str().unwrap();
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/entries",
+            Some(token),
+            Some(json!({ "title": "yeni başlık", "content": "merhaba" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/entries",
+            Some(token),
+            Some(json!({ "title": "kitaplar", "content": "merhaba" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_unverified_users_are_forbidden_to_write() {
+    let Some(app) = TestApp::with_config("entries_unverified", |config| {
+        config.auth.require_email_verification = true;
+    })
+    .await
+    else {
+        return;
+    };
+    let sema = app.user("sema").await;
+
+    // The session stays valid, so the refusal is not a 401.
+    let body = json!({ "title": "kitaplar", "content": "ilk girdi", "forceNewTitle": true });
+    let (status, body) = app
+        .request(Method::POST, "/entries", Some(&sema.token), Some(body))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], json!("Erişim izni yok"));
+
+    app.execute(r#"UPDATE "user" SET "email_verified_at" = now()"#)
+        .await;
+    app.entry(&sema, "kitaplar", "ilk girdi").await;
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_favorites_page_by_cursor() {
+    let Some(app) = TestApp::new("entries_favorites").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    let veli = app.user("veli").await;
+
+    let first = app.entry(&ali, "şarkılar", "birinci şarkı").await;
+    let second = app.entry(&ali, "şarkılar", "ikinci şarkı").await;
+    let gone = app
+        .entry(&veli, "şarkılar", "silinen yazarın şarkısı")
+        .await;
+    for entry in [first, gone, second] {
+        let (status, _) = app
+            .request(
+                Method::POST,
+                &format!("/entries/{entry}/favorite"),
+                Some(&sema.token),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    // Entries of deleted authors are left out before the page is cut.
+    app.execute(&format!(
+        r#"UPDATE "user" SET "deleted_at" = now() WHERE "id" = {}"#,
+        veli.id
+    ))
+    .await;
+
+    let (status, body) = app
+        .request(
+            Method::GET,
+            "/self/favorites?page=1&perPage=1",
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], json!(2));
+    assert_eq!(body["items"][0]["id"], json!(second));
+
+    // Pages by cursor aren't counted.
+    let cursor = body["next"].as_str().unwrap();
+    let (status, body) = app
+        .request(
+            Method::GET,
+            &format!("/self/favorites?perPage=1&cursor={cursor}"),
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], json!(null));
+    assert_eq!(body["items"][0]["id"], json!(first));
+    assert_eq!(body["next"], json!(null));
+
+    app.drop().await;
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use api::App;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use common::TestApp;
+use migration::{Migrator, MigratorTrait};
+use serde_json::{json, Value};
+use service::{config::Config, sea_orm::DatabaseConnection};
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_probes_need_no_session() {
+    let Some(app) = TestApp::new("health_probes").await else {
+        return;
+    };
+
+    let (status, body) = app.probe("/healthz").await;
+    assert_eq!((status, body), (StatusCode::OK, json!({ "status": "ok" })));
+
+    let (status, body) = app.probe("/readyz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "status": "ok",
+            "database": { "status": "ok" },
+            "migrations": { "status": "ok" },
+        })
+    );
+
+    let (status, body) = app.probe("/version").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["version"], json!(env!("CARGO_PKG_VERSION")));
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_pending_migrations_are_not_ready() {
+    let Some(app) = TestApp::new("health_pending").await else {
+        return;
+    };
+    Migrator::down(&app.db, Some(1)).await.unwrap();
+    let last = Migrator::migrations().last().unwrap().name().to_string();
+
+    let (status, body) = app.probe("/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], json!("unavailable"));
+    assert_eq!(body["database"]["status"], json!("ok"));
+    assert_eq!(body["migrations"]["status"], json!("unavailable"));
+    assert!(body["migrations"]["details"]
+        .as_str()
+        .unwrap()
+        .contains(&last));
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_unreachable_database_is_not_ready() {
+    let app = App::builder(DatabaseConnection::Disconnected, Config::default())
+        .build()
+        .unwrap();
+
+    let get = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::get(uri).body(Body::empty()).unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<Value>(&bytes).ok())
+        }
+    };
+
+    assert_eq!(get("/healthz").await.0, StatusCode::OK);
+    assert_eq!(
+        get("/api/v1/hello").await.0,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+
+    let (status, body) = get("/readyz").await;
+    let body = body.unwrap();
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["database"]["status"], json!("unavailable"));
+    assert_eq!(
+        body["migrations"]["details"],
+        json!("Veritabanı kullanılamıyor.")
+    );
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use api::Metrics;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+};
+use common::{TestApp, METRICS_TOKEN};
+use serde_json::json;
+use service::sea_orm::DatabaseConnection;
+use tower::ServiceExt;
+
+/// The value of the sample that starts with `series`, such as
+/// `arpacik_votes_total{rating="up"}`.
+fn sample(text: &str, series: &str) -> Option<f64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .map(|value| value.parse().unwrap())
+}
+
+#[tokio::test]
+async fn test_metrics_need_the_token() {
+    let Some(app) = TestApp::new("metrics_token").await else {
+        return;
+    };
+
+    let (status, _) = app.scrape(None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A session doesn't open the metrics.
+    let user = app.user("sema").await;
+    let (status, _) = app.scrape(Some(&user.token)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = app.scrape(Some(METRICS_TOKEN)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body
+        .as_str()
+        .unwrap()
+        .contains("# TYPE arpacik_logins_total counter"));
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_metrics_count_requests_and_domain_events() {
+    let Some(app) = TestApp::new("metrics_counts").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    let entry = app.entry(&sema, "kitaplar", "ilk girdi").await;
+
+    for _ in 0..2 {
+        let (status, _) = app
+            .request(Method::GET, &format!("/entries/{entry}"), None, None)
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    app.request(
+        Method::POST,
+        &format!("/entries/{entry}/vote/up"),
+        Some(&ali.token),
+        None,
+    )
+    .await;
+    app.request(
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(json!({ "nickname": "ali", "password": "Yanlis0!" })),
+    )
+    .await;
+    app.request(Method::GET, "/entries/42", None, None).await;
+
+    let (_, body) = app.scrape(Some(METRICS_TOKEN)).await;
+    let text = body.as_str().unwrap();
+
+    // Requests are counted by the route, not by the path.
+    let route = r#"method="GET",route="/api/v1/entries/:id""#;
+    assert_eq!(
+        sample(
+            text,
+            &format!(r#"arpacik_http_requests_total{{{route},status="200"}}"#)
+        ),
+        Some(2.0)
+    );
+    assert_eq!(
+        sample(
+            text,
+            &format!(r#"arpacik_http_requests_total{{{route},status="404"}}"#)
+        ),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(
+            text,
+            &format!("arpacik_http_request_duration_seconds_count{{{route}}}")
+        ),
+        Some(3.0)
+    );
+
+    assert_eq!(sample(text, "arpacik_entries_created_total"), Some(1.0));
+    assert_eq!(
+        sample(text, r#"arpacik_votes_total{rating="up"}"#),
+        Some(1.0)
+    );
+    assert_eq!(sample(text, "arpacik_logins_total"), Some(2.0));
+    assert_eq!(sample(text, "arpacik_failed_logins_total"), Some(1.0));
+    assert!(sample(text, "arpacik_db_max_connections").unwrap() > 0.0);
+    assert!(sample(text, r#"arpacik_db_connections{state="idle"}"#).is_some());
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_metrics_listener_needs_no_token() {
+    let router = Metrics::new().router(DatabaseConnection::Disconnected);
+    let request = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    assert_eq!(sample(&text, "arpacik_logins_total"), Some(0.0));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_roles_and_grants_are_unique() {
+    let Some(app) = TestApp::new("roles_unique").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    app.grant_role(&sema, "admin").await;
+
+    let role = json!({ "name": "editor", "permissions": ["hide_title"] });
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/roles",
+            Some(&sema.token),
+            Some(role.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = app
+        .request(Method::POST, "/roles", Some(&sema.token), Some(role))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"], json!("Bu isimde bir rol zaten var."));
+
+    let grant = format!("/users/{}/roles/editor", ali.id);
+    let (status, _) = app
+        .request(Method::PUT, &grant, Some(&sema.token), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = app
+        .request(Method::PUT, &grant, Some(&sema.token), None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"], json!("Kullanıcı bu role zaten sahip."));
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_deleted_admins_dont_manage_roles() {
+    let Some(app) = TestApp::new("roles_last_manager").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    app.grant_role(&sema, "admin").await;
+    app.grant_role(&ali, "admin").await;
+    app.execute(&format!(
+        r#"UPDATE "user" SET "deleted_at" = now() WHERE "id" = {}"#,
+        ali.id
+    ))
+    .await;
+
+    // The deleted administrator can't manage roles, so the last live one
+    // can't give the role up.
+    let (status, body) = app
+        .request(
+            Method::DELETE,
+            &format!("/users/{}/roles/admin", sema.id),
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["details"],
+        json!("Rolleri yönetebilecek en az bir kullanıcı kalmalıdır.")
+    );
+
+    app.drop().await;
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_trending_searches_count_each_searcher_once() {
+    let Some(app) = TestApp::with_config("trending_searches", |config| {
+        config.search.trending_denylist = vec!["KÖTÜ".to_string()];
+    })
+    .await
+    else {
+        return;
+    };
+
+    let mut users = Vec::new();
+    for nickname in ["arda", "beril", "cem", "duru", "ece"] {
+        users.push(app.user(nickname).await);
+    }
+
+    // One client searching again and again doesn't make a query trend.
+    for _ in 0..10 {
+        let (status, _) = app
+            .request(
+                Method::GET,
+                "/search?query=ayva",
+                Some(&users[0].token),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    for user in &users {
+        for query in ["armut", "k%C3%B6t%C3%BC%20s%C3%B6z"] {
+            let (status, _) = app
+                .request(
+                    Method::GET,
+                    &format!("/search?query={query}"),
+                    Some(&user.token),
+                    None,
+                )
+                .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+
+    let (status, body) = app
+        .request(Method::GET, "/search/trending", None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([{ "query": "armut", "searchCount": 5 }]));
+
+    app.drop().await;
+}
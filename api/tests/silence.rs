@@ -0,0 +1,91 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_silenced_users_cant_write_or_vote() {
+    let Some(app) = TestApp::new("silence_write").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    app.grant_role(&sema, "moderator").await;
+    let entry = app.entry(&sema, "kitaplar", "ilk girdi").await;
+
+    let silence = json!({ "reason": "hakaret", "until": "2999-01-01" });
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &format!("/users/{}/silence", sema.id),
+            Some(&ali.token),
+            Some(silence.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &format!("/users/{}/silence", ali.id),
+            Some(&sema.token),
+            Some(json!({ "reason": "hakaret", "until": "2000-01-01" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .request(
+            Method::PUT,
+            &format!("/users/{}/silence", ali.id),
+            Some(&sema.token),
+            Some(silence),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let body = json!({ "title": "kitaplar", "content": "ikinci girdi" });
+    let (status, body) = app
+        .request(Method::POST, "/entries", Some(&ali.token), Some(body))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(
+        body["details"],
+        json!("01.01.2999 tarihine kadar susturuldunuz: hakaret")
+    );
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/vote/up"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/users/{}/silence", ali.id),
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    app.entry(&ali, "kitaplar", "ikinci girdi").await;
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/users/{}/silence", ali.id),
+            Some(&sema.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    app.drop().await;
+}
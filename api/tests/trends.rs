@@ -0,0 +1,108 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::{json, Value};
+
+fn names(body: &Value) -> Vec<&str> {
+    body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].as_str().unwrap())
+        .collect()
+}
+
+fn assert_score(item: &Value, expected: f64) {
+    let score = item["score"].as_f64().unwrap();
+    assert!((score - expected).abs() < 0.01, "{item} != {expected}");
+}
+
+#[tokio::test]
+async fn test_trends_rank_titles_by_recent_activity() {
+    let Some(app) = TestApp::new("trends_rank").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    let veli = app.user("veli").await;
+    let deniz = app.user("deniz").await;
+
+    // Many authors count more than a burst of entries of one author.
+    let crowd = app.entry(&sema, "kalabalık", "kalabalık bir").await;
+    app.entry(&ali, "kalabalık", "kalabalık iki").await;
+    app.entry(&veli, "kalabalık", "kalabalık üç").await;
+    let burst = app.entry(&ali, "tekrar", "dört").await;
+    app.entry(&ali, "tekrar", "beş").await;
+    app.entry(&ali, "tekrar", "altı").await;
+    app.entry(&deniz, "soluk", "soluk bir").await;
+    let old = app.entry(&sema, "eski", "eski bir girdi").await;
+    let last_week = app.entry(&sema, "geçen hafta", "geçen haftaki girdi").await;
+
+    app.execute(r#"UPDATE "user" SET "is_faded" = true WHERE "nickname" = 'deniz'"#)
+        .await;
+    app.execute(&format!(
+        r#"UPDATE "entry" SET "created_at" = (now() AT TIME ZONE 'UTC') - interval '20 hours'
+           WHERE "id" = {old}"#
+    ))
+    .await;
+    app.execute(&format!(
+        r#"UPDATE "entry" SET "created_at" = (now() AT TIME ZONE 'UTC') - interval '2 days'
+           WHERE "id" = {last_week}"#
+    ))
+    .await;
+
+    for (user, path) in [
+        (&veli, format!("/entries/{crowd}/vote/down")),
+        (&veli, format!("/entries/{burst}/favorite")),
+        // Titles without entries in the window don't trend on votes alone.
+        (&veli, format!("/entries/{last_week}/vote/up")),
+    ] {
+        let (status, _) = app
+            .request(Method::POST, &path, Some(&user.token), None)
+            .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    let (status, body) = app
+        .request(Method::GET, "/trends?page=1&perPage=10", None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(names(&body), ["kalabalık", "tekrar", "soluk", "eski"]);
+    assert_eq!(body["total"], json!(4));
+
+    let items = &body["items"];
+    // Three authors with an entry each, less a down vote.
+    assert_score(&items[0], 3.0 * (1.0 + 2.0) - 0.5);
+    // One author with three entries, each counting half the one after it,
+    // and a favorite.
+    assert_score(&items[1], 1.0 + 0.5 + 0.25 + 2.0 + 1.0);
+    assert_score(&items[2], (1.0 + 2.0) * 0.25);
+    // Twenty hours into a day long window, with a half-life of six hours.
+    assert_score(&items[3], (1.0 + 2.0) * 0.5f64.powf(20.0 / 6.0));
+    assert_eq!(items[1]["entryCount"], json!(3));
+
+    let (_, body) = app
+        .request(Method::GET, "/trends?page=2&perPage=3", None, None)
+        .await;
+    assert_eq!(names(&body), ["eski"]);
+    assert_eq!(body["total"], json!(4));
+
+    let (_, body) = app
+        .request(Method::GET, "/trends?page=3&perPage=3", None, None)
+        .await;
+    assert_eq!(names(&body), Vec::<&str>::new());
+    assert_eq!(body["total"], json!(4));
+
+    let (_, body) = app
+        .request(
+            Method::GET,
+            "/trends?page=1&perPage=10&window=7d",
+            None,
+            None,
+        )
+        .await;
+    assert!(names(&body).contains(&"geçen hafta"));
+
+    app.drop().await;
+}
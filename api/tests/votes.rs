@@ -0,0 +1,138 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_votes_and_favorites_keep_counters() {
+    let Some(app) = TestApp::new("votes_counters").await else {
+        return;
+    };
+    let sema = app.user("sema").await;
+    let ali = app.user("ali").await;
+    let entry = app.entry(&sema, "kitaplar", "ilk girdi").await;
+
+    let counters = |column: &'static str| {
+        let app = &app;
+        async move {
+            app.scalar(&format!(
+                r#"SELECT "{column}" FROM "entry" WHERE "id" = {entry}"#
+            ))
+            .await
+        }
+    };
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/vote/up"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(counters("net_votes").await, 1);
+
+    // The same vote twice is refused, the opposite one replaces it.
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/vote/up"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/vote/down"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(counters("net_votes").await, -1);
+
+    let (_, body) = app
+        .request(
+            Method::GET,
+            &format!("/entries/{entry}"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(body["vote"], json!("Down"));
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/unvote"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(counters("net_votes").await, 0);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/favorite"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(counters("total_favorites").await, 1);
+
+    let (_, body) = app
+        .request(
+            Method::GET,
+            "/self/favorites?page=1&perPage=10",
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(body["items"][0]["id"], json!(entry));
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/entries/{entry}/unfavorite"),
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(counters("total_favorites").await, 0);
+
+    app.drop().await;
+}
+
+#[tokio::test]
+async fn test_votes_need_an_entry() {
+    let Some(app) = TestApp::new("votes_missing").await else {
+        return;
+    };
+    let ali = app.user("ali").await;
+
+    let (status, _) = app
+        .request(Method::POST, "/entries/42/vote/up", Some(&ali.token), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/entries/42/vote/sideways",
+            Some(&ali.token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    app.drop().await;
+}
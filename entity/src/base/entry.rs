@@ -3,8 +3,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
-#[sea_orm(schema_name = "arpacik", table_name = "entry")]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "entry")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
@@ -17,10 +17,18 @@ pub struct Model {
     pub created_at: DateTime,
     pub updated_at: Option<DateTime>,
     pub deleted_at: Option<DateTime>,
+    pub up_votes: i32,
+    pub down_votes: i32,
+    #[sea_orm(column_type = "Double")]
+    pub sukela_score: f64,
+    #[sea_orm(column_type = "Double")]
+    pub controversy_score: f64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::debe_entry::Entity")]
+    DebeEntry,
     #[sea_orm(has_many = "super::favorite::Entity")]
     Favorite,
     #[sea_orm(
@@ -43,6 +51,12 @@ pub enum Relation {
     Vote,
 }
 
+impl Related<super::debe_entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DebeEntry.def()
+    }
+}
+
 impl Related<super::favorite::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Favorite.def()
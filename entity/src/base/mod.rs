@@ -2,11 +2,21 @@
 
 pub mod prelude;
 
+pub mod access_token;
+pub mod debe_entry;
+pub mod email_verification;
 pub mod entry;
 pub mod favorite;
+pub mod login_attempt;
+pub mod recovery_code;
+pub mod role;
+pub mod role_permission;
 pub mod sea_orm_active_enums;
+pub mod search_query_searcher;
+pub mod search_query_stat;
 pub mod silenced_user;
 pub mod title;
 pub mod token;
 pub mod user;
+pub mod user_role;
 pub mod vote;
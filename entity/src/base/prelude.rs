@@ -1,9 +1,19 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+pub use super::access_token::Entity as AccessToken;
+pub use super::debe_entry::Entity as DebeEntry;
+pub use super::email_verification::Entity as EmailVerification;
 pub use super::entry::Entity as Entry;
 pub use super::favorite::Entity as Favorite;
+pub use super::login_attempt::Entity as LoginAttempt;
+pub use super::recovery_code::Entity as RecoveryCode;
+pub use super::role::Entity as Role;
+pub use super::role_permission::Entity as RolePermission;
+pub use super::search_query_searcher::Entity as SearchQuerySearcher;
+pub use super::search_query_stat::Entity as SearchQueryStat;
 pub use super::silenced_user::Entity as SilencedUser;
 pub use super::title::Entity as Title;
 pub use super::token::Entity as Token;
 pub use super::user::Entity as User;
+pub use super::user_role::Entity as UserRole;
 pub use super::vote::Entity as Vote;
@@ -4,7 +4,7 @@ use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
-#[sea_orm(schema_name = "arpacik", table_name = "user")]
+#[sea_orm(table_name = "user")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
@@ -13,28 +13,53 @@ pub struct Model {
     #[sea_orm(unique)]
     pub email: String,
     pub password_hash: String,
-    pub is_admin: bool,
-    pub is_moderator: bool,
     pub is_faded: bool,
     pub created_at: DateTime,
     pub updated_at: Option<DateTime>,
     pub deleted_at: Option<DateTime>,
+    pub email_verified_at: Option<DateTime>,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTime>,
+    pub totp_last_step: Option<i64>,
+    pub is_bot: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::access_token::Entity")]
+    AccessToken,
+    #[sea_orm(has_many = "super::email_verification::Entity")]
+    EmailVerification,
     #[sea_orm(has_many = "super::entry::Entity")]
     Entry,
     #[sea_orm(has_many = "super::favorite::Entity")]
     Favorite,
+    #[sea_orm(has_many = "super::login_attempt::Entity")]
+    LoginAttempt,
+    #[sea_orm(has_many = "super::recovery_code::Entity")]
+    RecoveryCode,
     #[sea_orm(has_many = "super::silenced_user::Entity")]
     SilencedUser,
     #[sea_orm(has_many = "super::token::Entity")]
     Token,
+    #[sea_orm(has_many = "super::user_role::Entity")]
+    UserRole,
     #[sea_orm(has_many = "super::vote::Entity")]
     Vote,
 }
 
+impl Related<super::access_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccessToken.def()
+    }
+}
+
+impl Related<super::email_verification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EmailVerification.def()
+    }
+}
+
 impl Related<super::entry::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Entry.def()
@@ -47,6 +72,18 @@ impl Related<super::favorite::Entity> for Entity {
     }
 }
 
+impl Related<super::login_attempt::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LoginAttempt.def()
+    }
+}
+
+impl Related<super::recovery_code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecoveryCode.def()
+    }
+}
+
 impl Related<super::silenced_user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::SilencedUser.def()
@@ -59,6 +96,12 @@ impl Related<super::token::Entity> for Entity {
     }
 }
 
+impl Related<super::user_role::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserRole.def()
+    }
+}
+
 impl Related<super::vote::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Vote.def()
@@ -2,26 +2,56 @@ pub use super::base::prelude::*;
 
 pub use super::base::sea_orm_active_enums::Rating;
 
+pub use super::base::access_token::ActiveModel as AccessTokenActiveModel;
+pub use super::base::debe_entry::ActiveModel as DebeEntryActiveModel;
+pub use super::base::email_verification::ActiveModel as EmailVerificationActiveModel;
 pub use super::base::entry::ActiveModel as EntryActiveModel;
 pub use super::base::favorite::ActiveModel as FavoriteActiveModel;
+pub use super::base::login_attempt::ActiveModel as LoginAttemptActiveModel;
+pub use super::base::recovery_code::ActiveModel as RecoveryCodeActiveModel;
+pub use super::base::role::ActiveModel as RoleActiveModel;
+pub use super::base::role_permission::ActiveModel as RolePermissionActiveModel;
+pub use super::base::search_query_searcher::ActiveModel as SearchQuerySearcherActiveModel;
+pub use super::base::search_query_stat::ActiveModel as SearchQueryStatActiveModel;
 pub use super::base::silenced_user::ActiveModel as SilencedUserActiveModel;
 pub use super::base::title::ActiveModel as TitleActiveModel;
 pub use super::base::token::ActiveModel as TokenActiveModel;
 pub use super::base::user::ActiveModel as UserActiveModel;
+pub use super::base::user_role::ActiveModel as UserRoleActiveModel;
 pub use super::base::vote::ActiveModel as VoteActiveModel;
 
+pub use super::base::access_token::Model as AccessTokenModel;
+pub use super::base::debe_entry::Model as DebeEntryModel;
+pub use super::base::email_verification::Model as EmailVerificationModel;
 pub use super::base::entry::Model as EntryModel;
 pub use super::base::favorite::Model as FavoriteModel;
+pub use super::base::login_attempt::Model as LoginAttemptModel;
+pub use super::base::recovery_code::Model as RecoveryCodeModel;
+pub use super::base::role::Model as RoleModel;
+pub use super::base::role_permission::Model as RolePermissionModel;
+pub use super::base::search_query_searcher::Model as SearchQuerySearcherModel;
+pub use super::base::search_query_stat::Model as SearchQueryStatModel;
 pub use super::base::silenced_user::Model as SilencedUserModel;
 pub use super::base::title::Model as TitleModel;
 pub use super::base::token::Model as TokenModel;
 pub use super::base::user::Model as UserModel;
+pub use super::base::user_role::Model as UserRoleModel;
 pub use super::base::vote::Model as VoteModel;
 
+pub use super::base::access_token::Column as AccessTokenColumn;
+pub use super::base::debe_entry::Column as DebeEntryColumn;
+pub use super::base::email_verification::Column as EmailVerificationColumn;
 pub use super::base::entry::Column as EntryColumn;
 pub use super::base::favorite::Column as FavoriteColumn;
+pub use super::base::login_attempt::Column as LoginAttemptColumn;
+pub use super::base::recovery_code::Column as RecoveryCodeColumn;
+pub use super::base::role::Column as RoleColumn;
+pub use super::base::role_permission::Column as RolePermissionColumn;
+pub use super::base::search_query_searcher::Column as SearchQuerySearcherColumn;
+pub use super::base::search_query_stat::Column as SearchQueryStatColumn;
 pub use super::base::silenced_user::Column as SilencedUserColumn;
 pub use super::base::title::Column as TitleColumn;
 pub use super::base::token::Column as TokenColumn;
 pub use super::base::user::Column as UserColumn;
+pub use super::base::user_role::Column as UserRoleColumn;
 pub use super::base::vote::Column as VoteColumn;
@@ -2,12 +2,42 @@ pub use sea_orm_migration::prelude::*;
 
 mod helper;
 mod m20220101_000001_init;
+mod m20261019_000001_email_verification;
+mod m20261019_000002_two_factor;
+mod m20261019_000003_login_attempt;
+mod m20261019_000004_access_token;
+mod m20261019_000005_role;
+mod m20261019_000006_unique_vote_favorite;
+mod m20261019_000007_index;
+mod m20261019_000008_debe;
+mod m20261019_000009_vote_count;
+mod m20261019_000010_entry_search;
+mod m20261019_000011_title_search;
+mod m20261019_000012_search_query_stat;
+mod m20261019_000013_search_query_searcher;
+mod m20261019_000014_user_lower_unique;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_init::Migration)]
+        vec![
+            Box::new(m20220101_000001_init::Migration),
+            Box::new(m20261019_000001_email_verification::Migration),
+            Box::new(m20261019_000002_two_factor::Migration),
+            Box::new(m20261019_000003_login_attempt::Migration),
+            Box::new(m20261019_000004_access_token::Migration),
+            Box::new(m20261019_000005_role::Migration),
+            Box::new(m20261019_000006_unique_vote_favorite::Migration),
+            Box::new(m20261019_000007_index::Migration),
+            Box::new(m20261019_000008_debe::Migration),
+            Box::new(m20261019_000009_vote_count::Migration),
+            Box::new(m20261019_000010_entry_search::Migration),
+            Box::new(m20261019_000011_title_search::Migration),
+            Box::new(m20261019_000012_search_query_stat::Migration),
+            Box::new(m20261019_000013_search_query_searcher::Migration),
+            Box::new(m20261019_000014_user_lower_unique::Migration),
+        ]
     }
 }
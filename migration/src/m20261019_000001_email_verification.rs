@@ -0,0 +1,114 @@
+use sea_orm_migration::prelude::*;
+
+use crate::helper::current_timestamp_utc;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::EmailVerifiedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Accounts created before verification existed are trusted as is.
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(User::Table)
+                    .value(User::EmailVerifiedAt, Expr::col(User::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailVerification::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmailVerification::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(EmailVerification::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EmailVerification::Hash)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EmailVerification::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(current_timestamp_utc()),
+                    )
+                    .col(
+                        ColumnDef::new(EmailVerification::ExpiresAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EmailVerification::UsedAt).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-email_verification-user_id")
+                            .from(EmailVerification::Table, EmailVerification::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmailVerification::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::EmailVerifiedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    CreatedAt,
+    EmailVerifiedAt,
+}
+
+#[derive(DeriveIden)]
+enum EmailVerification {
+    Table,
+    Id,
+    UserId,
+    Hash,
+    CreatedAt,
+    ExpiresAt,
+    UsedAt,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+use crate::helper::current_timestamp_utc;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempt::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LoginAttempt::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LoginAttempt::UserId).integer().null())
+                    .col(
+                        ColumnDef::new(LoginAttempt::Identifier)
+                            .string_len(320)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(LoginAttempt::Ip).string_len(45).null())
+                    .col(ColumnDef::new(LoginAttempt::Success).boolean().not_null())
+                    .col(
+                        ColumnDef::new(LoginAttempt::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(current_timestamp_utc()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-login_attempt-user_id")
+                            .from(LoginAttempt::Table, LoginAttempt::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-login_attempt-user_id-created_at")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::UserId)
+                    .col(LoginAttempt::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-login_attempt-ip-created_at")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::Ip)
+                    .col(LoginAttempt::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginAttempt::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempt {
+    Table,
+    Id,
+    UserId,
+    Identifier,
+    Ip,
+    Success,
+    CreatedAt,
+}
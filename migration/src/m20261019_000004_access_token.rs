@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+use crate::helper::current_timestamp_utc;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::IsBot)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessToken::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AccessToken::UserId).integer().not_null())
+                    .col(ColumnDef::new(AccessToken::Name).string_len(64).not_null())
+                    .col(
+                        ColumnDef::new(AccessToken::Hash)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AccessToken::Scopes).string().not_null())
+                    .col(
+                        ColumnDef::new(AccessToken::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(current_timestamp_utc()),
+                    )
+                    .col(ColumnDef::new(AccessToken::ExpiresAt).timestamp().null())
+                    .col(ColumnDef::new(AccessToken::LastUsedAt).timestamp().null())
+                    .col(ColumnDef::new(AccessToken::RevokedAt).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-access_token-user_id")
+                            .from(AccessToken::Table, AccessToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-access_token-user_id")
+                    .table(AccessToken::Table)
+                    .col(AccessToken::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessToken::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::IsBot)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    IsBot,
+}
+
+#[derive(DeriveIden)]
+enum AccessToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Hash,
+    Scopes,
+    CreatedAt,
+    ExpiresAt,
+    LastUsedAt,
+    RevokedAt,
+}
@@ -0,0 +1,314 @@
+use sea_orm_migration::prelude::*;
+
+use crate::helper::current_timestamp_utc;
+
+const ADMIN_PERMISSIONS: [&str; 7] = [
+    "hide_title",
+    "migrate_entry",
+    "silence_user",
+    "view_statistics",
+    "view_login_attempts",
+    "manage_bots",
+    "manage_roles",
+];
+
+const MODERATOR_PERMISSIONS: [&str; 4] = [
+    "hide_title",
+    "migrate_entry",
+    "silence_user",
+    "view_statistics",
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Role::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Role::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Role::Name)
+                            .string_len(32)
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Role::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(current_timestamp_utc()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RolePermission::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RolePermission::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RolePermission::RoleId).integer().not_null())
+                    .col(
+                        ColumnDef::new(RolePermission::Permission)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-role_permission-role_id")
+                            .from(RolePermission::Table, RolePermission::RoleId)
+                            .to(Role::Table, Role::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-role_permission-role_id-permission")
+                            .col(RolePermission::RoleId)
+                            .col(RolePermission::Permission)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRole::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserRole::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UserRole::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserRole::RoleId).integer().not_null())
+                    .col(ColumnDef::new(UserRole::GrantedBy).integer().null())
+                    .col(
+                        ColumnDef::new(UserRole::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(current_timestamp_utc()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_role-user_id")
+                            .from(UserRole::Table, UserRole::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_role-role_id")
+                            .from(UserRole::Table, UserRole::RoleId)
+                            .to(Role::Table, Role::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_role-granted_by")
+                            .from(UserRole::Table, UserRole::GrantedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-user_role-user_id-role_id")
+                            .col(UserRole::UserId)
+                            .col(UserRole::RoleId)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (role, permissions, flag) in [
+            ("admin", &ADMIN_PERMISSIONS[..], User::IsAdmin),
+            ("moderator", &MODERATOR_PERMISSIONS[..], User::IsModerator),
+        ] {
+            manager
+                .exec_stmt(
+                    Query::insert()
+                        .into_table(Role::Table)
+                        .columns([Role::Name])
+                        .values_panic([role.into()])
+                        .to_owned(),
+                )
+                .await?;
+
+            let role_id = Query::select()
+                .column(Role::Id)
+                .from(Role::Table)
+                .and_where(Expr::col(Role::Name).eq(role))
+                .to_owned();
+
+            for permission in permissions {
+                manager
+                    .exec_stmt(
+                        Query::insert()
+                            .into_table(RolePermission::Table)
+                            .columns([RolePermission::RoleId, RolePermission::Permission])
+                            .select_from(
+                                Query::select()
+                                    .column(Role::Id)
+                                    .expr(Expr::val(*permission))
+                                    .from(Role::Table)
+                                    .and_where(Expr::col(Role::Name).eq(role))
+                                    .to_owned(),
+                            )
+                            .map_err(|e| DbErr::Custom(e.to_string()))?
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+
+            manager
+                .exec_stmt(
+                    Query::insert()
+                        .into_table(UserRole::Table)
+                        .columns([UserRole::UserId, UserRole::RoleId])
+                        .select_from(
+                            Query::select()
+                                .column(User::Id)
+                                .expr(SimpleExpr::SubQuery(
+                                    None,
+                                    Box::new(role_id.into_sub_query_statement()),
+                                ))
+                                .from(User::Table)
+                                .and_where(Expr::col(flag).eq(true))
+                                .to_owned(),
+                        )
+                        .map_err(|e| DbErr::Custom(e.to_string()))?
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::IsAdmin)
+                    .drop_column(User::IsModerator)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::IsAdmin)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(User::IsModerator)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (role, flag) in [("admin", User::IsAdmin), ("moderator", User::IsModerator)] {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(User::Table)
+                        .value(flag, true)
+                        .and_where(
+                            Expr::col(User::Id).in_subquery(
+                                Query::select()
+                                    .column((UserRole::Table, UserRole::UserId))
+                                    .from(UserRole::Table)
+                                    .inner_join(
+                                        Role::Table,
+                                        Expr::col((Role::Table, Role::Id))
+                                            .equals((UserRole::Table, UserRole::RoleId)),
+                                    )
+                                    .and_where(Expr::col((Role::Table, Role::Name)).eq(role))
+                                    .to_owned(),
+                            ),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(UserRole::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RolePermission::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Role::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    IsAdmin,
+    IsModerator,
+}
+
+#[derive(DeriveIden)]
+enum Role {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RolePermission {
+    Table,
+    Id,
+    RoleId,
+    Permission,
+}
+
+#[derive(DeriveIden)]
+enum UserRole {
+    Table,
+    Id,
+    UserId,
+    RoleId,
+    GrantedBy,
+    CreatedAt,
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Keep the first of any duplicate votes and favorites so that the
+        // unique indexes can be created. The counters on `entry` may be off
+        // afterwards; `arpacik reconcile-counters` recomputes them.
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Vote::Table)
+                    .and_where(
+                        Expr::col(Vote::Id).not_in_subquery(
+                            Query::select()
+                                .expr(Expr::col(Vote::Id).min())
+                                .from(Vote::Table)
+                                .group_by_columns([Vote::UserId, Vote::EntryId])
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Favorite::Table)
+                    .and_where(
+                        Expr::col(Favorite::Id).not_in_subquery(
+                            Query::select()
+                                .expr(Expr::col(Favorite::Id).min())
+                                .from(Favorite::Table)
+                                .group_by_columns([Favorite::UserId, Favorite::EntryId])
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-vote-user_id-entry_id")
+                    .table(Vote::Table)
+                    .col(Vote::UserId)
+                    .col(Vote::EntryId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-favorite-user_id-entry_id")
+                    .table(Favorite::Table)
+                    .col(Favorite::UserId)
+                    .col(Favorite::EntryId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-favorite-user_id-entry_id")
+                    .table(Favorite::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-vote-user_id-entry_id")
+                    .table(Vote::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Vote {
+    Table,
+    Id,
+    UserId,
+    EntryId,
+}
+
+#[derive(DeriveIden)]
+enum Favorite {
+    Table,
+    Id,
+    UserId,
+    EntryId,
+}
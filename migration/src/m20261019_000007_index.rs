@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+
+/// Partial indexes, which `Index::create` can't express yet.
+const PARTIAL_INDEXES: [(&str, &str); 3] = [
+    (
+        "idx-entry-title_id-created_at",
+        r#"CREATE INDEX IF NOT EXISTS "idx-entry-title_id-created_at" ON "entry" ("title_id", "created_at") WHERE "deleted_at" IS NULL"#,
+    ),
+    (
+        "idx-entry-created_at",
+        r#"CREATE INDEX IF NOT EXISTS "idx-entry-created_at" ON "entry" ("created_at") WHERE "deleted_at" IS NULL"#,
+    ),
+    (
+        "idx-title-last_entry_at",
+        r#"CREATE INDEX IF NOT EXISTS "idx-title-last_entry_at" ON "title" ("last_entry_at") WHERE "is_visible""#,
+    ),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (_, statement) in PARTIAL_INDEXES {
+            manager
+                .get_connection()
+                .execute_unprepared(statement)
+                .await?;
+        }
+
+        // The bin lists deleted entries too, so this one isn't partial.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-entry-user_id-created_at")
+                    .table(Entry::Table)
+                    .col(Entry::UserId)
+                    .col(Entry::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-vote-entry_id")
+                    .table(Vote::Table)
+                    .col(Vote::EntryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Also serves lookups by `user_id` alone, and lists favorites in the
+        // order they were added.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-favorite-user_id-created_at")
+                    .table(Favorite::Table)
+                    .col(Favorite::UserId)
+                    .col(Favorite::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-token-hash")
+                    .table(Token::Table)
+                    .col(Token::Hash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table) in [
+            ("idx-token-hash", Token::Table.into_iden()),
+            (
+                "idx-favorite-user_id-created_at",
+                Favorite::Table.into_iden(),
+            ),
+            ("idx-vote-entry_id", Vote::Table.into_iden()),
+            ("idx-entry-user_id-created_at", Entry::Table.into_iden()),
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).table(table).to_owned())
+                .await?;
+        }
+
+        for (name, _) in PARTIAL_INDEXES {
+            manager
+                .drop_index(Index::drop().name(name).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Entry {
+    Table,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Vote {
+    Table,
+    EntryId,
+}
+
+#[derive(DeriveIden)]
+enum Favorite {
+    Table,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Token {
+    Table,
+    Hash,
+}
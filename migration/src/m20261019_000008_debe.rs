@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+use crate::helper::current_timestamp_utc;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DebeEntry::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DebeEntry::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(DebeEntry::Date).date().not_null())
+                    .col(ColumnDef::new(DebeEntry::Rank).integer().not_null())
+                    .col(ColumnDef::new(DebeEntry::EntryId).integer().not_null())
+                    .col(ColumnDef::new(DebeEntry::Score).double().not_null())
+                    .col(ColumnDef::new(DebeEntry::NetVotes).integer().not_null())
+                    .col(
+                        ColumnDef::new(DebeEntry::TotalFavorites)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DebeEntry::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(current_timestamp_utc()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-debe_entry-entry_id")
+                            .from(DebeEntry::Table, DebeEntry::EntryId)
+                            .to(Entry::Table, Entry::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-debe_entry-date-rank")
+                            .col(DebeEntry::Date)
+                            .col(DebeEntry::Rank)
+                            .unique(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-debe_entry-date-entry_id")
+                            .col(DebeEntry::Date)
+                            .col(DebeEntry::EntryId)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DebeEntry::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Entry {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum DebeEntry {
+    Table,
+    Id,
+    Date,
+    Rank,
+    EntryId,
+    Score,
+    NetVotes,
+    TotalFavorites,
+    CreatedAt,
+}
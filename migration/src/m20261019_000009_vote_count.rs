@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+
+/// The lower bound of the Wilson score interval of the up votes at 95%
+/// confidence (z = 1.96).
+const SUKELA_SCORE: &str = r#"CASE WHEN "up_votes" + "down_votes" = 0 THEN 0 ELSE (
+    "up_votes"::float8 / ("up_votes" + "down_votes")
+    + 3.8416 / (2 * ("up_votes" + "down_votes"))
+    - 1.96 * sqrt(
+        "up_votes"::float8 * "down_votes" / ("up_votes" + "down_votes") + 3.8416 / 4
+    ) / ("up_votes" + "down_votes")
+) / (1 + 3.8416 / ("up_votes" + "down_votes")) END"#;
+
+/// Grows with the number of votes and with how evenly they are split.
+const CONTROVERSY_SCORE: &str = r#"CASE WHEN "up_votes" = 0 OR "down_votes" = 0 THEN 0 ELSE power(
+    ("up_votes" + "down_votes")::float8,
+    LEAST("up_votes", "down_votes")::float8 / GREATEST("up_votes", "down_votes")
+) END"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entry::Table)
+                    .add_column(
+                        ColumnDef::new(Entry::UpVotes)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Entry::DownVotes)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (column, rating) in [(Entry::UpVotes, "Up"), (Entry::DownVotes, "Down")] {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Entry::Table)
+                        .value(
+                            column,
+                            SimpleExpr::SubQuery(
+                                None,
+                                Box::new(
+                                    Query::select()
+                                        .expr(Expr::col(Vote::Id).count())
+                                        .from(Vote::Table)
+                                        .and_where(
+                                            Expr::col((Vote::Table, Vote::EntryId))
+                                                .equals((Entry::Table, Entry::Id)),
+                                        )
+                                        .and_where(Expr::col(Vote::Rating).eq(
+                                            Expr::val(rating).as_enum(Alias::new("rating")),
+                                        ))
+                                        .to_owned()
+                                        .into_sub_query_statement(),
+                                ),
+                            ),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // Generated, so that the scores are computed by the database alone
+        // and cursors can point at their exact values.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entry::Table)
+                    .add_column(
+                        ColumnDef::new(Entry::SukelaScore)
+                            .double()
+                            .not_null()
+                            .extra(format!("GENERATED ALWAYS AS ({SUKELA_SCORE}) STORED")),
+                    )
+                    .add_column(
+                        ColumnDef::new(Entry::ControversyScore)
+                            .double()
+                            .not_null()
+                            .extra(format!("GENERATED ALWAYS AS ({CONTROVERSY_SCORE}) STORED")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entry::Table)
+                    .drop_column(Entry::ControversyScore)
+                    .drop_column(Entry::SukelaScore)
+                    .drop_column(Entry::DownVotes)
+                    .drop_column(Entry::UpVotes)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Entry {
+    Table,
+    Id,
+    UpVotes,
+    DownVotes,
+    SukelaScore,
+    ControversyScore,
+}
+
+#[derive(DeriveIden)]
+enum Vote {
+    Table,
+    Id,
+    EntryId,
+    Rating,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+/// Indexes the content with the Turkish configuration. Dotted and dotless
+/// capital I are folded by hand first, as `lower` only knows one of them.
+const SEARCH_VECTOR: &str =
+    r#"to_tsvector('turkish', replace(replace("content", 'I', 'ı'), 'İ', 'i'))"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entry::Table)
+                    .add_column(
+                        ColumnDef::new(Entry::SearchVector)
+                            .custom(Alias::new("tsvector"))
+                            .not_null()
+                            .extra(format!("GENERATED ALWAYS AS ({SEARCH_VECTOR}) STORED")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-entry-search_vector")
+                    .table(Entry::Table)
+                    .col(Entry::SearchVector)
+                    .full_text()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-entry-search_vector")
+                    .table(Entry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entry::Table)
+                    .drop_column(Entry::SearchVector)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Entry {
+    Table,
+    SearchVector,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+/// The name in lower case, with Turkish letters and circumflexes replaced by
+/// their closest ASCII letters, so that "Soğan" and "sogan" fold the same.
+/// Mirrored by `fold_title` in the service.
+const NAME_FOLDED: &str =
+    r#"lower(translate("name", 'ÇĞİIÖŞÜÂÎÛçğıöşüâîû', 'cgiiosuaiucgiosuaiu'))"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Installed in `public` so that every schema in the database can use
+        // it; queries refer to its functions and operators with the schema.
+        manager
+            .get_connection()
+            .execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm WITH SCHEMA public")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Title::Table)
+                    .add_column(
+                        ColumnDef::new(Title::NameFolded)
+                            .text()
+                            .not_null()
+                            .extra(format!("GENERATED ALWAYS AS ({NAME_FOLDED}) STORED")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Trigram similarity, and prefix matches with `LIKE 'abc%'`.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"CREATE INDEX "idx-title-name_folded-trgm" ON "title"
+                    USING gin ("name_folded" public.gin_trgm_ops);
+                CREATE INDEX "idx-title-name_folded-prefix" ON "title"
+                    ("name_folded" text_pattern_ops);"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in ["idx-title-name_folded-prefix", "idx-title-name_folded-trgm"] {
+            manager
+                .drop_index(Index::drop().name(name).table(Title::Table).to_owned())
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Title::Table)
+                    .drop_column(Title::NameFolded)
+                    .to_owned(),
+            )
+            .await?;
+
+        // The extension is left in place, other schemas may still use it.
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Title {
+    Table,
+    NameFolded,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SearchQueryStat::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SearchQueryStat::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQueryStat::Bucket)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQueryStat::Query)
+                            .string_len(100)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQueryStat::SearchCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQueryStat::ZeroResultCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQueryStat::ResultCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-search_query_stat-bucket-query")
+                            .col(SearchQueryStat::Bucket)
+                            .col(SearchQueryStat::Query)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SearchQueryStat::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SearchQueryStat {
+    Table,
+    Id,
+    Bucket,
+    Query,
+    SearchCount,
+    ZeroResultCount,
+    ResultCount,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SearchQuerySearcher::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SearchQuerySearcher::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQuerySearcher::Bucket)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQuerySearcher::Query)
+                            .string_len(100)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SearchQuerySearcher::Searcher)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx-search_query_searcher-bucket-query-searcher")
+                            .col(SearchQuerySearcher::Bucket)
+                            .col(SearchQuerySearcher::Query)
+                            .col(SearchQuerySearcher::Searcher)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SearchQuerySearcher::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SearchQuerySearcher {
+    Table,
+    Id,
+    Bucket,
+    Query,
+    Searcher,
+}
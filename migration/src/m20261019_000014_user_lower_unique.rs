@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Logins match nicknames and e-mail addresses ignoring case. Accounts
+        // that only differ in case from an older one are renamed so that the
+        // unique indexes can be created: the nickname gets the id appended,
+        // and the address is prefixed with it. Renamed users can still log in
+        // with their new nickname.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE "user" SET "nickname" = left("nickname", 29 - length("id"::text)) || '_' || "id"
+                    WHERE EXISTS (
+                        SELECT 1 FROM "user" AS "older"
+                        WHERE lower("older"."nickname") = lower("user"."nickname")
+                            AND "older"."id" < "user"."id"
+                    );
+                UPDATE "user" SET "email" = left("id" || '.' || "email", 320)
+                    WHERE EXISTS (
+                        SELECT 1 FROM "user" AS "older"
+                        WHERE lower("older"."email") = lower("user"."email")
+                            AND "older"."id" < "user"."id"
+                    );
+                CREATE UNIQUE INDEX "idx-user-nickname-lower" ON "user" (lower("nickname"));
+                CREATE UNIQUE INDEX "idx-user-email-lower" ON "user" (lower("email"));"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in ["idx-user-email-lower", "idx-user-nickname-lower"] {
+            manager
+                .drop_index(Index::drop().name(name).table(User::Table).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+}
@@ -0,0 +1,142 @@
+//! Fails if one of the hot queries is planned as a sequential scan once the
+//! database holds a realistic amount of data.
+//!
+//! Needs a PostgreSQL database: set `DATABASE_URL` to run it. The data is
+//! seeded into a schema of its own, which is dropped afterwards.
+
+use migration::{Migrator, MigratorTrait};
+use sea_orm_migration::sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+};
+
+const SCHEMA: &str = "arpacik_query_plans";
+
+const SEED: &str = r#"
+INSERT INTO "user" ("nickname", "email", "password_hash", "is_faded")
+SELECT 'user' || g, 'user' || g || '@example.com', '', false
+FROM generate_series(1, 500) g;
+
+INSERT INTO "user" ("nickname", "email", "password_hash", "is_faded")
+SELECT 'reader' || g, 'reader' || g || '@example.com', '', true
+FROM generate_series(1, 20000) g;
+
+INSERT INTO "title" ("name", "last_entry_at", "is_visible")
+SELECT 'title ' || g || ' ' || md5(g::text), now() - g * interval '1 minute', g % 50 <> 0
+FROM generate_series(1, 20000) g;
+
+INSERT INTO "entry" ("user_id", "title_id", "content", "created_at", "deleted_at")
+SELECT 1 + g % 500, 1 + g % 2000, 'entry ' || g, now() - g * interval '1 minute',
+    CASE WHEN g % 10 = 0 THEN now() END
+FROM generate_series(1, 50000) g;
+
+INSERT INTO "vote" ("rating", "user_id", "entry_id")
+SELECT 'Up', 1 + g % 500, 1 + (g * 7919) % 50000
+FROM generate_series(1, 50000) g;
+
+INSERT INTO "favorite" ("user_id", "entry_id")
+SELECT 1 + g % 500, 1 + (g * 7907) % 50000
+FROM generate_series(1, 20000) g;
+
+INSERT INTO "token" ("user_id", "hash")
+SELECT 1 + g % 500, md5(g::text)
+FROM generate_series(1, 20000) g;
+
+-- Searchers are pruned after a week, so a week of them is kept.
+INSERT INTO "search_query_searcher" ("bucket", "query", "searcher")
+SELECT date_trunc('hour', now()) - (g % 168) * interval '1 hour', 'query ' || g % 5000, md5(g::text)
+FROM generate_series(1, 200000) g;
+
+-- Autovacuum would move new rows from the pending list into the index.
+SELECT gin_clean_pending_list('"idx-entry-search_vector"');
+SELECT gin_clean_pending_list('"idx-title-name_folded-trgm"');
+
+ANALYZE;
+"#;
+
+/// The queries behind title and user listings, `today`, `trends`, the
+/// entry loader, the auth lookups, entry search, title autocompletion,
+/// trending searches and their hourly pruning.
+const QUERIES: [&str; 14] = [
+    r#"SELECT * FROM "entry" WHERE "title_id" = 42 AND "deleted_at" IS NULL
+        ORDER BY "created_at", "id" LIMIT 10"#,
+    r#"SELECT * FROM "entry" WHERE "user_id" = 42 AND "deleted_at" IS NULL
+        ORDER BY "created_at" DESC, "id" DESC LIMIT 10"#,
+    r#"SELECT * FROM "entry" WHERE "user_id" = 42 AND "deleted_at" IS NOT NULL
+        ORDER BY "created_at" DESC LIMIT 10"#,
+    r#"SELECT "title_id", COUNT(*) FROM "entry"
+        WHERE "created_at" > now() - interval '1 hour' AND "deleted_at" IS NULL
+        GROUP BY "title_id""#,
+    r#"SELECT * FROM "vote" WHERE "entry_id" = 42"#,
+    r#"SELECT * FROM "favorite" WHERE "user_id" = 42 ORDER BY "created_at" DESC LIMIT 10"#,
+    r#"SELECT * FROM "token" WHERE "hash" = md5('42') AND "invalidated_at" IS NULL"#,
+    r#"SELECT * FROM "user"
+        WHERE (LOWER("nickname") = LOWER('User42') OR LOWER("email") = LOWER('User42'))
+            AND "deleted_at" IS NULL"#,
+    r#"SELECT * FROM "title" WHERE "is_visible" ORDER BY "last_entry_at" DESC LIMIT 20"#,
+    r#"SELECT "id" FROM "entry" WHERE "search_vector" @@ websearch_to_tsquery('turkish', '4242')"#,
+    r#"SELECT "id" FROM "title" WHERE "name_folded" LIKE 'title 42%'"#,
+    r#"SELECT "id" FROM "title" WHERE "name_folded" OPERATOR(public.%) 'title 42 a1d0c6e83f027327d8461063f4ac58a6'"#,
+    r#"SELECT "query", COUNT(DISTINCT "searcher") FROM "search_query_searcher"
+        WHERE "bucket" >= date_trunc('hour', now()) - interval '23 hours'
+            AND "query" NOT LIKE '%kötü%' ESCAPE '\'
+        GROUP BY "query" HAVING COUNT(DISTINCT "searcher") >= 5
+        ORDER BY COUNT(DISTINCT "searcher") DESC, "query" ASC LIMIT 10"#,
+    r#"DELETE FROM "search_query_searcher"
+        WHERE "bucket" < date_trunc('hour', now()) - interval '167 hours'"#,
+];
+
+async fn plan(db: &DatabaseConnection, query: &str) -> String {
+    db.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!("EXPLAIN {query}"),
+    ))
+    .await
+    .expect("EXPLAIN failed")
+    .iter()
+    .map(|row| row.try_get::<String>("", "QUERY PLAN").unwrap())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[async_std::test]
+async fn test_hot_queries_use_indexes() {
+    let Ok(db_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set; skipping the query plan check.");
+        return;
+    };
+
+    let admin = Database::connect(&db_url).await.unwrap();
+    admin
+        .execute_unprepared(&format!(
+            "DROP SCHEMA IF EXISTS {SCHEMA} CASCADE; CREATE SCHEMA {SCHEMA}"
+        ))
+        .await
+        .unwrap();
+
+    let mut opt = ConnectOptions::new(db_url);
+    opt.set_schema_search_path(SCHEMA);
+    let db = Database::connect(opt).await.unwrap();
+
+    Migrator::up(&db, None).await.unwrap();
+    db.execute_unprepared(SEED).await.unwrap();
+
+    let mut regressions = Vec::new();
+    for query in QUERIES {
+        let plan = plan(&db, query).await;
+        if plan.contains("Seq Scan") {
+            regressions.push(format!("{query}\n{plan}"));
+        }
+    }
+
+    db.close().await.unwrap();
+    admin
+        .execute_unprepared(&format!("DROP SCHEMA {SCHEMA} CASCADE"))
+        .await
+        .unwrap();
+
+    assert!(
+        regressions.is_empty(),
+        "Sequential scans on seeded data:\n\n{}",
+        regressions.join("\n\n")
+    );
+}
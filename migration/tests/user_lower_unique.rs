@@ -0,0 +1,86 @@
+//! Checks that accounts which only differ in case are renamed before the
+//! case-insensitive unique indexes are created.
+//!
+//! Needs a PostgreSQL database: set `DATABASE_URL` to run it.
+
+use migration::{Migrator, MigratorTrait};
+use sea_orm_migration::sea_orm::{ConnectOptions, ConnectionTrait, Database, DbBackend, Statement};
+
+const SCHEMA: &str = "arpacik_user_lower_unique";
+
+/// The migrations that run before the unique indexes are added.
+const BEFORE: u32 = 14;
+
+#[async_std::test]
+async fn test_duplicates_are_renamed() {
+    let Ok(db_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set; skipping the duplicate user check.");
+        return;
+    };
+
+    let admin = Database::connect(&db_url).await.unwrap();
+    admin
+        .execute_unprepared(&format!(
+            "DROP SCHEMA IF EXISTS {SCHEMA} CASCADE; CREATE SCHEMA {SCHEMA}"
+        ))
+        .await
+        .unwrap();
+
+    let mut opt = ConnectOptions::new(db_url);
+    opt.set_schema_search_path(SCHEMA);
+    let db = Database::connect(opt).await.unwrap();
+
+    Migrator::up(&db, Some(BEFORE)).await.unwrap();
+    db.execute_unprepared(
+        r#"INSERT INTO "user" ("nickname", "email", "password_hash") VALUES
+            ('ali', 'ali@example.com', ''),
+            ('Ali', 'ALI@example.com', ''),
+            ('veli', 'Ali@Example.com', '')"#,
+    )
+    .await
+    .unwrap();
+    Migrator::up(&db, None).await.unwrap();
+
+    let users: Vec<(String, String)> = db
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            r#"SELECT "nickname", "email" FROM "user" ORDER BY "id""#,
+        ))
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row.try_get("", "nickname").unwrap(),
+                row.try_get("", "email").unwrap(),
+            )
+        })
+        .collect();
+
+    let duplicate = db
+        .execute_unprepared(
+            r#"INSERT INTO "user" ("nickname", "email", "password_hash")
+                VALUES ('ALI', 'yeni@example.com', '')"#,
+        )
+        .await;
+
+    db.close().await.unwrap();
+    admin
+        .execute_unprepared(&format!("DROP SCHEMA {SCHEMA} CASCADE"))
+        .await
+        .unwrap();
+
+    let users: Vec<(&str, &str)> = users
+        .iter()
+        .map(|(nickname, email)| (nickname.as_str(), email.as_str()))
+        .collect();
+    assert_eq!(
+        users,
+        vec![
+            ("ali", "ali@example.com"),
+            ("Ali_2", "2.ALI@example.com"),
+            ("veli", "3.Ali@Example.com"),
+        ]
+    );
+    assert!(duplicate.is_err());
+}
@@ -0,0 +1,208 @@
+use ::entity::prelude::*;
+use sea_orm::*;
+use sea_query::Expr;
+use validator::Validate;
+
+use crate::{
+    config::AuthConfig,
+    dto::access_token::{
+        AccessTokenDto, CreateAccessTokenRequest, CreateAccessTokenResponse, Scope,
+    },
+    role::permissions,
+    token::{generate_token, hash_token},
+    Error, Result,
+};
+
+/// Personal access tokens carry this prefix so they can be told apart from
+/// session JWTs without a database lookup.
+pub const TOKEN_PREFIX: &str = "arp_";
+const MAX_ACTIVE_TOKENS: u64 = 20;
+const LAST_USED_RESOLUTION_MINUTES: i64 = 5;
+
+/// The owner of a valid personal access token and what it may do.
+#[derive(Debug, Clone)]
+pub struct AccessTokenOwner {
+    pub user_id: i32,
+    pub scopes: Vec<Scope>,
+    /// Whether the owner has two-factor authentication set up.
+    pub has_two_factor: bool,
+}
+
+impl AccessTokenOwner {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+pub fn is_access_token(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
+
+/// Creates a token for `user_id`. `two_factor` tells whether the session
+/// asking for it passed two-factor authentication: when the crew must use
+/// 2FA, a moderation token outlives the session and reaches the crew routes,
+/// so a password alone can't mint one.
+pub async fn create_access_token(
+    db: &DbConn,
+    config: &AuthConfig,
+    user_id: i32,
+    two_factor: bool,
+    request: CreateAccessTokenRequest,
+) -> Result<CreateAccessTokenResponse> {
+    request.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let user = User::find()
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+
+    if request.scopes.contains(&Scope::Moderation) {
+        if permissions(db, user.id).await?.is_empty() {
+            return Err(Error::Forbidden(
+                "Moderasyon yetkisi olmayan kullanıcılar bu kapsamı seçemez.".to_string(),
+            ));
+        }
+
+        if config.require_crew_two_factor && !two_factor {
+            return Err(Error::Forbidden(
+                "Moderasyon anahtarı oluşturmak için iki aşamalı doğrulama ile giriş yapın."
+                    .to_string(),
+            ));
+        }
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+
+    let active_tokens = AccessToken::find()
+        .filter(AccessTokenColumn::UserId.eq(user.id))
+        .filter(AccessTokenColumn::RevokedAt.is_null())
+        .filter(
+            Condition::any()
+                .add(AccessTokenColumn::ExpiresAt.is_null())
+                .add(AccessTokenColumn::ExpiresAt.gt(now)),
+        )
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Erişim anahtarları bulunamadı.".to_string()))?;
+
+    if active_tokens >= MAX_ACTIVE_TOKENS {
+        return Err(Error::InvalidRequest(format!(
+            "En fazla {MAX_ACTIVE_TOKENS} etkin erişim anahtarınız olabilir."
+        )));
+    }
+
+    let mut scopes: Vec<&str> = Vec::new();
+    for scope in &request.scopes {
+        if !scopes.contains(&scope.as_str()) {
+            scopes.push(scope.as_str());
+        }
+    }
+
+    let token = format!("{TOKEN_PREFIX}{}", generate_token());
+
+    let access_token = AccessTokenActiveModel {
+        user_id: Set(user.id),
+        name: Set(request.name.trim().to_string()),
+        hash: Set(hash_token(&token)),
+        scopes: Set(scopes.join(",")),
+        expires_at: Set(request
+            .expires_in_days
+            .map(|days| now + chrono::Duration::days(days.into()))),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|_| Error::InternalError("Erişim anahtarı oluşturulamadı.".to_string()))?;
+
+    Ok(CreateAccessTokenResponse {
+        id: access_token.id,
+        token,
+    })
+}
+
+pub async fn list_access_tokens(db: &DbConn, user_id: i32) -> Result<Vec<AccessTokenDto>> {
+    let tokens = AccessToken::find()
+        .filter(AccessTokenColumn::UserId.eq(user_id))
+        .filter(AccessTokenColumn::RevokedAt.is_null())
+        .order_by_desc(AccessTokenColumn::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Erişim anahtarları bulunamadı.".to_string()))?;
+
+    Ok(tokens.into_iter().map(AccessTokenDto::from).collect())
+}
+
+pub async fn revoke_access_token(db: &DbConn, user_id: i32, id: i32) -> Result<()> {
+    let result = AccessToken::update_many()
+        .col_expr(
+            AccessTokenColumn::RevokedAt,
+            Expr::value(chrono::Utc::now().naive_utc()),
+        )
+        .filter(AccessTokenColumn::Id.eq(id))
+        .filter(AccessTokenColumn::UserId.eq(user_id))
+        .filter(AccessTokenColumn::RevokedAt.is_null())
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Erişim anahtarı iptal edilemedi.".to_string()))?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound("Erişim anahtarı bulunamadı.".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Resolves a personal access token to its owner. Returns `None` for unknown,
+/// revoked or expired tokens and for tokens of deleted users.
+pub async fn authenticate(db: &DbConn, token: &str) -> Result<Option<AccessTokenOwner>> {
+    let now = chrono::Utc::now().naive_utc();
+
+    let found = AccessToken::find()
+        .find_also_related(User)
+        .filter(AccessTokenColumn::Hash.eq(hash_token(token)))
+        .filter(AccessTokenColumn::RevokedAt.is_null())
+        .filter(
+            Condition::any()
+                .add(AccessTokenColumn::ExpiresAt.is_null())
+                .add(AccessTokenColumn::ExpiresAt.gt(now)),
+        )
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Erişim anahtarı bulunamadı.".to_string()))?;
+
+    let (access_token, user) = match found {
+        Some((access_token, Some(user))) => (access_token, user),
+        _ => return Ok(None),
+    };
+
+    // Bots may call the API many times a second; a coarse timestamp is enough
+    // to tell which tokens are still in use.
+    let is_stale = access_token.last_used_at.is_none_or(|last_used_at| {
+        now - last_used_at > chrono::Duration::minutes(LAST_USED_RESOLUTION_MINUTES)
+    });
+
+    if is_stale {
+        AccessToken::update_many()
+            .col_expr(AccessTokenColumn::LastUsedAt, Expr::value(now))
+            .filter(AccessTokenColumn::Id.eq(access_token.id))
+            .exec(db)
+            .await
+            .map_err(|_| Error::InternalError("Erişim anahtarı güncellenemedi.".to_string()))?;
+    }
+
+    Ok(Some(AccessTokenOwner {
+        user_id: user.id,
+        has_two_factor: user.totp_enabled_at.is_some(),
+        scopes: access_token
+            .scopes
+            .split(',')
+            .filter_map(Scope::parse)
+            .collect(),
+    }))
+}
@@ -1,8 +1,15 @@
-use std::env;
+use std::str::FromStr;
 
+use crate::config::{AuthConfig, MailConfig};
 use crate::cookie::Cookie;
-use crate::dto::auth::{LoginRequest, LoginResponse, RegisterRequest};
-use crate::token::{is_admin, is_moderator, validate_token, UserClaims};
+use crate::dto::auth::{LoginRequest, LoginResponse, LoginResult, RegisterRequest};
+use crate::dto::two_factor::{TwoFactorChallengeResponse, TwoFactorLoginRequest};
+use crate::login_attempt::{self, ensure_not_locked};
+use crate::mail::Mailer;
+use crate::role::{permissions, user_roles};
+use crate::token::{hash_token, UserClaims};
+use crate::two_factor::{challenge_user_id, check_code, create_challenge};
+use crate::verification::send_verification;
 use crate::{Error, Result};
 use ::entity::prelude::*;
 use argon2::PasswordVerifier;
@@ -12,31 +19,35 @@ use argon2::{
 };
 use chrono::TimeZone;
 use sea_orm::*;
+use sea_query::{Expr, Func, SimpleExpr};
+use serde::Deserialize;
 use validator::Validate;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum AuthHeader {
     Cookie,
     Authorization,
 }
 
-pub enum Role {
-    Admin,
-    Moderator,
-    User,
-}
-
-pub fn authorize(token: &str, jwt_secret: &str, role: Role) -> bool {
-    let is_valid = validate_token(token, jwt_secret);
+impl FromStr for AuthHeader {
+    type Err = String;
 
-    match role {
-        Role::Admin => is_admin(token, jwt_secret) && is_valid,
-        Role::Moderator => is_moderator(token, jwt_secret) && is_valid,
-        Role::User => is_valid,
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "cookie" => Ok(Self::Cookie),
+            "authorization" => Ok(Self::Authorization),
+            _ => Err("expected `cookie` or `authorization`".to_string()),
+        }
     }
 }
 
-pub async fn register(db: &DbConn, request: RegisterRequest) -> Result<UserActiveModel> {
+pub async fn register(
+    db: &DbConn,
+    mailer: &dyn Mailer,
+    config: &MailConfig,
+    request: RegisterRequest,
+) -> Result<UserModel> {
     match request.validate() {
         Ok(_) => (),
         Err(_) => {
@@ -59,23 +70,67 @@ pub async fn register(db: &DbConn, request: RegisterRequest) -> Result<UserActiv
         password_hash: Set(password_hash),
         ..Default::default()
     }
-    .save(db)
+    .insert(db)
     .await;
 
-    match result {
-        Ok(user) => Ok(user),
+    // Nicknames and addresses are unique ignoring case, so "Ali" and "ali"
+    // can't both exist.
+    let user = match result {
+        Ok(user) => user,
         Err(e) => {
-            if e.to_string().contains("duplicate key value") {
+            if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
                 return Err(Error::InvalidRequest(
                     "Bu e-posta adresi veya kullanıcı adı zaten kullanımda.".to_string(),
                 ));
             }
-            Err(Error::InternalError(e.to_string()))
+            return Err(Error::InternalError(e.to_string()));
         }
+    };
+
+    // The account already exists at this point; if the mail can't be sent the
+    // user can ask for a new one through the resend endpoint.
+    let _ = send_verification(db, mailer, config, &user).await;
+
+    Ok(user)
+}
+
+fn lower_eq(column: UserColumn, value: &str) -> SimpleExpr {
+    Expr::expr(Func::lower(Expr::col(column))).eq(Func::lower(Expr::val(value.trim())))
+}
+
+/// Finds the user by nickname or email, ignoring case.
+async fn find_login_user(db: &DbConn, identifier: &str) -> Result<Option<UserModel>> {
+    let users = User::find()
+        .filter(
+            Condition::any()
+                .add(lower_eq(UserColumn::Nickname, identifier))
+                .add(lower_eq(UserColumn::Email, identifier)),
+        )
+        .filter(UserColumn::DeletedAt.is_null())
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))?;
+
+    // A nickname may look like the address of another account; an exact
+    // match wins, otherwise the login is ambiguous.
+    let identifier = identifier.trim();
+    let exact = users
+        .iter()
+        .position(|user| user.nickname == identifier || user.email == identifier);
+
+    match (exact, users.len()) {
+        (Some(i), _) => Ok(users.into_iter().nth(i)),
+        (None, 1) => Ok(users.into_iter().next()),
+        _ => Ok(None),
     }
 }
 
-pub async fn login(db: &DbConn, request: LoginRequest) -> Result<LoginResponse> {
+pub async fn login(
+    db: &DbConn,
+    config: &AuthConfig,
+    request: LoginRequest,
+    ip: Option<&str>,
+) -> Result<LoginResult> {
     match request.validate() {
         Ok(_) => (),
         Err(_) => {
@@ -85,14 +140,18 @@ pub async fn login(db: &DbConn, request: LoginRequest) -> Result<LoginResponse>
         }
     }
 
-    let user = User::find()
-        .filter(UserColumn::Nickname.contains(request.nickname))
-        .filter(UserColumn::DeletedAt.is_null())
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))?;
+    ensure_not_locked(db, None, ip).await?;
+
+    let user = match find_login_user(db, &request.nickname).await? {
+        Some(user) => user,
+        None => {
+            login_attempt::record(db, None, &request.nickname, ip, false).await?;
+            return Err(Error::InvalidCredentials);
+        }
+    };
+
+    ensure_not_locked(db, Some(user.id), None).await?;
 
-    let user = user.ok_or(Error::InvalidCredentials)?;
     let parsed_hash = match argon2::PasswordHash::new(&user.password_hash) {
         Ok(parsed_hash) => parsed_hash,
         Err(_) => {
@@ -107,29 +166,86 @@ pub async fn login(db: &DbConn, request: LoginRequest) -> Result<LoginResponse>
         .verify_password(request.password.as_bytes(), &parsed_hash)
         .is_err()
     {
+        login_attempt::record(db, Some(user.id), &request.nickname, ip, false).await?;
         return Err(Error::InvalidCredentials);
     }
 
+    // With 2FA the attempt is recorded once the second step is done, so that
+    // a known password doesn't reset the failure count of the code.
+    if user.totp_enabled_at.is_some() {
+        let challenge = create_challenge(
+            user.id,
+            config.jwt_secret.as_bytes(),
+            config.two_factor_challenge_seconds,
+        )?;
+
+        return Ok(LoginResult::TwoFactorRequired(TwoFactorChallengeResponse {
+            two_factor_required: true,
+            challenge,
+        }));
+    }
+
+    login_attempt::record(db, Some(user.id), &request.nickname, ip, true).await?;
+
+    issue_token(db, config, user, false)
+        .await
+        .map(LoginResult::Success)
+}
+
+pub async fn login_two_factor(
+    db: &DbConn,
+    config: &AuthConfig,
+    request: TwoFactorLoginRequest,
+    ip: Option<&str>,
+) -> Result<LoginResponse> {
+    request.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let user_id = challenge_user_id(&request.challenge, config.jwt_secret.as_bytes())
+        .ok_or(Error::InvalidToken)?;
+
+    let user = User::find()
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))?
+        .ok_or(Error::InvalidToken)?;
+
+    ensure_not_locked(db, Some(user.id), ip).await?;
+
+    if let Err(e) = check_code(db, &user, &request.code).await {
+        login_attempt::record(db, Some(user.id), &user.nickname, ip, false).await?;
+        return Err(e);
+    }
+
+    login_attempt::record(db, Some(user.id), &user.nickname, ip, true).await?;
+
+    issue_token(db, config, user, true).await
+}
+
+async fn issue_token(
+    db: &DbConn,
+    config: &AuthConfig,
+    user: UserModel,
+    two_factor: bool,
+) -> Result<LoginResponse> {
+    let now = chrono::Utc::now().timestamp();
     let claims = UserClaims {
         id: user.id,
         nickname: user.nickname,
         email: user.email,
-        is_admin: user.is_admin,
-        is_moderator: user.is_moderator,
         is_faded: user.is_faded,
-        iat: chrono::Utc::now().timestamp(),
-        exp: chrono::Utc::now().timestamp() + 60 * 60 * 24,
-    };
-
-    let key = match env::var("JWT_SECRET") {
-        Ok(key) => key.into_bytes(),
-        Err(_) => return Err(Error::InternalError("JWT_SECRET is not set".to_string())),
+        two_factor,
+        iat: now,
+        exp: now + config.session_lifetime_hours * 60 * 60,
     };
 
     let token = jsonwebtoken::encode(
         &jsonwebtoken::Header::default(),
         &claims,
-        &jsonwebtoken::EncodingKey::from_secret(&key),
+        &jsonwebtoken::EncodingKey::from_secret(config.jwt_secret.as_bytes()),
     );
 
     let token = match token {
@@ -137,7 +253,7 @@ pub async fn login(db: &DbConn, request: LoginRequest) -> Result<LoginResponse>
         Err(_) => return Err(Error::InternalError("Token oluşturulamadı.".to_string())),
     };
 
-    let token_hash = blake3::hash(token.as_bytes()).to_hex().to_string();
+    let token_hash = hash_token(&token);
 
     let result = TokenActiveModel {
         user_id: Set(user.id),
@@ -162,15 +278,17 @@ pub async fn login(db: &DbConn, request: LoginRequest) -> Result<LoginResponse>
     let response = LoginResponse {
         cookie,
         token,
-        is_admin: user.is_admin,
-        is_moderator: user.is_moderator,
+        roles: user_roles(db, user.id).await?,
+        permissions: permissions(db, user.id).await?,
+        is_email_verified: user.email_verified_at.is_some(),
+        is_two_factor_enabled: user.totp_enabled_at.is_some(),
     };
 
     Ok(response)
 }
 
 pub async fn logout(db: &DbConn, token: &str) -> Result<Cookie> {
-    let token_hash = blake3::hash(token.as_bytes()).to_hex().to_string();
+    let token_hash = hash_token(token);
 
     let token = Token::find()
         .filter(TokenColumn::Hash.eq(token_hash))
@@ -196,7 +314,7 @@ pub async fn logout(db: &DbConn, token: &str) -> Result<Cookie> {
 }
 
 async fn _is_token_invalidated(db: &DbConn, token: &str) -> bool {
-    let token_hash = blake3::hash(token.as_bytes()).to_hex().to_string();
+    let token_hash = hash_token(token);
 
     let token = Token::find()
         .filter(TokenColumn::Hash.eq(token_hash))
@@ -0,0 +1,701 @@
+//! Settings of the server. They are read from an optional TOML file and then
+//! from the environment, so that a variable such as `PORT` overrides
+//! `server.port` in the file. Everything is checked once at startup and all
+//! problems are reported together.
+
+use std::{env, fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr};
+
+use serde::Deserialize;
+use thiserror::Error;
+use toml_edit::{Item, Value};
+
+use crate::{
+    auth::AuthHeader,
+    debe::DebeConfig,
+    time::{Tz, DEFAULT_TIMEZONE},
+    trends::TrendWeights,
+};
+
+/// The file read when `CONFIG_FILE` is not set, if it exists.
+pub const DEFAULT_CONFIG_FILE: &str = "arpacik.toml";
+
+/// The shortest JWT secret or metrics token accepted.
+const MIN_SECRET_LENGTH: usize = 16;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("{path}: {message}")]
+    File { path: String, message: String },
+    #[error("Invalid configuration:{}", list(.0))]
+    Invalid(Vec<String>),
+}
+
+fn list(problems: &[String]) -> String {
+    problems
+        .iter()
+        .map(|problem| format!("\n  - {problem}"))
+        .collect()
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The timezone that days, such as those of `today` and the debe, are
+    /// counted in.
+    pub timezone: Tz,
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub mail: MailConfig,
+    pub search: SearchConfig,
+    pub trends: TrendWeights,
+    pub debe: DebeConfig,
+    pub metrics: MetricsConfig,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Whether the client address is taken from `X-Forwarded-For`. Only
+    /// enable it behind a proxy that sets the header.
+    pub trust_proxy: bool,
+    /// The origins browsers may call the API from. Any origin when empty.
+    pub cors_origins: Vec<String>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    /// The schema the tables are kept in. `arpacik` by default, like the
+    /// schema `sql/init.sql` creates.
+    pub schema: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    /// How long a request waits for a free connection, in seconds.
+    pub acquire_timeout: u64,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub jwt_secret: String,
+    /// Where session tokens are read from.
+    pub from: AuthHeader,
+    pub session_lifetime_hours: i64,
+    /// How long the password step of a login with 2FA stays valid.
+    pub two_factor_challenge_seconds: i64,
+    /// Whether only verified users can write entries.
+    pub require_email_verification: bool,
+    /// Whether crew routes need a session that passed 2FA. Moderation access
+    /// tokens can then only be created from such a session, and only work
+    /// while their owner has 2FA set up.
+    pub require_crew_two_factor: bool,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    /// Mails are only logged when it is not set.
+    pub smtp_url: Option<String>,
+    pub from: Option<String>,
+    /// The page of the client that verifies e-mail addresses. The token is
+    /// appended to it.
+    pub verification_url: String,
+    pub verification_lifetime_hours: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchEngine {
+    /// Full-text search of PostgreSQL.
+    Postgres,
+    /// An embedded Tantivy index.
+    Tantivy,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    pub backend: SearchEngine,
+    pub index_path: PathBuf,
+    /// Words that keep a query off the public trending list, such as slurs
+    /// or names that shouldn't be pushed there.
+    pub trending_denylist: Vec<String>,
+}
+
+/// Where Prometheus can scrape `/metrics`. Metrics aren't served when neither
+/// is set.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// The bearer token that opens `/metrics` on the API listener.
+    pub token: Option<String>,
+    /// A separate address, such as one only reachable from inside the
+    /// cluster, that serves `/metrics` without a token.
+    pub listen: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            timezone: DEFAULT_TIMEZONE,
+            server: ServerConfig::default(),
+            database: DatabaseConfig::default(),
+            auth: AuthConfig::default(),
+            mail: MailConfig::default(),
+            search: SearchConfig::default(),
+            trends: TrendWeights::default(),
+            debe: DebeConfig::default(),
+            metrics: MetricsConfig::default(),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8099,
+            trust_proxy: false,
+            cors_origins: Vec::new(),
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            schema: "arpacik".to_string(),
+            max_connections: 10,
+            min_connections: 1,
+            acquire_timeout: 30,
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: String::new(),
+            from: AuthHeader::Cookie,
+            session_lifetime_hours: 24,
+            two_factor_challenge_seconds: 60 * 5,
+            require_email_verification: false,
+            require_crew_two_factor: false,
+        }
+    }
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            smtp_url: None,
+            from: None,
+            verification_url: String::new(),
+            verification_lifetime_hours: 24,
+        }
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            backend: SearchEngine::Postgres,
+            index_path: PathBuf::from("search-index"),
+            trending_denylist: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for SearchEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(Self::Postgres),
+            "tantivy" => Ok(Self::Tantivy),
+            _ => Err("expected `postgres` or `tantivy`".to_string()),
+        }
+    }
+}
+
+/// Applies environment variables over the file, collecting the ones that
+/// can't be parsed.
+struct Overrides<F> {
+    env: F,
+    problems: Vec<String>,
+}
+
+impl<F: Fn(&str) -> Option<String>> Overrides<F> {
+    fn set<T>(&mut self, name: &str, target: &mut T)
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let Some(value) = (self.env)(name) else {
+            return;
+        };
+
+        match value.parse() {
+            Ok(value) => *target = value,
+            Err(err) => self
+                .problems
+                .push(format!("{name} is not valid (`{value}`): {err}")),
+        }
+    }
+
+    fn set_optional(&mut self, name: &str, target: &mut Option<String>) {
+        if let Some(value) = (self.env)(name) {
+            *target = Some(value);
+        }
+    }
+
+    fn set_list(&mut self, name: &str, target: &mut Vec<String>) {
+        if let Some(value) = (self.env)(name) {
+            *target = value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+    }
+}
+
+impl Config {
+    /// Reads the file named by `CONFIG_FILE`, or `arpacik.toml` if there is
+    /// one, and then the environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let env = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+
+        let path = match env("CONFIG_FILE") {
+            Some(path) => Some(path),
+            None if std::path::Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Some(DEFAULT_CONFIG_FILE.to_string())
+            }
+            None => None,
+        };
+
+        let text = match &path {
+            Some(path) => Some(
+                std::fs::read_to_string(path).map_err(|err| ConfigError::File {
+                    path: path.clone(),
+                    message: err.to_string(),
+                })?,
+            ),
+            None => None,
+        };
+
+        Self::from_sources(path.as_deref().zip(text.as_deref()), env)
+    }
+
+    /// Builds the configuration from the path and the text of a TOML file,
+    /// and a lookup of environment variables.
+    pub fn from_sources(
+        file: Option<(&str, &str)>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = match file {
+            Some((path, text)) => parse_file(text).map_err(|message| ConfigError::File {
+                path: path.to_string(),
+                message,
+            })?,
+            None => Self::default(),
+        };
+
+        let mut overrides = Overrides {
+            env,
+            problems: Vec::new(),
+        };
+        config.apply(&mut overrides);
+
+        let mut problems = overrides.problems;
+        problems.extend(config.problems());
+
+        match problems.is_empty() {
+            true => Ok(config),
+            false => Err(ConfigError::Invalid(problems)),
+        }
+    }
+
+    fn apply<F: Fn(&str) -> Option<String>>(&mut self, env: &mut Overrides<F>) {
+        env.set("TIMEZONE", &mut self.timezone);
+
+        env.set("HOST", &mut self.server.host);
+        env.set("PORT", &mut self.server.port);
+        env.set("TRUST_PROXY", &mut self.server.trust_proxy);
+        env.set_list("CORS_ORIGINS", &mut self.server.cors_origins);
+
+        env.set("DATABASE_URL", &mut self.database.url);
+        env.set("DATABASE_SCHEMA", &mut self.database.schema);
+        env.set(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+        );
+        env.set(
+            "DATABASE_MIN_CONNECTIONS",
+            &mut self.database.min_connections,
+        );
+        env.set(
+            "DATABASE_ACQUIRE_TIMEOUT",
+            &mut self.database.acquire_timeout,
+        );
+
+        env.set("JWT_SECRET", &mut self.auth.jwt_secret);
+        env.set("AUTH_FROM", &mut self.auth.from);
+        env.set(
+            "SESSION_LIFETIME_HOURS",
+            &mut self.auth.session_lifetime_hours,
+        );
+        env.set(
+            "TWO_FACTOR_CHALLENGE_SECONDS",
+            &mut self.auth.two_factor_challenge_seconds,
+        );
+        env.set(
+            "REQUIRE_EMAIL_VERIFICATION",
+            &mut self.auth.require_email_verification,
+        );
+        env.set(
+            "REQUIRE_CREW_TWO_FACTOR",
+            &mut self.auth.require_crew_two_factor,
+        );
+
+        env.set_optional("SMTP_URL", &mut self.mail.smtp_url);
+        env.set_optional("MAIL_FROM", &mut self.mail.from);
+        env.set("EMAIL_VERIFICATION_URL", &mut self.mail.verification_url);
+        env.set(
+            "VERIFICATION_LIFETIME_HOURS",
+            &mut self.mail.verification_lifetime_hours,
+        );
+
+        env.set("SEARCH_BACKEND", &mut self.search.backend);
+        env.set("SEARCH_INDEX_PATH", &mut self.search.index_path);
+        env.set_list(
+            "SEARCH_TRENDING_DENYLIST",
+            &mut self.search.trending_denylist,
+        );
+
+        env.set("TREND_ENTRY_WEIGHT", &mut self.trends.entry);
+        env.set("TREND_AUTHOR_WEIGHT", &mut self.trends.author);
+        env.set("TREND_VOTE_WEIGHT", &mut self.trends.vote);
+        env.set("TREND_FAVORITE_WEIGHT", &mut self.trends.favorite);
+        env.set("TREND_HALF_LIFE", &mut self.trends.half_life);
+        env.set("TREND_FADED_AUTHOR_WEIGHT", &mut self.trends.faded_author);
+        env.set("TREND_REPEAT_ENTRY_WEIGHT", &mut self.trends.repeat_entry);
+
+        env.set("DEBE_VOTE_WEIGHT", &mut self.debe.vote_weight);
+        env.set("DEBE_FAVORITE_WEIGHT", &mut self.debe.favorite_weight);
+        env.set("DEBE_SIZE", &mut self.debe.size);
+
+        env.set_optional("METRICS_TOKEN", &mut self.metrics.token);
+        env.set_optional("METRICS_LISTEN", &mut self.metrics.listen);
+    }
+
+    /// Lists what is missing or out of range, naming both the key and the
+    /// variable that set it.
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: &str| {
+            if !ok {
+                problems.push(problem.to_string());
+            }
+        };
+
+        check(
+            !self.database.url.is_empty(),
+            "database.url (DATABASE_URL) is not set",
+        );
+        check(
+            !self.database.schema.is_empty(),
+            "database.schema (DATABASE_SCHEMA) is not set",
+        );
+        check(
+            self.database
+                .schema
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+            "database.schema (DATABASE_SCHEMA) can only hold lower case letters, digits and underscores",
+        );
+        check(
+            self.database.max_connections > 0,
+            "database.max_connections (DATABASE_MAX_CONNECTIONS) must be at least 1",
+        );
+        check(
+            self.database.min_connections <= self.database.max_connections,
+            "database.min_connections (DATABASE_MIN_CONNECTIONS) can't be more than database.max_connections",
+        );
+        check(
+            !self.auth.jwt_secret.is_empty(),
+            "auth.jwt_secret (JWT_SECRET) is not set",
+        );
+        check(
+            self.auth.jwt_secret.is_empty() || self.auth.jwt_secret.len() >= MIN_SECRET_LENGTH,
+            "auth.jwt_secret (JWT_SECRET) must be at least 16 characters long",
+        );
+        check(
+            self.auth.session_lifetime_hours > 0,
+            "auth.session_lifetime_hours (SESSION_LIFETIME_HOURS) must be positive",
+        );
+        check(
+            self.auth.two_factor_challenge_seconds > 0,
+            "auth.two_factor_challenge_seconds (TWO_FACTOR_CHALLENGE_SECONDS) must be positive",
+        );
+        check(
+            !self.mail.verification_url.is_empty(),
+            "mail.verification_url (EMAIL_VERIFICATION_URL) is not set",
+        );
+        check(
+            self.mail.verification_lifetime_hours > 0,
+            "mail.verification_lifetime_hours (VERIFICATION_LIFETIME_HOURS) must be positive",
+        );
+        check(
+            self.mail.smtp_url.is_none() || self.mail.from.is_some(),
+            "mail.from (MAIL_FROM) is needed to send mail through mail.smtp_url",
+        );
+        check(
+            self.server
+                .cors_origins
+                .iter()
+                .all(|origin| origin.starts_with("http://") || origin.starts_with("https://")),
+            "server.cors_origins (CORS_ORIGINS) must start with http:// or https://",
+        );
+        check(
+            self.trends.half_life > 0.0,
+            "trends.half_life (TREND_HALF_LIFE) must be positive",
+        );
+        check(
+            self.debe.size > 0,
+            "debe.size (DEBE_SIZE) must be at least 1",
+        );
+        check(
+            self.metrics
+                .token
+                .as_ref()
+                .is_none_or(|token| token.len() >= MIN_SECRET_LENGTH),
+            "metrics.token (METRICS_TOKEN) must be at least 16 characters long",
+        );
+        check(
+            self.metrics
+                .listen
+                .as_ref()
+                .is_none_or(|listen| listen.parse::<SocketAddr>().is_ok()),
+            "metrics.listen (METRICS_LISTEN) must be an address such as 127.0.0.1:9090",
+        );
+
+        problems
+    }
+}
+
+/// Reads a TOML file over the defaults. Keys left out keep their defaults,
+/// and unknown keys are rejected so that typos don't go unnoticed.
+fn parse_file(text: &str) -> Result<Config, String> {
+    let document = text
+        .parse::<toml_edit::Document>()
+        .map_err(|err| err.to_string())?;
+    let value = item_json(document.as_item());
+
+    serde_path_to_error::deserialize(value).map_err(|err| match err.path().to_string() {
+        path if path == "." => err.inner().to_string(),
+        path => format!("`{path}`: {}", err.inner()),
+    })
+}
+
+fn item_json(item: &Item) -> serde_json::Value {
+    match item {
+        Item::None => serde_json::Value::Null,
+        Item::Value(value) => value_json(value),
+        Item::Table(table) => table
+            .iter()
+            .map(|(key, item)| (key.to_string(), item_json(item)))
+            .collect(),
+        Item::ArrayOfTables(tables) => tables
+            .iter()
+            .map(|table| item_json(&Item::Table(table.clone())))
+            .collect(),
+    }
+}
+
+fn value_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(value) => value.value().clone().into(),
+        Value::Integer(value) => (*value.value()).into(),
+        Value::Float(value) => (*value.value()).into(),
+        Value::Boolean(value) => (*value.value()).into(),
+        Value::Datetime(value) => value.value().to_string().into(),
+        Value::Array(values) => values.iter().map(value_json).collect(),
+        Value::InlineTable(table) => table
+            .iter()
+            .map(|(key, value)| (key.to_string(), value_json(value)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn load(file: Option<&str>, vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Config::from_sources(file.map(|text| ("arpacik.toml", text)), |name| {
+            vars.get(name).cloned()
+        })
+    }
+
+    fn problems(result: Result<Config, ConfigError>) -> Vec<String> {
+        match result {
+            Err(ConfigError::Invalid(problems)) => problems,
+            Err(err) => vec![err.to_string()],
+            Ok(_) => Vec::new(),
+        }
+    }
+
+    const REQUIRED: [(&str, &str); 3] = [
+        ("DATABASE_URL", "postgres://localhost/sogan"),
+        ("JWT_SECRET", "VcHCJhYoXGGL7awzIL6woA=="),
+        (
+            "EMAIL_VERIFICATION_URL",
+            "http://localhost:3000/verify-email",
+        ),
+    ];
+
+    #[test]
+    fn test_defaults_need_the_secrets() {
+        assert_eq!(
+            problems(load(None, &[])),
+            vec![
+                "database.url (DATABASE_URL) is not set",
+                "auth.jwt_secret (JWT_SECRET) is not set",
+                "mail.verification_url (EMAIL_VERIFICATION_URL) is not set",
+            ]
+        );
+
+        let config = load(None, &REQUIRED).ok().unwrap();
+        assert_eq!(config.server.port, 8099);
+        assert_eq!(config.database.schema, "arpacik");
+        assert_eq!(config.timezone, DEFAULT_TIMEZONE);
+        assert_eq!(config.search.backend, SearchEngine::Postgres);
+    }
+
+    #[test]
+    fn test_environment_overrides_file() {
+        let file = r#"
+            timezone = "Europe/Berlin"
+
+            [server]
+            port = 8000
+            cors_origins = ["https://sogan.example"]
+
+            [database]
+            url = "postgres://file/sogan"
+            max_connections = 20
+
+            [trends]
+            vote = 1
+
+            [search]
+            backend = "tantivy"
+        "#;
+
+        let config = load(
+            Some(file),
+            &[
+                ("PORT", "9000"),
+                ("JWT_SECRET", "VcHCJhYoXGGL7awzIL6woA=="),
+                ("EMAIL_VERIFICATION_URL", "http://localhost/verify"),
+                ("AUTH_FROM", "authorization"),
+                ("CORS_ORIGINS", "https://a.example, https://b.example"),
+            ],
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(config.server.port, 9000);
+        assert_eq!(
+            config.server.cors_origins,
+            vec!["https://a.example", "https://b.example"]
+        );
+        assert_eq!(config.database.url, "postgres://file/sogan");
+        assert_eq!(config.database.max_connections, 20);
+        assert_eq!(config.database.min_connections, 1);
+        assert_eq!(config.trends.vote, 1.0);
+        assert_eq!(config.timezone, chrono_tz::Europe::Berlin);
+        assert_eq!(config.search.backend, SearchEngine::Tantivy);
+        assert!(matches!(config.auth.from, AuthHeader::Authorization));
+    }
+
+    #[test]
+    fn test_invalid_variables_are_all_reported() {
+        let mut vars = REQUIRED.to_vec();
+        vars.extend([
+            ("PORT", "eighty"),
+            ("AUTH_FROM", "header"),
+            ("REQUIRE_CREW_TWO_FACTOR", "yes"),
+            ("DATABASE_SCHEMA", "arpacik\"; DROP"),
+            ("DEBE_SIZE", "0"),
+        ]);
+
+        assert_eq!(
+            problems(load(None, &vars)),
+            vec![
+                "PORT is not valid (`eighty`): invalid digit found in string",
+                "AUTH_FROM is not valid (`header`): expected `cookie` or `authorization`",
+                "REQUIRE_CREW_TWO_FACTOR is not valid (`yes`): provided string was not `true` or `false`",
+                "database.schema (DATABASE_SCHEMA) can only hold lower case letters, digits and underscores",
+                "debe.size (DEBE_SIZE) must be at least 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_errors_name_the_key() {
+        assert_eq!(
+            problems(load(Some("[server]\nport = \"80\""), &REQUIRED)),
+            vec!["arpacik.toml: `server.port`: invalid type: string \"80\", expected u16"]
+        );
+        assert_eq!(
+            problems(load(Some("[auth]\nform = \"cookie\""), &REQUIRED)),
+            vec!["arpacik.toml: `auth.form`: unknown field `form`, expected one of `jwt_secret`, `from`, `session_lifetime_hours`, `two_factor_challenge_seconds`, `require_email_verification`, `require_crew_two_factor`"]
+        );
+    }
+
+    #[test]
+    fn test_metrics_need_a_strong_token_and_an_address() {
+        let mut vars = REQUIRED.to_vec();
+        vars.extend([("METRICS_TOKEN", "short"), ("METRICS_LISTEN", "localhost")]);
+
+        assert_eq!(
+            problems(load(None, &vars)),
+            vec![
+                "metrics.token (METRICS_TOKEN) must be at least 16 characters long",
+                "metrics.listen (METRICS_LISTEN) must be an address such as 127.0.0.1:9090",
+            ]
+        );
+
+        let config = load(Some("[metrics]\nlisten = \"0.0.0.0:9090\""), &REQUIRED)
+            .ok()
+            .unwrap();
+        assert_eq!(config.metrics.listen.as_deref(), Some("0.0.0.0:9090"));
+        assert_eq!(config.metrics.token, None);
+    }
+
+    #[test]
+    fn test_mail_needs_a_sender() {
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("SMTP_URL", "smtp://localhost"));
+
+        assert_eq!(
+            problems(load(None, &vars)),
+            vec!["mail.from (MAIL_FROM) is needed to send mail through mail.smtp_url"]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use ::entity::prelude::*;
+use sea_orm::*;
+use sea_query::{Expr, Func, SimpleExpr};
+
+use crate::{Error, Result};
+
+/// Counts the votes of the entry with the given rating.
+fn vote_count(rating: Rating) -> SimpleExpr {
+    SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            Vote::find()
+                .select_only()
+                .expr(Expr::col(VoteColumn::Id).count())
+                .filter(Expr::col((Vote, VoteColumn::EntryId)).equals((Entry, EntryColumn::Id)))
+                .filter(VoteColumn::Rating.eq(rating))
+                .into_query()
+                .into_sub_query_statement(),
+        ),
+    )
+}
+
+/// Recomputes the vote counters and `total_favorites` of every entry from
+/// the `vote` and `favorite` tables. Returns the number of entries whose
+/// counters were off.
+pub async fn reconcile_counters(db: &DbConn) -> Result<u64> {
+    let net_votes = SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            Vote::find()
+                .select_only()
+                .expr(Func::coalesce([
+                    Func::sum(Expr::case(VoteColumn::Rating.eq(Rating::Up), 1).finally(-1)).into(),
+                    Expr::val(0).into(),
+                ]))
+                .filter(Expr::col((Vote, VoteColumn::EntryId)).equals((Entry, EntryColumn::Id)))
+                .into_query()
+                .into_sub_query_statement(),
+        ),
+    );
+
+    let total_favorites = SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            Favorite::find()
+                .select_only()
+                .expr(Expr::col(FavoriteColumn::Id).count())
+                .filter(
+                    Expr::col((Favorite, FavoriteColumn::EntryId)).equals((Entry, EntryColumn::Id)),
+                )
+                .into_query()
+                .into_sub_query_statement(),
+        ),
+    );
+
+    let up_votes = vote_count(Rating::Up);
+    let down_votes = vote_count(Rating::Down);
+
+    let result = Entry::update_many()
+        .col_expr(EntryColumn::NetVotes, net_votes.clone())
+        .col_expr(EntryColumn::UpVotes, up_votes.clone())
+        .col_expr(EntryColumn::DownVotes, down_votes.clone())
+        .col_expr(EntryColumn::TotalFavorites, total_favorites.clone())
+        .filter(
+            Condition::any()
+                .add(Expr::col(EntryColumn::NetVotes).ne(net_votes))
+                .add(Expr::col(EntryColumn::UpVotes).ne(up_votes))
+                .add(Expr::col(EntryColumn::DownVotes).ne(down_votes))
+                .add(Expr::col(EntryColumn::TotalFavorites).ne(total_favorites)),
+        )
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Sayaçlar güncellenemedi.".to_string()))?;
+
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use ::entity::prelude::*;
+use sea_orm::*;
+use sea_query::Expr;
+use serde::Deserialize;
+
+use crate::{
+    dto::debe::{DebeDto, DebeEntryDto},
+    loader::entry_dtos,
+    time::{self, Tz},
+    Error, Result,
+};
+
+/// How the entries of a day are ranked for its debe, and how many are kept.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebeConfig {
+    pub vote_weight: f64,
+    pub favorite_weight: f64,
+    pub size: u64,
+}
+
+impl Default for DebeConfig {
+    fn default() -> Self {
+        Self {
+            vote_weight: 1.0,
+            favorite_weight: 2.0,
+            size: 50,
+        }
+    }
+}
+
+pub fn yesterday(tz: Tz) -> chrono::NaiveDate {
+    time::today(tz) - chrono::Duration::days(1)
+}
+
+/// Ranks the entries written on `date` in `tz` and stores the ranking, so
+/// that later votes don't change it. Returns the number of entries in the
+/// snapshot, or `None` if there already is one for the date.
+pub async fn create_snapshot(
+    db: &DbConn,
+    config: &DebeConfig,
+    tz: Tz,
+    date: chrono::NaiveDate,
+) -> Result<Option<usize>> {
+    if date >= time::today(tz) {
+        return Err(Error::InvalidRequest(
+            "Debe yalnızca geçmiş günler için oluşturulabilir.".to_string(),
+        ));
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("Debe oluşturulamadı.".to_string()))?;
+
+    let exists = DebeEntry::find()
+        .filter(DebeEntryColumn::Date.eq(date))
+        .count(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Debe bulunamadı.".to_string()))?
+        > 0;
+
+    if exists {
+        return Ok(None);
+    }
+
+    let (start, end) = time::day_bounds(date, tz);
+    let score = Expr::col((Entry, EntryColumn::NetVotes))
+        .mul(config.vote_weight)
+        .add(Expr::col((Entry, EntryColumn::TotalFavorites)).mul(config.favorite_weight));
+
+    let entries: Vec<(i32, i32, i32)> = Entry::find()
+        .select_only()
+        .column(EntryColumn::Id)
+        .column(EntryColumn::NetVotes)
+        .column(EntryColumn::TotalFavorites)
+        .filter(EntryColumn::CreatedAt.gte(start))
+        .filter(EntryColumn::CreatedAt.lt(end))
+        .filter(EntryColumn::DeletedAt.is_null())
+        .filter(Expr::expr(score.clone()).gt(0))
+        .inner_join(Title)
+        .filter(TitleColumn::IsVisible.eq(true))
+        .inner_join(User)
+        .filter(UserColumn::DeletedAt.is_null())
+        .filter(UserColumn::IsFaded.eq(false))
+        .order_by(score, Order::Desc)
+        .order_by_desc(EntryColumn::NetVotes)
+        .order_by_asc(EntryColumn::Id)
+        .limit(config.size)
+        .into_tuple()
+        .all(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?;
+
+    if entries.is_empty() {
+        return Ok(Some(0));
+    }
+
+    let count = entries.len();
+    let models =
+        entries
+            .into_iter()
+            .zip(1..)
+            .map(
+                |((entry_id, net_votes, total_favorites), rank)| DebeEntryActiveModel {
+                    date: Set(date),
+                    rank: Set(rank),
+                    entry_id: Set(entry_id),
+                    score: Set(f64::from(net_votes) * config.vote_weight
+                        + f64::from(total_favorites) * config.favorite_weight),
+                    net_votes: Set(net_votes),
+                    total_favorites: Set(total_favorites),
+                    ..Default::default()
+                },
+            );
+
+    let result = DebeEntry::insert_many(models).exec(&txn).await;
+
+    match result {
+        Ok(_) => {}
+        // Another instance stored the snapshot in the meantime.
+        Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+            return Ok(None)
+        }
+        Err(_) => return Err(Error::InternalError("Debe oluşturulamadı.".to_string())),
+    }
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Debe oluşturulamadı.".to_string()))?;
+
+    Ok(Some(count))
+}
+
+/// Returns the debe of `date`, or of yesterday in `tz`.
+pub async fn get_debe(
+    db: &DbConn,
+    tz: Tz,
+    date: Option<chrono::NaiveDate>,
+    viewer_id: Option<i32>,
+) -> Result<DebeDto> {
+    let date = date.unwrap_or_else(|| yesterday(tz));
+
+    let snapshot = DebeEntry::find()
+        .filter(DebeEntryColumn::Date.eq(date))
+        .order_by_asc(DebeEntryColumn::Rank)
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Debe bulunamadı.".to_string()))?;
+
+    if snapshot.is_empty() {
+        return Err(Error::NotFound("Bu gün için debe bulunamadı.".to_string()));
+    }
+
+    // Entries deleted since the snapshot are left out, but the others keep
+    // their rank.
+    let mut entries: HashMap<i32, EntryModel> = Entry::find()
+        .filter(EntryColumn::Id.is_in(snapshot.iter().map(|debe| debe.entry_id)))
+        .filter(EntryColumn::DeletedAt.is_null())
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?
+        .into_iter()
+        .map(|entry| (entry.id, entry))
+        .collect();
+
+    let ranks: HashMap<i32, i32> = snapshot
+        .iter()
+        .map(|debe| (debe.entry_id, debe.rank))
+        .collect();
+    let entries: Vec<EntryModel> = snapshot
+        .iter()
+        .filter_map(|debe| entries.remove(&debe.entry_id))
+        .collect();
+
+    let entry_dtos = entry_dtos(db, entries, viewer_id).await?;
+
+    Ok(DebeDto {
+        date,
+        entries: entry_dtos
+            .into_iter()
+            .map(|entry| DebeEntryDto {
+                rank: ranks[&entry.id],
+                entry,
+            })
+            .collect(),
+    })
+}
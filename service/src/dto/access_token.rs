@@ -0,0 +1,83 @@
+use entity::prelude::AccessTokenModel;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::time::timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "read")]
+    Read,
+    #[serde(rename = "entries:write")]
+    WriteEntries,
+    #[serde(rename = "vote")]
+    Vote,
+    #[serde(rename = "moderation")]
+    Moderation,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::WriteEntries => "entries:write",
+            Scope::Vote => "vote",
+            Scope::Moderation => "moderation",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "read" => Some(Scope::Read),
+            "entries:write" => Some(Scope::WriteEntries),
+            "vote" => Some(Scope::Vote),
+            "moderation" => Some(Scope::Moderation),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAccessTokenRequest {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub scopes: Vec<Scope>,
+    /// Tokens without an expiry stay valid until revoked.
+    #[validate(range(min = 1, max = 365))]
+    #[serde(rename = "expiresInDays")]
+    pub expires_in_days: Option<u16>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateAccessTokenResponse {
+    pub id: i32,
+    /// The plain token. It is only shown once and can't be recovered later.
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessTokenDto {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<String>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<String>,
+}
+
+impl From<AccessTokenModel> for AccessTokenDto {
+    fn from(token: AccessTokenModel) -> Self {
+        Self {
+            id: token.id,
+            scopes: token.scopes.split(',').filter_map(Scope::parse).collect(),
+            name: token.name,
+            created_at: timestamp(token.created_at),
+            expires_at: token.expires_at.map(timestamp),
+            last_used_at: token.last_used_at.map(timestamp),
+        }
+    }
+}
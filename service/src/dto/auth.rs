@@ -1,10 +1,15 @@
-use crate::{cookie::Cookie, validation::validate_password};
+use crate::{
+    cookie::Cookie,
+    dto::{role::Permission, two_factor::TwoFactorChallengeResponse},
+    validation::validate_password,
+};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct LoginRequest {
-    #[validate(length(min = 2, max = 30))]
+    /// Nickname or email address of the user.
+    #[validate(length(min = 2, max = 320))]
     pub nickname: String,
     #[validate(length(min = 8, max = 32))]
     pub password: String,
@@ -14,10 +19,23 @@ pub struct LoginRequest {
 pub struct LoginResponse {
     pub cookie: Cookie,
     pub token: String,
-    #[serde(rename = "isAdmin")]
-    pub is_admin: bool,
-    #[serde(rename = "isModerator")]
-    pub is_moderator: bool,
+    pub roles: Vec<String>,
+    pub permissions: Vec<Permission>,
+    #[serde(rename = "isEmailVerified")]
+    pub is_email_verified: bool,
+    #[serde(rename = "isTwoFactorEnabled")]
+    pub is_two_factor_enabled: bool,
+}
+
+pub enum LoginResult {
+    Success(LoginResponse),
+    /// The password was correct but the user has to enter a second factor.
+    TwoFactorRequired(TwoFactorChallengeResponse),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
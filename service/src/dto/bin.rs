@@ -7,5 +7,5 @@ pub struct GetUserBinQuery {
     #[serde(rename = "perPage")]
     pub per_page: u8,
     #[validate(range(min = 1))]
-    pub page: u8,
+    pub page: u32,
 }
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use super::entry::EntryDto;
+use crate::time::Tz;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DebeQuery {
+    /// Defaults to yesterday in `tz`.
+    pub date: Option<chrono::NaiveDate>,
+    pub tz: Option<Tz>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DebeEntryDto {
+    pub rank: i32,
+    pub entry: EntryDto,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DebeDto {
+    pub date: chrono::NaiveDate,
+    pub entries: Vec<DebeEntryDto>,
+}
@@ -16,6 +16,8 @@ pub struct EntryAuthorDto {
     pub nickname: String,
     #[serde(rename = "isFaded")]
     pub is_faded: bool,
+    #[serde(rename = "isBot")]
+    pub is_bot: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,6 +44,9 @@ pub struct CreateEntryRequest {
     pub title: String,
     #[validate(length(min = 1, max = 65535))]
     pub content: String,
+    /// Opens the title even if titles with similar names exist.
+    #[serde(rename = "forceNewTitle", default)]
+    pub force_new_title: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -55,13 +60,38 @@ pub struct UpdateEntryRequest {
     pub content: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct EntryPositionQuery {
+    #[validate(range(min = 1, max = 100))]
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+    #[serde(rename = "orderBy")]
+    pub order_by: Option<OrderBy>,
+    pub order: Option<Order>,
+}
+
+/// Where an entry stands in its title, in the order it was asked for.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryPositionDto {
+    pub id: i32,
+    pub title: EntryTitleDto,
+    /// The 1-based number of the entry in the title.
+    pub position: u64,
+    /// The page of the title listing that contains the entry.
+    pub page: u32,
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct GetTitleEntriesQuery {
     #[validate(range(min = 1, max = 100))]
     #[serde(rename = "perPage")]
     pub per_page: u8,
     #[validate(range(min = 1))]
-    pub page: u8,
+    pub page: Option<u32>,
+    /// Takes precedence over `page` when both are given.
+    pub cursor: Option<String>,
     pub from: Option<chrono::DateTime<chrono::Utc>>,
     pub to: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(rename = "orderBy")]
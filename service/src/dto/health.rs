@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Unavailable,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthDto {
+    pub status: HealthStatus,
+}
+
+/// The outcome of one readiness check, with what went wrong when it failed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckDto {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadinessDto {
+    /// `ok` only when every check is.
+    pub status: HealthStatus,
+    pub database: CheckDto,
+    pub migrations: CheckDto,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionDto {
+    pub version: String,
+    /// The commit the server was built from, when it was built from a git
+    /// checkout or `GIT_HASH` was set.
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+}
@@ -0,0 +1,43 @@
+use entity::prelude::LoginAttemptModel;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::time::timestamp;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginAttemptDto {
+    pub id: i32,
+    #[serde(rename = "userId")]
+    pub user_id: Option<i32>,
+    pub identifier: String,
+    pub ip: Option<String>,
+    pub success: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl From<LoginAttemptModel> for LoginAttemptDto {
+    fn from(attempt: LoginAttemptModel) -> Self {
+        Self {
+            id: attempt.id,
+            user_id: attempt.user_id,
+            identifier: attempt.identifier,
+            ip: attempt.ip,
+            success: attempt.success,
+            created_at: timestamp(attempt.created_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct LoginAttemptsQuery {
+    #[validate(range(min = 1))]
+    pub page: u32,
+    #[validate(range(min = 1, max = 100))]
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+    #[serde(rename = "userId")]
+    pub user_id: Option<i32>,
+    pub ip: Option<String>,
+    pub success: Option<bool>,
+}
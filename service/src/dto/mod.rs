@@ -1,10 +1,18 @@
+pub mod access_token;
 pub mod auth;
 pub mod bin;
+pub mod debe;
 pub mod entry;
+pub mod health;
+pub mod login_attempt;
 pub mod order;
 pub mod pagination;
+pub mod role;
 pub mod search;
+pub mod silence;
 pub mod statistics;
+pub mod time;
 pub mod title;
 pub mod today;
 pub mod trends;
+pub mod two_factor;
@@ -16,4 +16,13 @@ pub enum OrderBy {
     UpdatedAt,
     #[serde(rename = "netVotes")]
     NetVotes,
+    /// Ranks entries by how sure it is that they are liked, so that a few
+    /// up votes on a new entry can beat many mixed votes on an old one.
+    #[serde(rename = "sukela")]
+    Sukela,
+    #[serde(rename = "favorites")]
+    Favorites,
+    /// Ranks entries with many, evenly split votes first.
+    #[serde(rename = "controversial")]
+    Controversial,
 }
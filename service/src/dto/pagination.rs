@@ -4,17 +4,34 @@ use validator::Validate;
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct PaginationQuery {
     #[validate(range(min = 1))]
-    pub page: u8,
+    pub page: u32,
     #[validate(range(min = 1, max = 100))]
     #[serde(rename = "perPage")]
     pub per_page: u8,
 }
 
+/// Pages either by `cursor`, taken from the `next` or `prev` field of an
+/// earlier response, or by page number. The cursor wins when both are given.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CursorPaginationQuery {
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 100))]
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+    pub cursor: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginationResponse<T> {
-    pub total: u64,
-    pub page: u8,
+    /// How many items there are in all. Only counted when the page was asked
+    /// for by number, as counting costs as much as reading every row.
+    pub total: Option<u64>,
+    /// The page number, unless the page was requested by cursor.
+    pub page: Option<u32>,
     #[serde(rename = "perPage")]
     pub per_page: u8,
     pub items: Vec<T>,
+    pub next: Option<String>,
+    pub prev: Option<String>,
 }
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    HideTitle,
+    MigrateEntry,
+    SilenceUser,
+    ViewStatistics,
+    ViewLoginAttempts,
+    ManageBots,
+    ManageRoles,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::HideTitle => "hide_title",
+            Permission::MigrateEntry => "migrate_entry",
+            Permission::SilenceUser => "silence_user",
+            Permission::ViewStatistics => "view_statistics",
+            Permission::ViewLoginAttempts => "view_login_attempts",
+            Permission::ManageBots => "manage_bots",
+            Permission::ManageRoles => "manage_roles",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hide_title" => Some(Permission::HideTitle),
+            "migrate_entry" => Some(Permission::MigrateEntry),
+            "silence_user" => Some(Permission::SilenceUser),
+            "view_statistics" => Some(Permission::ViewStatistics),
+            "view_login_attempts" => Some(Permission::ViewLoginAttempts),
+            "manage_bots" => Some(Permission::ManageBots),
+            "manage_roles" => Some(Permission::ManageRoles),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleDto {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRoleRequest {
+    #[validate(length(min = 2, max = 32))]
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub permissions: Vec<Permission>,
+}
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::entry::EntryDto;
 
 #[derive(Serialize, Deserialize)]
 pub struct SearchQuery {
@@ -10,3 +13,95 @@ pub struct SearchItem {
     pub id: i32,
     pub name: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct EntrySearchQuery {
+    /// Words to look for and filters, as in `@ssg "tam ifade" -hariç
+    /// title:film after:2024-01-01 fav:>10`. See `search::query`.
+    #[validate(length(min = 1, max = 200))]
+    pub query: String,
+    #[validate(range(min = 1))]
+    pub page: u32,
+    #[validate(range(min = 1, max = 100))]
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+    /// The nickname of the author.
+    pub author: Option<String>,
+    /// The name of the title.
+    pub title: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "minNetVotes")]
+    pub min_net_votes: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntrySearchHitDto {
+    pub entry: EntryDto,
+    pub rank: f32,
+    /// Parts of the content around the matches, HTML escaped, with the
+    /// matches wrapped in `<mark>` tags.
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TitleAutocompleteQuery {
+    #[validate(length(min = 1, max = 75))]
+    pub query: String,
+    #[validate(range(min = 1, max = 20))]
+    pub limit: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DidYouMeanQuery {
+    /// The name of the title about to be opened.
+    #[validate(length(min = 1, max = 75))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TitleSuggestionDto {
+    pub id: i32,
+    pub name: String,
+    #[serde(rename = "entryCount")]
+    pub entry_count: i64,
+    pub score: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TrendingSearchQuery {
+    /// How many hours back to count, 24 by default.
+    #[validate(range(min = 1, max = 168))]
+    pub hours: Option<u16>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrendingSearchDto {
+    pub query: String,
+    #[serde(rename = "searchCount")]
+    pub search_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ZeroResultSearchQuery {
+    /// How many days back to count, 7 by default.
+    #[validate(range(min = 1, max = 90))]
+    pub days: Option<u16>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZeroResultSearchDto {
+    pub query: String,
+    /// How many times the query found nothing.
+    #[serde(rename = "zeroResultCount")]
+    pub zero_result_count: i64,
+    #[serde(rename = "searchCount")]
+    pub search_count: i64,
+    /// The start of the last hour it was searched in.
+    #[serde(rename = "lastSearchedAt")]
+    pub last_searched_at: String,
+}
@@ -0,0 +1,12 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SilenceUserRequest {
+    /// Shown to the user when they try to write.
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+    /// The last day the user is silenced, in the configured timezone.
+    pub until: NaiveDate,
+}
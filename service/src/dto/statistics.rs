@@ -10,4 +10,8 @@ pub struct StatisticsResponse {
     pub user_count: u64,
     #[serde(rename = "crewCount")]
     pub crew_count: u64,
+    #[serde(rename = "todayEntryCount")]
+    pub today_entry_count: u64,
+    #[serde(rename = "todayUserCount")]
+    pub today_user_count: u64,
 }
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+use crate::time::Tz;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimezoneQuery {
+    /// An IANA timezone such as `Europe/Istanbul`. Defaults to the timezone
+    /// of the site.
+    pub tz: Option<Tz>,
+}
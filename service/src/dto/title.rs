@@ -1,12 +1,13 @@
-use chrono::NaiveDateTime;
 use entity::prelude::TitleActiveModel;
 use serde::{Deserialize, Serialize};
 
+use crate::time::timestamp;
+
 #[derive(Serialize, Deserialize)]
 pub struct TitleDto {
     pub id: i32,
     pub name: String,
-    pub last_entry_at: NaiveDateTime,
+    pub last_entry_at: String,
     pub is_visible: bool,
 }
 
@@ -15,7 +16,7 @@ impl From<TitleActiveModel> for TitleDto {
         Self {
             id: title.id.unwrap(),
             name: title.name.unwrap(),
-            last_entry_at: title.last_entry_at.unwrap(),
+            last_entry_at: timestamp(title.last_entry_at.unwrap()),
             is_visible: title.is_visible.unwrap(),
         }
     }
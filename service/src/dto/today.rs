@@ -1,4 +1,19 @@
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::time::Tz;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TodayQuery {
+    #[validate(range(min = 1))]
+    pub page: u32,
+    #[validate(range(min = 1, max = 100))]
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+    /// The timezone whose midnight starts the day. Defaults to the timezone
+    /// of the site.
+    pub tz: Option<Tz>,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 
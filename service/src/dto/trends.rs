@@ -1,11 +1,43 @@
-use sea_orm::FromQueryResult;
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[derive(FromQueryResult, Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TrendWindow {
+    #[serde(rename = "1h")]
+    Hour,
+    #[default]
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+}
+
+impl TrendWindow {
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            TrendWindow::Hour => chrono::Duration::hours(1),
+            TrendWindow::Day => chrono::Duration::hours(24),
+            TrendWindow::Week => chrono::Duration::days(7),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TrendsQuery {
+    #[validate(range(min = 1))]
+    pub page: u32,
+    #[validate(range(min = 1, max = 100))]
+    #[serde(rename = "perPage")]
+    pub per_page: u8,
+    pub window: Option<TrendWindow>,
+}
 
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TrendTitleDto {
     pub id: i32,
     pub name: String,
+    /// The number of entries written in the window.
     #[serde(rename = "entryCount")]
     pub entry_count: i64,
+    pub score: f64,
 }
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnrollTwoFactorResponse {
+    pub secret: String,
+    #[serde(rename = "otpauthUri")]
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TwoFactorCodeRequest {
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    #[serde(rename = "recoveryCodes")]
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorChallengeResponse {
+    #[serde(rename = "twoFactorRequired")]
+    pub two_factor_required: bool,
+    pub challenge: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TwoFactorLoginRequest {
+    pub challenge: String,
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
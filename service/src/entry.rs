@@ -1,88 +1,150 @@
+use std::collections::HashMap;
+
 use ::entity::prelude::*;
 use sea_orm::*;
+use sea_query::{Expr, Func, OnConflict, SimpleExpr};
 use validator::Validate;
 
 use crate::{
     dto::{
         entry::{
-            CreateEntryRequest, CreateEntryResponse, EntryAuthorDto, EntryDto, EntryTitleDto,
-            GetTitleEntriesQuery, UpdateEntryRequest,
+            CreateEntryRequest, CreateEntryResponse, EntryAuthorDto, EntryDto, EntryPositionDto,
+            EntryPositionQuery, EntryTitleDto, GetTitleEntriesQuery, UpdateEntryRequest,
         },
         order::{self, OrderBy},
-        pagination::{PaginationQuery, PaginationResponse},
+        pagination::{CursorPaginationQuery, PaginationResponse},
+        search::DidYouMeanQuery,
+    },
+    loader::entry_dtos,
+    pagination::{fetch_page, float_key, position, KeyKind, Keyset},
+    search::{
+        backend::{reindex, SearchBackend},
+        title::did_you_mean,
     },
-    title::{create_title, title_id_by_name},
+    silence::check_not_silenced,
+    time::{timestamp, Tz},
+    title::{touch_title, upsert_title},
     Error, Result,
 };
 
 pub async fn create_entry(
     db: &DbConn,
+    search: &dyn SearchBackend,
+    tz: Tz,
     user_id: i32,
     request: CreateEntryRequest,
+    require_verified_email: bool,
 ) -> Result<CreateEntryResponse> {
     request.validate().map_err(|_| {
         Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
     })?;
 
-    let is_faded = User::find()
+    let user = User::find()
         .filter(UserColumn::Id.eq(user_id))
         .filter(UserColumn::DeletedAt.is_null())
         .one(db)
         .await
         .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
-        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?
-        .is_faded;
-
-    let title_id = match title_id_by_name(db, &request.title).await {
-        Some(title_id) => title_id,
-        None => {
-            if !is_faded {
-                create_title(db, &request.title).await?.id.unwrap()
-            } else {
-                return Err(Error::InvalidRequest(
-                    "Solgun kullanıcılar başlık oluşturamaz.".to_string(),
-                ));
-            }
-        }
-    };
-    let title = Title::find()
-        .filter(TitleColumn::Name.eq(&request.title))
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Başlık bulunamadı.".to_string()))?;
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
 
-    let mut title = match title {
-        Some(title) => title.into(),
-        None => create_title(db, &request.title).await?,
-    };
+    if require_verified_email && user.email_verified_at.is_none() {
+        return Err(Error::Forbidden(
+            "Girdi yazabilmek için e-posta adresinizi doğrulamalısınız.".to_string(),
+        ));
+    }
 
-    title.last_entry_at = Set(chrono::Utc::now().naive_utc());
-    title
-        .save(db)
+    check_not_silenced(db, tz, user.id).await?;
+
+    if !user.is_faded && !request.force_new_title {
+        suggest_existing_title(db, &request.title).await?;
+    }
+
+    // The title and the entry are written together, so that a failed entry
+    // leaves no new title behind.
+    let txn = db
+        .begin()
         .await
-        .map_err(|_| Error::InternalError("Başlık güncellenemedi.".to_string()))?;
+        .map_err(|_| Error::InternalError("Girdi oluşturulamadı.".to_string()))?;
+
+    let title = if user.is_faded {
+        touch_title(&txn, &request.title)
+            .await?
+            .ok_or(Error::InvalidRequest(
+                "Solgun kullanıcılar başlık oluşturamaz.".to_string(),
+            ))?
+    } else {
+        upsert_title(&txn, &request.title).await?
+    };
+
+    if !title.is_visible {
+        return Err(Error::NotFound("Başlık bulunamadı.".to_string()));
+    }
 
     let entry = EntryActiveModel {
-        title_id: Set(title_id),
+        title_id: Set(title.id),
         user_id: Set(user_id),
         content: Set(request.content),
         ..Default::default()
     }
-    .save(db)
+    .insert(&txn)
     .await
-    .map_err(|e| {
-        if e.to_string().contains("duplicate key value") {
-            return Error::InvalidRequest("Böyle bir girdi zaten var.".to_string());
+    .map_err(|e| match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => {
+            Error::Conflict("Böyle bir girdi zaten var.".to_string())
         }
-        Error::InternalError("Girdi oluşturulamadı.".to_string())
+        _ => Error::InternalError("Girdi oluşturulamadı.".to_string()),
     })?;
 
-    Ok(CreateEntryResponse {
-        id: entry.id.unwrap(),
-    })
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Girdi oluşturulamadı.".to_string()))?;
+
+    // The entry is saved at this point; if the search index can't be updated
+    // it catches up on the next rebuild.
+    reindex(search, db, vec![entry.id]).await;
+
+    Ok(CreateEntryResponse { id: entry.id })
+}
+
+/// Fails with the names of similar titles if `name` would open a new title
+/// while one of them was probably meant.
+async fn suggest_existing_title(db: &DbConn, name: &str) -> Result<()> {
+    let exists = Title::find()
+        .filter(TitleColumn::Name.eq(name))
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Başlık bulunamadı.".to_string()))?
+        > 0;
+    if exists {
+        return Ok(());
+    }
+
+    let similar = did_you_mean(
+        db,
+        DidYouMeanQuery {
+            name: name.to_string(),
+        },
+    )
+    .await?;
+    if similar.is_empty() {
+        return Ok(());
+    }
+
+    let names: Vec<String> = similar.into_iter().map(|title| title.name).collect();
+
+    Err(Error::Conflict(format!(
+        "Bunu mu demek istediniz: {}? Yeni başlığı yine de açmak için forceNewTitle gönderin.",
+        names.join(", ")
+    )))
 }
 
-pub async fn delete_entry(db: &DbConn, user_id: i32, id: i32, soft_delete: bool) -> Result<()> {
+pub async fn delete_entry(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    user_id: i32,
+    id: i32,
+    soft_delete: bool,
+) -> Result<()> {
     let entry = Entry::find()
         .filter(EntryColumn::Id.eq(id))
         .filter(EntryColumn::UserId.eq(user_id))
@@ -102,6 +164,8 @@ pub async fn delete_entry(db: &DbConn, user_id: i32, id: i32, soft_delete: bool)
             .await
             .map_err(|_| Error::InternalError("Girdi silinemedi.".to_string()))?;
 
+        reindex(search, db, vec![id]).await;
+
         return Ok(());
     }
 
@@ -113,10 +177,17 @@ pub async fn delete_entry(db: &DbConn, user_id: i32, id: i32, soft_delete: bool)
         .await
         .map_err(|_| Error::InternalError("Girdi silinemedi.".to_string()))?;
 
+    reindex(search, db, vec![id]).await;
+
     Ok(())
 }
 
-pub async fn recover_entry(db: &DbConn, user_id: i32, id: i32) -> Result<()> {
+pub async fn recover_entry(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    user_id: i32,
+    id: i32,
+) -> Result<()> {
     let entry = Entry::find()
         .filter(EntryColumn::Id.eq(id))
         .filter(EntryColumn::UserId.eq(user_id))
@@ -138,6 +209,8 @@ pub async fn recover_entry(db: &DbConn, user_id: i32, id: i32) -> Result<()> {
         .await
         .map_err(|_| Error::InternalError("Girdi geri alınamadı.".to_string()))?;
 
+    reindex(search, db, vec![id]).await;
+
     Ok(())
 }
 
@@ -215,17 +288,20 @@ pub async fn get_entry(db: &DbConn, id: i32, user_id: Option<i32>) -> Result<Ent
             id: author.id,
             nickname: author.nickname,
             is_faded: author.is_faded,
+            is_bot: author.is_bot,
         },
         is_favorite,
         vote,
-        created_at: entry.created_at.and_utc().to_string(),
-        updated_at: entry.updated_at.map(|t| t.and_utc().to_string()),
-        deleted_at: entry.deleted_at.map(|t| t.and_utc().to_string()),
+        created_at: timestamp(entry.created_at),
+        updated_at: entry.updated_at.map(timestamp),
+        deleted_at: entry.deleted_at.map(timestamp),
     })
 }
 
 pub async fn update_entry(
     db: &DbConn,
+    search: &dyn SearchBackend,
+    tz: Tz,
     user_id: i32,
     id: i32,
     request: UpdateEntryRequest,
@@ -234,6 +310,8 @@ pub async fn update_entry(
         Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
     })?;
 
+    check_not_silenced(db, tz, user_id).await?;
+
     let entry = Entry::find()
         .filter(EntryColumn::Id.eq(id))
         .filter(EntryColumn::DeletedAt.is_null())
@@ -257,10 +335,17 @@ pub async fn update_entry(
         .await
         .map_err(|_| Error::InternalError("Girdi güncellenemedi.".to_string()))?;
 
+    reindex(search, db, vec![id]).await;
+
     Ok(())
 }
 
-pub async fn migrate_entry(db: &DbConn, id: i32, title_id: i32) -> Result<()> {
+pub async fn migrate_entry(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    id: i32,
+    title_id: i32,
+) -> Result<()> {
     let entry = Entry::find()
         .filter(EntryColumn::Id.eq(id))
         .one(db)
@@ -284,9 +369,71 @@ pub async fn migrate_entry(db: &DbConn, id: i32, title_id: i32) -> Result<()> {
         .await
         .map_err(|_| Error::InternalError("Girdi taşınamadı.".to_string()))?;
 
+    reindex(search, db, vec![id]).await;
+
     Ok(())
 }
 
+/// The entries listed under a title: those that are not deleted and whose
+/// authors are neither deleted nor faded.
+fn title_entries_query(title_id: i32) -> Select<Entry> {
+    Entry::find()
+        .filter(EntryColumn::TitleId.eq(title_id))
+        .filter(EntryColumn::DeletedAt.is_null())
+        .inner_join(User)
+        .filter(UserColumn::DeletedAt.is_null())
+        .filter(UserColumn::IsFaded.eq(false))
+}
+
+/// Finds the entry's number in its title and the page to load to see it.
+pub async fn get_entry_position(
+    db: &DbConn,
+    id: i32,
+    query: EntryPositionQuery,
+) -> Result<EntryPositionDto> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let (entry, title) = Entry::find()
+        .filter(EntryColumn::Id.eq(id))
+        .find_also_related(Title)
+        .filter(TitleColumn::IsVisible.eq(true))
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdi bulunamadı.".to_string()))?
+        .and_then(|(entry, title)| title.map(|title| (entry, title)))
+        .ok_or(Error::NotFound("Girdi bulunamadı.".to_string()))?;
+
+    // The entry must be one the title listing shows.
+    let is_listed = title_entries_query(title.id)
+        .filter(EntryColumn::Id.eq(entry.id))
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdi bulunamadı.".to_string()))?
+        > 0;
+
+    if !is_listed {
+        return Err(Error::NotFound("Girdi bulunamadı.".to_string()));
+    }
+
+    let keyset = entry_keyset(query.order_by.as_ref(), query.order.as_ref());
+    let position = position(db, title_entries_query(title.id), &keyset, &entry).await?;
+    let page = (position - 1) / u64::from(query.per_page) + 1;
+
+    Ok(EntryPositionDto {
+        id: entry.id,
+        title: EntryTitleDto {
+            id: title.id,
+            name: title.name,
+        },
+        position,
+        page: u32::try_from(page)
+            .map_err(|_| Error::InternalError("Sayfa bulunamadı.".to_string()))?,
+        per_page: query.per_page,
+    })
+}
+
 pub async fn get_title_entries(
     db: &DbConn,
     id: i32,
@@ -314,9 +461,7 @@ pub async fn get_title_entries(
         None => None,
     };
 
-    let base_query = Entry::find()
-        .filter(EntryColumn::TitleId.eq(id))
-        .filter(EntryColumn::DeletedAt.is_null())
+    let base_query = title_entries_query(id)
         .apply_if(Some(query.from), |q, v| match v {
             Some(v) => q.filter(EntryColumn::CreatedAt.gt(v)),
             None => q,
@@ -324,131 +469,40 @@ pub async fn get_title_entries(
         .apply_if(Some(query.to), |q, v| match v {
             Some(v) => q.filter(EntryColumn::CreatedAt.lt(v)),
             None => q,
-        })
-        .apply_if(Some(query.order_by), |q, v| match v {
-            Some(OrderBy::CreatedAt) => match query.order {
-                Some(order::Order::Asc) => q.order_by_asc(EntryColumn::CreatedAt),
-                Some(order::Order::Desc) => q.order_by_desc(EntryColumn::CreatedAt),
-                None => q,
-            },
-            Some(OrderBy::UpdatedAt) => match query.order {
-                Some(order::Order::Asc) => q.order_by_asc(EntryColumn::UpdatedAt),
-                Some(order::Order::Desc) => q.order_by_desc(EntryColumn::UpdatedAt),
-                None => q,
-            },
-            Some(OrderBy::NetVotes) => match query.order {
-                Some(order::Order::Asc) => q.order_by_asc(EntryColumn::NetVotes),
-                Some(order::Order::Desc) => q.order_by_desc(EntryColumn::NetVotes),
-                None => q,
-            },
-            None => match query.order {
-                Some(order::Order::Desc) => q.order_by_desc(EntryColumn::CreatedAt),
-                _ => q.order_by_asc(EntryColumn::CreatedAt),
-            },
-        })
-        .inner_join(User)
-        .filter(UserColumn::DeletedAt.is_null())
-        .filter(UserColumn::IsFaded.eq(false));
-
-    let entry_pages = base_query.clone().paginate(db, query.per_page.into());
-
-    let entries = entry_pages
-        .fetch_page(query.page as u64 - 1)
-        .await
-        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?;
-
-    let title_name: String = Title::find()
+        });
+
+    let keyset = entry_keyset(query.order_by.as_ref(), query.order.as_ref());
+    let page = fetch_page(
+        db,
+        base_query,
+        &keyset,
+        query.page,
+        query.per_page,
+        query.cursor.as_deref(),
+    )
+    .await?;
+
+    let is_title_visible = Title::find()
         .filter(TitleColumn::Id.eq(id))
         .filter(TitleColumn::IsVisible.eq(true))
-        .select_only()
-        .column(TitleColumn::Name)
-        .into_tuple()
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Başlık adı getirilemedi.".to_string()))
-        .and_then(|title_name: Option<String>| {
-            title_name.ok_or(Error::NotFound("Başlık adı getirilemedi.".to_string()))
-        })?;
-
-    let entry_dto_futures = entries.into_iter().map(|entry| {
-        let db = db.clone();
-        let title_name = title_name.clone();
-        let user = user.clone();
-
-        async move {
-            let author = User::find()
-                .filter(UserColumn::Id.eq(entry.user_id))
-                .filter(UserColumn::DeletedAt.is_null())
-                .filter(UserColumn::IsFaded.eq(false))
-                .one(&db)
-                .await;
-
-            let is_favorite: Option<bool> = match user {
-                Some(ref user) => Some(
-                    Favorite::find()
-                        .filter(FavoriteColumn::UserId.eq(user.id))
-                        .filter(FavoriteColumn::EntryId.eq(entry.id))
-                        .one(&db)
-                        .await
-                        .map_err(|_| Error::InternalError("Favori bulunamadı.".to_string()))
-                        .map(|favorite| favorite.is_some())?,
-                ),
-                None => None,
-            };
-
-            let vote: Option<Rating> = match user {
-                Some(user) => Vote::find()
-                    .filter(VoteColumn::UserId.eq(user.id))
-                    .filter(VoteColumn::EntryId.eq(entry.id))
-                    .one(&db)
-                    .await
-                    .map_err(|_| Error::InternalError("Oy bulunamadı.".to_string()))
-                    .map(|vote| vote.map(|vote| vote.rating))?,
-                None => None,
-            };
-
-            if let Ok(Some(user)) = author {
-                Ok(Some(EntryDto {
-                    id: entry.id,
-                    title: EntryTitleDto {
-                        id,
-                        name: title_name.clone(),
-                    },
-                    content: entry.content,
-                    author: EntryAuthorDto {
-                        id: user.id,
-                        nickname: user.nickname,
-                        is_faded: user.is_faded,
-                    },
-                    is_favorite,
-                    vote,
-                    created_at: entry.created_at.and_utc().to_string(),
-                    updated_at: entry.updated_at.map(|t| t.and_utc().to_string()),
-                    deleted_at: entry.deleted_at.map(|t| t.and_utc().to_string()),
-                }))
-            } else {
-                Ok(None)
-            }
-        }
-    });
-
-    let entry_dtos: Result<Vec<Option<EntryDto>>> = futures::future::join_all(entry_dto_futures)
+        .count(db)
         .await
-        .into_iter()
-        .collect();
+        .map_err(|_| Error::InternalError("Başlık bulunamadı.".to_string()))?
+        > 0;
 
-    let entry_dtos: Vec<EntryDto> = entry_dtos?.into_iter().flatten().collect();
+    if !is_title_visible {
+        return Err(Error::NotFound("Başlık bulunamadı.".to_string()));
+    }
 
-    let total = base_query
-        .count(db)
-        .await
-        .map_err(|_| Error::InternalError("Girdi sayısı getirilemedi.".to_string()))?;
+    let entry_dtos = entry_dtos(db, page.items, user.map(|user| user.id)).await?;
 
     Ok(PaginationResponse {
-        total,
-        page: query.page,
+        total: page.total,
+        page: page.page,
         per_page: query.per_page,
         items: entry_dtos,
+        next: page.next,
+        prev: page.prev,
     })
 }
 
@@ -471,7 +525,7 @@ pub async fn get_title_entries_by_name(
 pub async fn get_user_entries(
     db: &DbConn,
     nickname: String,
-    query: PaginationQuery,
+    query: CursorPaginationQuery,
     user_id: Option<i32>,
 ) -> Result<PaginationResponse<EntryDto>> {
     query.validate().map_err(|_| {
@@ -495,14 +549,11 @@ pub async fn get_user_entries(
         None => None,
     };
 
-    // TODO: Do not select nickname again
-    let (author_id, nickname, is_faded): (i32, String, bool) = User::find()
+    let author_id: i32 = User::find()
         .filter(UserColumn::Nickname.eq(nickname))
         .filter(UserColumn::DeletedAt.is_null())
         .select_only()
         .column(UserColumn::Id)
-        .column(UserColumn::Nickname)
-        .column(UserColumn::IsFaded)
         .into_tuple()
         .one(db)
         .await
@@ -515,98 +566,202 @@ pub async fn get_user_entries(
         .inner_join(Title)
         .filter(TitleColumn::IsVisible.eq(true));
 
-    let entry_pages = base_query
-        .clone()
-        .order_by_desc(EntryColumn::CreatedAt)
-        .paginate(db, query.per_page.into());
-
-    let entries = entry_pages
-        .fetch_page(query.page as u64 - 1)
-        .await
-        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?;
-
-    let entry_dto_futures = entries.into_iter().map(|entry| {
-        let db = db.clone();
-        let nickname = nickname.clone();
-        let user = user.clone();
-
-        async move {
-            let title = Title::find()
-                .filter(TitleColumn::Id.eq(entry.title_id))
-                .one(&db)
-                .await;
-
-            let is_favorite: Option<bool> = match user {
-                Some(ref user) => Some(
-                    Favorite::find()
-                        .filter(FavoriteColumn::UserId.eq(user.id))
-                        .filter(FavoriteColumn::EntryId.eq(entry.id))
-                        .one(&db)
-                        .await
-                        .map_err(|_| Error::InternalError("Favori bulunamadı.".to_string()))
-                        .map(|favorite| favorite.is_some())?,
-                ),
-                None => None,
-            };
-
-            let vote: Option<Rating> = match user {
-                Some(user) => Vote::find()
-                    .filter(VoteColumn::UserId.eq(user.id))
-                    .filter(VoteColumn::EntryId.eq(entry.id))
-                    .one(&db)
-                    .await
-                    .map_err(|_| Error::InternalError("Oy bulunamadı.".to_string()))
-                    .map(|vote| vote.map(|vote| vote.rating))?,
-                None => None,
-            };
-
-            if let Ok(Some(title)) = title {
-                Ok(Some(EntryDto {
-                    id: entry.id,
-                    title: EntryTitleDto {
-                        id: title.id,
-                        name: title.name,
-                    },
-                    content: entry.content,
-                    author: EntryAuthorDto {
-                        id: author_id,
-                        nickname,
-                        is_faded,
-                    },
-                    is_favorite,
-                    vote,
-                    created_at: entry.created_at.and_utc().to_string(),
-                    updated_at: entry.updated_at.map(|t| t.and_utc().to_string()),
-                    deleted_at: entry.deleted_at.map(|t| t.and_utc().to_string()),
-                }))
-            } else {
-                Ok(None)
-            }
-        }
-    });
+    let keyset = entry_keyset(Some(&OrderBy::CreatedAt), Some(&order::Order::Desc));
+    let page = fetch_page(
+        db,
+        base_query,
+        &keyset,
+        query.page,
+        query.per_page,
+        query.cursor.as_deref(),
+    )
+    .await?;
+
+    let entry_dtos = entry_dtos(db, page.items, user.map(|user| user.id)).await?;
+
+    Ok(PaginationResponse {
+        total: page.total,
+        page: page.page,
+        per_page: query.per_page,
+        items: entry_dtos,
+        next: page.next,
+        prev: page.prev,
+    })
+}
 
-    let entry_dtos: Result<Vec<Option<EntryDto>>> = futures::future::join_all(entry_dto_futures)
+/// Returns the entries the user added to their favorites, most recently added
+/// first.
+pub async fn get_user_favorites(
+    db: &DbConn,
+    user_id: i32,
+    query: CursorPaginationQuery,
+) -> Result<PaginationResponse<EntryDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let base_query = Favorite::find()
+        .filter(FavoriteColumn::UserId.eq(user_id))
+        .filter(
+            FavoriteColumn::EntryId.in_subquery(
+                Entry::find()
+                    .select_only()
+                    .column(EntryColumn::Id)
+                    .filter(EntryColumn::DeletedAt.is_null())
+                    .inner_join(Title)
+                    .filter(TitleColumn::IsVisible.eq(true))
+                    .inner_join(User)
+                    .filter(UserColumn::DeletedAt.is_null())
+                    .into_query(),
+            ),
+        );
+
+    let keyset = Keyset {
+        name: "favoritedAt:desc".to_string(),
+        key: Expr::col((Favorite, FavoriteColumn::CreatedAt)).into(),
+        kind: KeyKind::Timestamp,
+        id: Expr::col((Favorite, FavoriteColumn::Id)).into(),
+        order: Order::Desc,
+        cursor_of: |favorite: &FavoriteModel| {
+            (
+                favorite.created_at.and_utc().timestamp_micros(),
+                favorite.id,
+            )
+        },
+    };
+    let page = fetch_page(
+        db,
+        base_query,
+        &keyset,
+        query.page,
+        query.per_page,
+        query.cursor.as_deref(),
+    )
+    .await?;
+
+    let mut entries: HashMap<i32, EntryModel> = Entry::find()
+        .filter(EntryColumn::Id.is_in(page.items.iter().map(|favorite| favorite.entry_id)))
+        .all(db)
         .await
+        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?
         .into_iter()
+        .map(|entry| (entry.id, entry))
+        .collect();
+    let entries = page
+        .items
+        .iter()
+        .filter_map(|favorite| entries.remove(&favorite.entry_id))
         .collect();
 
-    let entry_dtos: Vec<EntryDto> = entry_dtos?.into_iter().flatten().collect();
-
-    let total = base_query
-        .count(db)
-        .await
-        .map_err(|_| Error::InternalError("Girdi sayısı getirilemedi.".to_string()))?;
+    let entry_dtos = entry_dtos(db, entries, Some(user_id)).await?;
 
     Ok(PaginationResponse {
-        total,
-        page: query.page,
+        total: page.total,
+        page: page.page,
         per_page: query.per_page,
         items: entry_dtos,
+        next: page.next,
+        prev: page.prev,
     })
 }
 
-pub async fn favorite_entry(db: &DbConn, user_id: i32, entry_id: i32) -> Result<()> {
-    let mut entry = Entry::find()
+/// Returns the keyset to list entries in the given order. Entries are listed
+/// oldest first unless told otherwise, and the most voted first when ordered
+/// by votes.
+pub(crate) fn entry_keyset(
+    order_by: Option<&OrderBy>,
+    order: Option<&order::Order>,
+) -> Keyset<EntryModel> {
+    let order_by = order_by.unwrap_or(&OrderBy::CreatedAt);
+    let order = match (order, order_by) {
+        (Some(order::Order::Asc), _) => Order::Asc,
+        (Some(order::Order::Desc), _) => Order::Desc,
+        (None, OrderBy::CreatedAt | OrderBy::UpdatedAt) => Order::Asc,
+        (None, _) => Order::Desc,
+    };
+
+    let direction = match order {
+        Order::Asc => "asc",
+        _ => "desc",
+    };
+    let id: SimpleExpr = Expr::col((Entry, EntryColumn::Id)).into();
+
+    match order_by {
+        OrderBy::CreatedAt => Keyset {
+            name: format!("createdAt:{direction}"),
+            key: Expr::col((Entry, EntryColumn::CreatedAt)).into(),
+            kind: KeyKind::Timestamp,
+            id,
+            order,
+            cursor_of: |entry| (entry.created_at.and_utc().timestamp_micros(), entry.id),
+        },
+        // Entries that were never updated are ordered by when they were
+        // written.
+        OrderBy::UpdatedAt => Keyset {
+            name: format!("updatedAt:{direction}"),
+            key: Func::coalesce([
+                Expr::col((Entry, EntryColumn::UpdatedAt)).into(),
+                Expr::col((Entry, EntryColumn::CreatedAt)).into(),
+            ])
+            .into(),
+            kind: KeyKind::Timestamp,
+            id,
+            order,
+            cursor_of: |entry| {
+                (
+                    entry
+                        .updated_at
+                        .unwrap_or(entry.created_at)
+                        .and_utc()
+                        .timestamp_micros(),
+                    entry.id,
+                )
+            },
+        },
+        OrderBy::NetVotes => Keyset {
+            name: format!("netVotes:{direction}"),
+            key: Expr::col((Entry, EntryColumn::NetVotes)).into(),
+            kind: KeyKind::Int,
+            id,
+            order,
+            cursor_of: |entry| (entry.net_votes.into(), entry.id),
+        },
+        // The scores are generated columns, see the `vote_count` migration.
+        OrderBy::Sukela => Keyset {
+            name: format!("sukela:{direction}"),
+            key: Expr::col((Entry, EntryColumn::SukelaScore)).into(),
+            kind: KeyKind::Float,
+            id,
+            order,
+            cursor_of: |entry| (float_key(entry.sukela_score), entry.id),
+        },
+        OrderBy::Favorites => Keyset {
+            name: format!("favorites:{direction}"),
+            key: Expr::col((Entry, EntryColumn::TotalFavorites)).into(),
+            kind: KeyKind::Int,
+            id,
+            order,
+            cursor_of: |entry| (entry.total_favorites.into(), entry.id),
+        },
+        OrderBy::Controversial => Keyset {
+            name: format!("controversial:{direction}"),
+            key: Expr::col((Entry, EntryColumn::ControversyScore)).into(),
+            kind: KeyKind::Float,
+            id,
+            order,
+            cursor_of: |entry| (float_key(entry.controversy_score), entry.id),
+        },
+    }
+}
+
+/// Checks that the entry can be voted and favorited, and that the user
+/// exists.
+async fn check_entry_and_user(
+    db: &impl ConnectionTrait,
+    user_id: i32,
+    entry_id: i32,
+) -> Result<()> {
+    Entry::find()
         .filter(EntryColumn::Id.eq(entry_id))
         .filter(EntryColumn::DeletedAt.is_null())
         .inner_join(Title)
@@ -614,8 +769,7 @@ pub async fn favorite_entry(db: &DbConn, user_id: i32, entry_id: i32) -> Result<
         .one(db)
         .await
         .map_err(|_| Error::InternalError("Girdi bulunamadı.".to_string()))
-        .and_then(|entry| entry.ok_or(Error::NotFound("Girdi bulunamadı.".to_string())))?
-        .into_active_model();
+        .and_then(|entry| entry.ok_or(Error::NotFound("Girdi bulunamadı.".to_string())))?;
 
     User::find()
         .filter(UserColumn::Id.eq(user_id))
@@ -625,109 +779,182 @@ pub async fn favorite_entry(db: &DbConn, user_id: i32, entry_id: i32) -> Result<
         .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
         .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
 
-    // TODO: Use exists instead of find
-    let favorite = Favorite::find()
-        .filter(FavoriteColumn::UserId.eq(user_id))
-        .filter(FavoriteColumn::EntryId.eq(entry_id))
-        .one(db)
+    Ok(())
+}
+
+/// Adds the deltas to counters of the entry in the database, so that
+/// concurrent changes don't overwrite each other.
+async fn add_to_counters(
+    db: &impl ConnectionTrait,
+    entry_id: i32,
+    deltas: &[(EntryColumn, i32)],
+) -> Result<()> {
+    let mut update = Entry::update_many();
+    for &(counter, delta) in deltas {
+        update = update.col_expr(counter, Expr::col(counter).add(delta));
+    }
+
+    update
+        .filter(EntryColumn::Id.eq(entry_id))
+        .exec(db)
         .await
-        .map_err(|_| Error::InternalError("Favori bulunamadı.".to_string()))?;
+        .map_err(|_| Error::InternalError("Girdi güncellenemedi.".to_string()))?;
 
-    if favorite.is_some() {
-        return Err(Error::InvalidRequest(
-            "Zaten favorilere eklenmiş.".to_string(),
-        ));
+    Ok(())
+}
+
+fn rating_value(rating: Rating) -> i32 {
+    match rating {
+        Rating::Up => 1,
+        Rating::Down => -1,
+    }
+}
+
+fn rating_counter(rating: Rating) -> EntryColumn {
+    match rating {
+        Rating::Up => EntryColumn::UpVotes,
+        Rating::Down => EntryColumn::DownVotes,
     }
+}
+
+pub async fn favorite_entry(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    tz: Tz,
+    user_id: i32,
+    entry_id: i32,
+) -> Result<()> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("Favori eklenemedi.".to_string()))?;
+
+    check_entry_and_user(&txn, user_id, entry_id).await?;
+    check_not_silenced(&txn, tz, user_id).await?;
 
-    FavoriteActiveModel {
+    let result = Favorite::insert(FavoriteActiveModel {
         user_id: Set(user_id),
         entry_id: Set(entry_id),
         ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([FavoriteColumn::UserId, FavoriteColumn::EntryId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec(&txn)
+    .await;
+
+    match result {
+        Ok(_) => {}
+        Err(DbErr::RecordNotInserted) => {
+            return Err(Error::InvalidRequest(
+                "Zaten favorilere eklenmiş.".to_string(),
+            ))
+        }
+        Err(_) => return Err(Error::InternalError("Favori eklenemedi.".to_string())),
     }
-    .save(db)
-    .await
-    .map_err(|_| Error::InternalError("Favori eklenemedi.".to_string()))?;
 
-    entry.total_favorites = Set(entry.total_favorites.unwrap() + 1);
-    entry
-        .update(db)
+    add_to_counters(&txn, entry_id, &[(EntryColumn::TotalFavorites, 1)]).await?;
+
+    txn.commit()
         .await
         .map_err(|_| Error::InternalError("Favori eklenemedi.".to_string()))?;
 
+    // Searches filter on the counters.
+    reindex(search, db, vec![entry_id]).await;
+
     Ok(())
 }
 
-pub async fn unfavorite_entry(db: &DbConn, user_id: i32, entry_id: i32) -> Result<()> {
-    let mut entry = Entry::find()
-        .filter(EntryColumn::Id.eq(entry_id))
-        .filter(EntryColumn::DeletedAt.is_null())
-        .inner_join(Title)
-        .filter(TitleColumn::IsVisible.eq(true))
-        .one(db)
+pub async fn unfavorite_entry(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    user_id: i32,
+    entry_id: i32,
+) -> Result<()> {
+    let txn = db
+        .begin()
         .await
-        .map_err(|_| Error::InternalError("Girdi bulunamadı.".to_string()))
-        .and_then(|entry| entry.ok_or(Error::NotFound("Girdi bulunamadı.".to_string())))?
-        .into_active_model();
+        .map_err(|_| Error::InternalError("Favori silinemedi.".to_string()))?;
 
-    User::find()
-        .filter(UserColumn::Id.eq(user_id))
-        .filter(UserColumn::DeletedAt.is_null())
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
-        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+    check_entry_and_user(&txn, user_id, entry_id).await?;
 
-    Favorite::find()
+    let result = Favorite::delete_many()
         .filter(FavoriteColumn::UserId.eq(user_id))
         .filter(FavoriteColumn::EntryId.eq(entry_id))
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Favori bulunamadı.".to_string()))
-        .and_then(|favorite| favorite.ok_or(Error::NotFound("Favori bulunamadı.".to_string())))?
-        .delete(db)
+        .exec(&txn)
         .await
         .map_err(|_| Error::InternalError("Favori silinemedi.".to_string()))?;
 
-    entry.total_favorites = Set(entry.total_favorites.unwrap() - 1);
-    entry
-        .update(db)
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound("Favori bulunamadı.".to_string()));
+    }
+
+    add_to_counters(&txn, entry_id, &[(EntryColumn::TotalFavorites, -1)]).await?;
+
+    txn.commit()
         .await
         .map_err(|_| Error::InternalError("Favori silinemedi.".to_string()))?;
 
+    // Searches filter on the counters.
+    reindex(search, db, vec![entry_id]).await;
+
     Ok(())
 }
 
-pub async fn vote_entry(db: &DbConn, user_id: i32, entry_id: i32, rating: Rating) -> Result<()> {
-    let mut entry = Entry::find()
-        .filter(EntryColumn::Id.eq(entry_id))
-        .filter(EntryColumn::DeletedAt.is_null())
-        .inner_join(Title)
-        .filter(TitleColumn::IsVisible.eq(true))
-        .one(db)
+pub async fn vote_entry(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    tz: Tz,
+    user_id: i32,
+    entry_id: i32,
+    rating: Rating,
+) -> Result<()> {
+    let txn = db
+        .begin()
         .await
-        .map_err(|_| Error::InternalError("Girdi bulunamadı.".to_string()))
-        .and_then(|entry| entry.ok_or(Error::NotFound("Girdi bulunamadı.".to_string())))?
-        .into_active_model();
+        .map_err(|_| Error::InternalError("Oy eklenemedi.".to_string()))?;
 
-    // TODO: Check if user silenced
-    User::find()
-        .filter(UserColumn::Id.eq(user_id))
-        .filter(UserColumn::DeletedAt.is_null())
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
-        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+    check_entry_and_user(&txn, user_id, entry_id).await?;
+    check_not_silenced(&txn, tz, user_id).await?;
 
-    let vote = Vote::find()
-        .filter(VoteColumn::UserId.eq(user_id))
-        .filter(VoteColumn::EntryId.eq(entry_id))
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Oy bulunamadı.".to_string()))?;
+    let result = Vote::insert(VoteActiveModel {
+        user_id: Set(user_id),
+        entry_id: Set(entry_id),
+        rating: Set(rating),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([VoteColumn::UserId, VoteColumn::EntryId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec(&txn)
+    .await;
+
+    let value = rating_value(rating);
+    let deltas = match result {
+        Ok(_) => vec![(EntryColumn::NetVotes, value), (rating_counter(rating), 1)],
+        Err(DbErr::RecordNotInserted) => {
+            // The user has already voted; only an opposite vote changes it.
+            let result = Vote::update_many()
+                .col_expr(
+                    VoteColumn::Rating,
+                    Expr::val(rating.to_value()).as_enum(Rating::name()),
+                )
+                .col_expr(
+                    VoteColumn::UpdatedAt,
+                    Expr::value(chrono::Utc::now().naive_utc()),
+                )
+                .filter(VoteColumn::UserId.eq(user_id))
+                .filter(VoteColumn::EntryId.eq(entry_id))
+                .filter(VoteColumn::Rating.ne(rating))
+                .exec(&txn)
+                .await
+                .map_err(|_| Error::InternalError("Oy güncellenemedi.".to_string()))?;
 
-    match vote {
-        Some(vote) => {
-            if vote.rating == rating {
+            if result.rows_affected == 0 {
                 return Err(Error::InvalidRequest(format!(
                     "Zaten {} oy verilmiş.",
                     if rating == Rating::Up {
@@ -738,92 +965,73 @@ pub async fn vote_entry(db: &DbConn, user_id: i32, entry_id: i32, rating: Rating
                 )));
             }
 
-            let mut vote = vote.into_active_model();
-            vote.rating = Set(rating);
-            vote.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+            // The vote moves from one counter to the other.
+            vec![
+                (EntryColumn::NetVotes, 2 * value),
+                (EntryColumn::UpVotes, value),
+                (EntryColumn::DownVotes, -value),
+            ]
+        }
+        Err(_) => return Err(Error::InternalError("Oy eklenemedi.".to_string())),
+    };
 
-            vote.save(db)
-                .await
-                .map_err(|_| Error::InternalError("Oy güncellenemedi.".to_string()))?;
+    add_to_counters(&txn, entry_id, &deltas).await?;
 
-            entry.net_votes =
-                Set(entry.net_votes.unwrap() + if rating == Rating::Up { 2 } else { -2 });
-            entry
-                .save(db)
-                .await
-                .map_err(|_| Error::InternalError("Oy güncellenemedi.".to_string()))?;
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Oy eklenemedi.".to_string()))?;
 
-            Ok(())
-        }
-        None => {
-            VoteActiveModel {
-                user_id: Set(user_id),
-                entry_id: Set(entry_id),
-                rating: Set(rating),
-                ..Default::default()
-            }
-            .save(db)
-            .await
-            .map_err(|_| Error::InternalError("Oy eklenemedi.".to_string()))?;
+    // Searches filter on the counters.
+    reindex(search, db, vec![entry_id]).await;
 
-            entry.net_votes =
-                Set(entry.net_votes.unwrap() + if rating == Rating::Up { 1 } else { -1 });
-            entry
-                .save(db)
-                .await
-                .map_err(|_| Error::InternalError("Oy eklenemedi.".to_string()))?;
-
-            Ok(())
-        }
-    }
+    Ok(())
 }
 
-pub async fn unvote(db: &DbConn, user_id: i32, entry_id: i32) -> Result<()> {
-    let mut entry = Entry::find()
-        .filter(EntryColumn::Id.eq(entry_id))
-        .filter(EntryColumn::DeletedAt.is_null())
-        .inner_join(Title)
-        .filter(TitleColumn::IsVisible.eq(true))
-        .one(db)
+pub async fn unvote(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    user_id: i32,
+    entry_id: i32,
+) -> Result<()> {
+    let txn = db
+        .begin()
         .await
-        .map_err(|_| Error::InternalError("Girdi bulunamadı.".to_string()))
-        .and_then(|entry| entry.ok_or(Error::NotFound("Girdi bulunamadı.".to_string())))?
-        .into_active_model();
+        .map_err(|_| Error::InternalError("Oy silinemedi.".to_string()))?;
 
-    User::find()
-        .filter(UserColumn::Id.eq(user_id))
-        .filter(UserColumn::DeletedAt.is_null())
-        .one(db)
-        .await
-        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
-        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+    check_entry_and_user(&txn, user_id, entry_id).await?;
 
+    // Lock the vote so that a concurrent vote can't change its rating before
+    // it is deleted.
     let vote = Vote::find()
         .filter(VoteColumn::UserId.eq(user_id))
         .filter(VoteColumn::EntryId.eq(entry_id))
-        .one(db)
+        .lock_exclusive()
+        .one(&txn)
         .await
-        .map_err(|_| Error::InternalError("Oy bulunamadı.".to_string()))?;
+        .map_err(|_| Error::InternalError("Oy bulunamadı.".to_string()))?
+        .ok_or(Error::InvalidRequest("Oy bulunamadı.".to_string()))?;
 
-    match vote {
-        Some(vote) => {
-            entry.net_votes = Set(entry.net_votes.unwrap()
-                - match vote.rating {
-                    Rating::Up => 1,
-                    Rating::Down => -1,
-                });
-
-            vote.delete(db)
-                .await
-                .map_err(|_| Error::InternalError("Oy silinemedi.".to_string()))?;
+    Vote::delete_by_id(vote.id)
+        .exec(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Oy silinemedi.".to_string()))?;
+
+    add_to_counters(
+        &txn,
+        entry_id,
+        &[
+            (EntryColumn::NetVotes, -rating_value(vote.rating)),
+            (rating_counter(vote.rating), -1),
+        ],
+    )
+    .await?;
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Oy silinemedi.".to_string()))?;
 
-            entry
-                .save(db)
-                .await
-                .map_err(|_| Error::InternalError("Oy silinemedi.".to_string()))?;
+    // Searches filter on the counters.
+    reindex(search, db, vec![entry_id]).await;
 
-            Ok(())
-        }
-        None => Err(Error::InvalidRequest("Oy bulunamadı.".to_string())),
-    }
+    Ok(())
 }
@@ -14,6 +14,14 @@ pub enum Error {
     InvalidToken,
     #[error("Kimlik doğrulama hatası: {0}")]
     Unauthorized(String),
+    #[error("Erişim izni yok: {0}")]
+    Forbidden(String),
     #[error("{0}")]
     NotFound(String),
+    #[error("Çakışma: {0}")]
+    Conflict(String),
+    #[error("{0}")]
+    TooManyRequests(String),
+    #[error("Çok fazla başarısız giriş denemesi. {0} saniye sonra tekrar deneyin.")]
+    TooManyAttempts(i64),
 }
@@ -1,18 +1,31 @@
+pub mod access_token;
 pub mod auth;
 pub mod bin;
+pub mod config;
 pub mod cookie;
+pub mod counter;
+pub mod debe;
 pub mod dto;
 pub mod entry;
 pub mod error;
 pub mod feed;
+pub mod loader;
+pub mod login_attempt;
+pub mod mail;
+pub mod pagination;
+pub mod role;
 pub mod search;
+pub mod silence;
 pub mod statistics;
+pub mod time;
 pub mod title;
 pub mod today;
 pub mod token;
 pub mod trends;
+pub mod two_factor;
 pub mod user;
 pub mod validation;
+pub mod verification;
 
 pub use self::error::{Error, Result};
 pub use sea_orm;
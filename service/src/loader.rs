@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+
+use ::entity::prelude::*;
+use sea_orm::*;
+
+use crate::{
+    dto::entry::{EntryAuthorDto, EntryDto, EntryTitleDto},
+    time::timestamp,
+    Error, Result,
+};
+
+/// Turns a page of entries into DTOs with at most four queries, no matter how
+/// many entries there are: one each for the authors and titles, and, when
+/// `viewer_id` is given, one each for the viewer's favorites and votes.
+///
+/// The order of `entries` is kept. Entries whose author or title is missing
+/// are left out.
+pub async fn entry_dtos(
+    db: &DbConn,
+    entries: Vec<EntryModel>,
+    viewer_id: Option<i32>,
+) -> Result<Vec<EntryDto>> {
+    if entries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let entry_ids: Vec<i32> = entries.iter().map(|entry| entry.id).collect();
+    let user_ids: HashSet<i32> = entries.iter().map(|entry| entry.user_id).collect();
+    let title_ids: HashSet<i32> = entries.iter().map(|entry| entry.title_id).collect();
+
+    let authors: HashMap<i32, UserModel> = User::find()
+        .filter(UserColumn::Id.is_in(user_ids))
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcılar getirilemedi.".to_string()))?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+    let titles: HashMap<i32, TitleModel> = Title::find()
+        .filter(TitleColumn::Id.is_in(title_ids))
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Başlıklar getirilemedi.".to_string()))?
+        .into_iter()
+        .map(|title| (title.id, title))
+        .collect();
+
+    let (favorites, votes): (HashSet<i32>, HashMap<i32, Rating>) = match viewer_id {
+        Some(viewer_id) => {
+            let favorites: Vec<i32> = Favorite::find()
+                .select_only()
+                .column(FavoriteColumn::EntryId)
+                .filter(FavoriteColumn::UserId.eq(viewer_id))
+                .filter(FavoriteColumn::EntryId.is_in(entry_ids.clone()))
+                .into_tuple()
+                .all(db)
+                .await
+                .map_err(|_| Error::InternalError("Favoriler getirilemedi.".to_string()))?;
+
+            let votes: Vec<(i32, Rating)> = Vote::find()
+                .select_only()
+                .column(VoteColumn::EntryId)
+                .column(VoteColumn::Rating)
+                .filter(VoteColumn::UserId.eq(viewer_id))
+                .filter(VoteColumn::EntryId.is_in(entry_ids))
+                .into_tuple()
+                .all(db)
+                .await
+                .map_err(|_| Error::InternalError("Oylar getirilemedi.".to_string()))?;
+
+            (favorites.into_iter().collect(), votes.into_iter().collect())
+        }
+        None => (HashSet::new(), HashMap::new()),
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let author = authors.get(&entry.user_id)?;
+            let title = titles.get(&entry.title_id)?;
+
+            Some(EntryDto {
+                id: entry.id,
+                title: EntryTitleDto {
+                    id: title.id,
+                    name: title.name.clone(),
+                },
+                content: entry.content,
+                author: EntryAuthorDto {
+                    id: author.id,
+                    nickname: author.nickname.clone(),
+                    is_faded: author.is_faded,
+                    is_bot: author.is_bot,
+                },
+                is_favorite: viewer_id.map(|_| favorites.contains(&entry.id)),
+                vote: votes.get(&entry.id).cloned(),
+                created_at: timestamp(entry.created_at),
+                updated_at: entry.updated_at.map(timestamp),
+                deleted_at: entry.deleted_at.map(timestamp),
+            })
+        })
+        .collect())
+}
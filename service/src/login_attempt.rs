@@ -0,0 +1,197 @@
+use ::entity::prelude::*;
+use sea_orm::*;
+use validator::Validate;
+
+use crate::{
+    dto::{
+        login_attempt::{LoginAttemptDto, LoginAttemptsQuery},
+        pagination::PaginationResponse,
+    },
+    Error, Result,
+};
+
+const WINDOW_MINUTES: i64 = 60;
+const ACCOUNT_FREE_ATTEMPTS: u64 = 3;
+const IP_FREE_ATTEMPTS: u64 = 20;
+const MAX_LOCKOUT_SECONDS: i64 = 60 * 15;
+
+/// Returns how long a client has to wait after its last failed attempt.
+/// The first `free_attempts` failures are not penalized, after that the wait
+/// doubles with every failure up to `MAX_LOCKOUT_SECONDS`.
+pub fn lockout_duration(failures: u64, free_attempts: u64) -> Option<chrono::Duration> {
+    if failures < free_attempts {
+        return None;
+    }
+
+    let exponent = (failures - free_attempts).min(16) as u32;
+    let seconds = 2i64.pow(exponent).min(MAX_LOCKOUT_SECONDS);
+
+    Some(chrono::Duration::seconds(seconds))
+}
+
+async fn retry_after(
+    db: &DbConn,
+    condition: Condition,
+    reset_on_success: bool,
+    free_attempts: u64,
+) -> Result<Option<i64>> {
+    let now = chrono::Utc::now().naive_utc();
+    let mut since = now - chrono::Duration::minutes(WINDOW_MINUTES);
+
+    if reset_on_success {
+        let last_success: Option<chrono::NaiveDateTime> = LoginAttempt::find()
+            .filter(condition.clone())
+            .filter(LoginAttemptColumn::Success.eq(true))
+            .filter(LoginAttemptColumn::CreatedAt.gt(since))
+            .select_only()
+            .column_as(LoginAttemptColumn::CreatedAt.max(), "last_success")
+            .into_tuple()
+            .one(db)
+            .await
+            .map_err(|_| Error::InternalError("Giriş denemeleri getirilemedi.".to_string()))?
+            .flatten();
+
+        if let Some(last_success) = last_success {
+            since = last_success;
+        }
+    }
+
+    let (failures, last_failure): (i64, Option<chrono::NaiveDateTime>) = LoginAttempt::find()
+        .filter(condition)
+        .filter(LoginAttemptColumn::Success.eq(false))
+        .filter(LoginAttemptColumn::CreatedAt.gt(since))
+        .select_only()
+        .column_as(LoginAttemptColumn::Id.count(), "failures")
+        .column_as(LoginAttemptColumn::CreatedAt.max(), "last_failure")
+        .into_tuple()
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Giriş denemeleri getirilemedi.".to_string()))?
+        .unwrap_or((0, None));
+
+    let (Some(duration), Some(last_failure)) = (
+        lockout_duration(failures as u64, free_attempts),
+        last_failure,
+    ) else {
+        return Ok(None);
+    };
+
+    let until = last_failure + duration;
+    if until <= now {
+        return Ok(None);
+    }
+
+    Ok(Some((until - now).num_seconds().max(1)))
+}
+
+/// Fails with `Error::TooManyAttempts` while the account or the client IP is
+/// locked out because of recent failed logins.
+pub async fn ensure_not_locked(db: &DbConn, user_id: Option<i32>, ip: Option<&str>) -> Result<()> {
+    if let Some(ip) = ip {
+        let condition = Condition::all().add(LoginAttemptColumn::Ip.eq(ip));
+        if let Some(seconds) = retry_after(db, condition, false, IP_FREE_ATTEMPTS).await? {
+            return Err(Error::TooManyAttempts(seconds));
+        }
+    }
+
+    if let Some(user_id) = user_id {
+        let condition = Condition::all().add(LoginAttemptColumn::UserId.eq(user_id));
+        if let Some(seconds) = retry_after(db, condition, true, ACCOUNT_FREE_ATTEMPTS).await? {
+            return Err(Error::TooManyAttempts(seconds));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn record(
+    db: &DbConn,
+    user_id: Option<i32>,
+    identifier: &str,
+    ip: Option<&str>,
+    success: bool,
+) -> Result<()> {
+    LoginAttemptActiveModel {
+        user_id: Set(user_id),
+        identifier: Set(identifier.chars().take(320).collect()),
+        ip: Set(ip.map(|ip| ip.to_string())),
+        success: Set(success),
+        ..Default::default()
+    }
+    .save(db)
+    .await
+    .map_err(|_| Error::InternalError("Giriş denemesi kaydedilemedi.".to_string()))?;
+
+    Ok(())
+}
+
+pub async fn login_attempts(
+    db: &DbConn,
+    query: LoginAttemptsQuery,
+) -> Result<PaginationResponse<LoginAttemptDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let base_query = LoginAttempt::find()
+        .apply_if(query.user_id, |q, v| {
+            q.filter(LoginAttemptColumn::UserId.eq(v))
+        })
+        .apply_if(query.ip.clone(), |q, v| {
+            q.filter(LoginAttemptColumn::Ip.eq(v))
+        })
+        .apply_if(query.success, |q, v| {
+            q.filter(LoginAttemptColumn::Success.eq(v))
+        });
+
+    let attempt_pages = base_query
+        .clone()
+        .order_by_desc(LoginAttemptColumn::CreatedAt)
+        .order_by_desc(LoginAttemptColumn::Id)
+        .paginate(db, query.per_page.into());
+
+    let attempts = attempt_pages
+        .fetch_page(u64::from(query.page - 1))
+        .await
+        .map_err(|_| Error::InternalError("Giriş denemeleri getirilemedi.".to_string()))?;
+
+    let total = base_query
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Giriş denemesi sayısı getirilemedi.".to_string()))?;
+
+    Ok(PaginationResponse {
+        total: Some(total),
+        page: Some(query.page),
+        per_page: query.per_page,
+        items: attempts.into_iter().map(LoginAttemptDto::from).collect(),
+        next: None,
+        prev: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_duration() {
+        assert_eq!(lockout_duration(0, 3), None);
+        assert_eq!(lockout_duration(2, 3), None);
+        assert_eq!(lockout_duration(3, 3), Some(chrono::Duration::seconds(1)));
+        assert_eq!(lockout_duration(4, 3), Some(chrono::Duration::seconds(2)));
+        assert_eq!(lockout_duration(8, 3), Some(chrono::Duration::seconds(32)));
+    }
+
+    #[test]
+    fn test_lockout_duration_is_capped() {
+        assert_eq!(
+            lockout_duration(20, 3),
+            Some(chrono::Duration::seconds(MAX_LOCKOUT_SECONDS))
+        );
+        assert_eq!(
+            lockout_duration(u64::MAX, 3),
+            Some(chrono::Duration::seconds(MAX_LOCKOUT_SECONDS))
+        );
+    }
+}
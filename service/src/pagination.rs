@@ -0,0 +1,340 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sea_orm::*;
+use sea_query::{Expr, SimpleExpr};
+
+use crate::{Error, Result};
+
+const CURSOR_VERSION: &str = "v1";
+
+#[derive(Debug, Clone, Copy)]
+pub enum KeyKind {
+    Timestamp,
+    Int,
+    /// Floats are given as their bits, so that cursors keep exact values.
+    Float,
+}
+
+/// Describes a stable sort order over a query: the sort key and a unique
+/// column to break ties. Cursors point at a `(key, id)` pair.
+pub struct Keyset<M> {
+    /// Names the sort order, so that a cursor can't be used with another one.
+    pub name: String,
+    pub key: SimpleExpr,
+    pub kind: KeyKind,
+    pub id: SimpleExpr,
+    pub order: Order,
+    /// Returns the key and the id of a row. Timestamps are given in
+    /// microseconds, and floats by [`float_key`].
+    pub cursor_of: fn(&M) -> (i64, i32),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cursor {
+    pub name: String,
+    pub key: i64,
+    pub id: i32,
+    /// Whether the cursor points to the rows before the key instead of the
+    /// ones after it.
+    pub backward: bool,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let direction = if self.backward { "p" } else { "n" };
+        let raw = format!(
+            "{CURSOR_VERSION}|{}|{}|{}|{direction}",
+            self.name, self.key, self.id
+        );
+
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let raw = String::from_utf8(raw).ok()?;
+        let mut parts = raw.split('|');
+
+        if parts.next()? != CURSOR_VERSION {
+            return None;
+        }
+
+        let name = parts.next()?.to_string();
+        let key = parts.next()?.parse().ok()?;
+        let id = parts.next()?.parse().ok()?;
+        let backward = match parts.next()? {
+            "n" => false,
+            "p" => true,
+            _ => return None,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            key,
+            id,
+            backward,
+        })
+    }
+}
+
+pub struct Page<M> {
+    pub items: Vec<M>,
+    /// Only counted for pages asked for by number.
+    pub total: Option<u64>,
+    pub page: Option<u32>,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+fn reverse(order: &Order) -> Order {
+    match order {
+        Order::Asc => Order::Desc,
+        _ => Order::Asc,
+    }
+}
+
+/// Stores a float key in a cursor.
+pub fn float_key(value: f64) -> i64 {
+    value.to_bits() as i64
+}
+
+fn key_value(kind: KeyKind, key: i64) -> Option<SimpleExpr> {
+    match kind {
+        KeyKind::Timestamp => chrono::DateTime::from_timestamp_micros(key)
+            .map(|time| Expr::val(time.naive_utc()).into()),
+        KeyKind::Int => Some(Expr::val(key).into()),
+        KeyKind::Float => Some(f64::from_bits(key as u64))
+            .filter(|value| value.is_finite())
+            .map(|value| Expr::val(value).into()),
+    }
+}
+
+/// Fetches one page of `select`, either after or before `cursor`, or by page
+/// number when no cursor is given. Only pages by number are counted, so that
+/// following cursors costs the same however deep the page is.
+pub async fn fetch_page<E>(
+    db: &DbConn,
+    select: Select<E>,
+    keyset: &Keyset<E::Model>,
+    page: Option<u32>,
+    per_page: u8,
+    cursor: Option<&str>,
+) -> Result<Page<E::Model>>
+where
+    E: EntityTrait,
+    E::Model: Send + Sync,
+{
+    let per_page = u64::from(per_page);
+
+    let cursor_at = |item: &E::Model, backward: bool| {
+        let (key, id) = (keyset.cursor_of)(item);
+
+        Cursor {
+            name: keyset.name.clone(),
+            key,
+            id,
+            backward,
+        }
+        .encode()
+    };
+
+    let cursor = match cursor {
+        Some(cursor) => Some(
+            Cursor::decode(cursor)
+                .filter(|cursor| cursor.name == keyset.name)
+                .ok_or(Error::InvalidRequest("Geçersiz sayfa imleci.".to_string()))?,
+        ),
+        None => None,
+    };
+
+    let Some(cursor) = cursor else {
+        let page = page.unwrap_or(1).max(1);
+        let offset = u64::from(page - 1) * per_page;
+
+        let total = select
+            .clone()
+            .count(db)
+            .await
+            .map_err(|_| Error::InternalError("Kayıt sayısı getirilemedi.".to_string()))?;
+
+        let items = select
+            .order_by(keyset.key.clone(), keyset.order.clone())
+            .order_by(keyset.id.clone(), keyset.order.clone())
+            .offset(offset)
+            .limit(per_page)
+            .all(db)
+            .await
+            .map_err(|_| Error::InternalError("Kayıtlar getirilemedi.".to_string()))?;
+
+        let next = match items.last() {
+            Some(last) if offset + (items.len() as u64) < total => Some(cursor_at(last, false)),
+            _ => None,
+        };
+        let prev = match items.first() {
+            Some(first) if page > 1 => Some(cursor_at(first, true)),
+            _ => None,
+        };
+
+        return Ok(Page {
+            items,
+            total: Some(total),
+            page: Some(page),
+            next,
+            prev,
+        });
+    };
+
+    let key = key_value(keyset.kind, cursor.key)
+        .ok_or(Error::InvalidRequest("Geçersiz sayfa imleci.".to_string()))?;
+    let order = if cursor.backward {
+        reverse(&keyset.order)
+    } else {
+        keyset.order.clone()
+    };
+
+    let row = Expr::tuple([keyset.key.clone(), keyset.id.clone()]);
+    let at = Expr::tuple([key, Expr::val(cursor.id).into()]);
+    let condition = match order {
+        Order::Asc => row.gt(at),
+        _ => row.lt(at),
+    };
+
+    let mut items = select
+        .filter(condition)
+        .order_by(keyset.key.clone(), order.clone())
+        .order_by(keyset.id.clone(), order)
+        .limit(per_page + 1)
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Kayıtlar getirilemedi.".to_string()))?;
+
+    let has_more = items.len() as u64 > per_page;
+    items.truncate(per_page as usize);
+
+    if cursor.backward {
+        items.reverse();
+    }
+
+    // Moving in one direction means there is at least the row of the cursor
+    // in the other one.
+    let (has_next, has_prev) = if cursor.backward {
+        (true, has_more)
+    } else {
+        (has_more, true)
+    };
+
+    let next = items
+        .last()
+        .filter(|_| has_next)
+        .map(|last| cursor_at(last, false));
+    let prev = items
+        .first()
+        .filter(|_| has_prev)
+        .map(|first| cursor_at(first, true));
+
+    Ok(Page {
+        items,
+        total: None,
+        page: None,
+        next,
+        prev,
+    })
+}
+
+/// Returns the 1-based position of `item` among the rows of `select` in the
+/// order of the keyset.
+pub async fn position<E>(
+    db: &DbConn,
+    select: Select<E>,
+    keyset: &Keyset<E::Model>,
+    item: &E::Model,
+) -> Result<u64>
+where
+    E: EntityTrait,
+    E::Model: Send + Sync,
+{
+    let (key, id) = (keyset.cursor_of)(item);
+    let key =
+        key_value(keyset.kind, key).ok_or(Error::InternalError("Sıra bulunamadı.".to_string()))?;
+
+    let row = Expr::tuple([keyset.key.clone(), keyset.id.clone()]);
+    let at = Expr::tuple([key, Expr::val(id).into()]);
+    let condition = match keyset.order {
+        Order::Asc => row.lt(at),
+        _ => row.gt(at),
+    };
+
+    let before = select
+        .filter(condition)
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Sıra bulunamadı.".to_string()))?;
+
+    Ok(before + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            name: "createdAt:asc".to_string(),
+            key: 1_760_000_000_123_456,
+            id: 42,
+            backward: true,
+        };
+
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn test_cursor_is_opaque() {
+        let cursor = Cursor {
+            name: "netVotes:desc".to_string(),
+            key: -3,
+            id: 7,
+            backward: false,
+        }
+        .encode();
+
+        assert!(!cursor.contains('|'));
+        assert!(cursor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn test_float_key_round_trip() {
+        for value in [0.0, 0.206_549_300_051_174_1, 14.5, -2.25] {
+            let cursor = Cursor {
+                name: "sukela:desc".to_string(),
+                key: float_key(value),
+                id: 1,
+                backward: false,
+            };
+            let key = Cursor::decode(&cursor.encode()).unwrap().key;
+
+            assert_eq!(f64::from_bits(key as u64), value);
+        }
+    }
+
+    #[test]
+    fn test_float_key_value_rejects_nan() {
+        assert!(key_value(KeyKind::Float, float_key(f64::NAN)).is_none());
+        assert!(key_value(KeyKind::Float, float_key(1.5)).is_some());
+    }
+
+    #[test]
+    fn test_cursor_decode_rejects_invalid_input() {
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v0|a|1|2|n")), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v1|a|x|2|n")), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v1|a|1|2|x")), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v1|a|1|2|n|")), None);
+    }
+}
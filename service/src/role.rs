@@ -0,0 +1,267 @@
+use ::entity::prelude::*;
+use sea_orm::*;
+use validator::Validate;
+
+use crate::{
+    dto::role::{CreateRoleRequest, Permission, RoleDto, UpdateRoleRequest},
+    Error, Result,
+};
+
+/// Returns the permissions granted to the user through all of their roles.
+pub async fn permissions(db: &impl ConnectionTrait, user_id: i32) -> Result<Vec<Permission>> {
+    let permissions: Vec<String> = RolePermission::find()
+        .select_only()
+        .column(RolePermissionColumn::Permission)
+        .distinct()
+        .filter(
+            RolePermissionColumn::RoleId.in_subquery(
+                UserRole::find()
+                    .select_only()
+                    .column(UserRoleColumn::RoleId)
+                    .filter(UserRoleColumn::UserId.eq(user_id))
+                    .into_query(),
+            ),
+        )
+        .order_by_asc(RolePermissionColumn::Permission)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Yetkiler bulunamadı.".to_string()))?;
+
+    Ok(permissions
+        .iter()
+        .filter_map(|permission| Permission::parse(permission))
+        .collect())
+}
+
+pub async fn has_permission(db: &DbConn, user_id: i32, permission: Permission) -> Result<bool> {
+    permissions(db, user_id)
+        .await
+        .map(|permissions| permissions.contains(&permission))
+}
+
+/// Returns the names of the roles of the user.
+pub async fn user_roles(db: &DbConn, user_id: i32) -> Result<Vec<String>> {
+    Role::find()
+        .select_only()
+        .column(RoleColumn::Name)
+        .filter(
+            RoleColumn::Id.in_subquery(
+                UserRole::find()
+                    .select_only()
+                    .column(UserRoleColumn::RoleId)
+                    .filter(UserRoleColumn::UserId.eq(user_id))
+                    .into_query(),
+            ),
+        )
+        .order_by_asc(RoleColumn::Name)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Roller bulunamadı.".to_string()))
+}
+
+pub async fn roles(db: &DbConn) -> Result<Vec<RoleDto>> {
+    let roles = Role::find()
+        .order_by_asc(RoleColumn::Name)
+        .find_with_related(RolePermission)
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Roller bulunamadı.".to_string()))?;
+
+    Ok(roles
+        .into_iter()
+        .map(|(role, permissions)| RoleDto {
+            name: role.name,
+            permissions: permissions
+                .iter()
+                .filter_map(|permission| Permission::parse(&permission.permission))
+                .collect(),
+        })
+        .collect())
+}
+
+async fn find_role(db: &impl ConnectionTrait, name: &str) -> Result<RoleModel> {
+    Role::find()
+        .filter(RoleColumn::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Rol bulunamadı.".to_string()))
+        .and_then(|role| role.ok_or(Error::NotFound("Rol bulunamadı.".to_string())))
+}
+
+async fn set_permissions(
+    db: &impl ConnectionTrait,
+    role_id: i32,
+    permissions: &[Permission],
+) -> Result<()> {
+    RolePermission::delete_many()
+        .filter(RolePermissionColumn::RoleId.eq(role_id))
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Rol güncellenemedi.".to_string()))?;
+
+    let mut models: Vec<RolePermissionActiveModel> = Vec::new();
+    for permission in permissions {
+        if models
+            .iter()
+            .any(|model| model.permission.as_ref() == permission.as_str())
+        {
+            continue;
+        }
+
+        models.push(RolePermissionActiveModel {
+            role_id: Set(role_id),
+            permission: Set(permission.as_str().to_string()),
+            ..Default::default()
+        });
+    }
+
+    if models.is_empty() {
+        return Ok(());
+    }
+
+    RolePermission::insert_many(models)
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Rol güncellenemedi.".to_string()))?;
+
+    Ok(())
+}
+
+/// Fails if no user would be left to manage roles, so that the last
+/// administrator can't lock everyone out. Deleted users don't count.
+async fn ensure_role_manager_left(db: &impl ConnectionTrait) -> Result<()> {
+    let managers = UserRole::find()
+        .inner_join(User)
+        .filter(UserColumn::DeletedAt.is_null())
+        .filter(
+            UserRoleColumn::RoleId.in_subquery(
+                RolePermission::find()
+                    .select_only()
+                    .column(RolePermissionColumn::RoleId)
+                    .filter(RolePermissionColumn::Permission.eq(Permission::ManageRoles.as_str()))
+                    .into_query(),
+            ),
+        )
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Roller bulunamadı.".to_string()))?;
+
+    if managers == 0 {
+        return Err(Error::InvalidRequest(
+            "Rolleri yönetebilecek en az bir kullanıcı kalmalıdır.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn create_role(db: &DbConn, request: CreateRoleRequest) -> Result<RoleDto> {
+    request.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let name = request.name.trim().to_lowercase();
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("Rol oluşturulamadı.".to_string()))?;
+
+    let role = RoleActiveModel {
+        name: Set(name.clone()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => {
+            Error::InvalidRequest("Bu isimde bir rol zaten var.".to_string())
+        }
+        _ => Error::InternalError("Rol oluşturulamadı.".to_string()),
+    })?;
+
+    set_permissions(&txn, role.id, &request.permissions).await?;
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Rol oluşturulamadı.".to_string()))?;
+
+    Ok(RoleDto {
+        name,
+        permissions: request.permissions,
+    })
+}
+
+pub async fn update_role(db: &DbConn, name: &str, request: UpdateRoleRequest) -> Result<()> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("Rol güncellenemedi.".to_string()))?;
+
+    let role = find_role(&txn, name).await?;
+    set_permissions(&txn, role.id, &request.permissions).await?;
+    ensure_role_manager_left(&txn).await?;
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Rol güncellenemedi.".to_string()))
+}
+
+pub async fn grant_role(db: &DbConn, user_id: i32, name: &str, granted_by: i32) -> Result<()> {
+    let role = find_role(db, name).await?;
+
+    let user = User::find()
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+
+    let result = UserRoleActiveModel {
+        user_id: Set(user.id),
+        role_id: Set(role.id),
+        granted_by: Set(Some(granted_by)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => Err(
+            Error::InvalidRequest("Kullanıcı bu role zaten sahip.".to_string()),
+        ),
+        Err(_) => Err(Error::InternalError("Rol verilemedi.".to_string())),
+    }
+}
+
+pub async fn revoke_role(db: &DbConn, user_id: i32, name: &str) -> Result<()> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("Rol geri alınamadı.".to_string()))?;
+
+    let role = find_role(&txn, name).await?;
+
+    let result = UserRole::delete_many()
+        .filter(UserRoleColumn::UserId.eq(user_id))
+        .filter(UserRoleColumn::RoleId.eq(role.id))
+        .exec(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Rol geri alınamadı.".to_string()))?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound(
+            "Kullanıcı bu role sahip değil.".to_string(),
+        ));
+    }
+
+    ensure_role_manager_left(&txn).await?;
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Rol geri alınamadı.".to_string()))
+}
@@ -0,0 +1,339 @@
+//! Counts what is searched for, without saying who searched. Queries are
+//! normalized and added up in hourly buckets. Searchers are only kept as a
+//! keyed hash, so that each is counted once on the trending list.
+
+use ::entity::prelude::*;
+use chrono::{Duration, NaiveDateTime, Timelike};
+use sea_orm::*;
+use sea_query::{Expr, LikeExpr, OnConflict};
+use validator::Validate;
+
+use crate::{
+    dto::search::{
+        TrendingSearchDto, TrendingSearchQuery, ZeroResultSearchDto, ZeroResultSearchQuery,
+    },
+    time::timestamp,
+    Error, Result,
+};
+
+use super::fold_turkish;
+
+/// The longest query kept, in characters.
+const MAX_QUERY_LENGTH: usize = 100;
+
+/// Queries searched by fewer people than this are never shown publicly, so
+/// that a query only one person would make doesn't point back at them, and a
+/// few clients can't push any text onto the list.
+const MIN_TRENDING_SEARCHERS: i64 = 5;
+
+/// The longest window of the trending list. Searchers are forgotten after it.
+const MAX_TRENDING_HOURS: i64 = 168;
+
+/// Queries with this many digits in a row look like phone or ID numbers.
+const MAX_DIGIT_RUN: usize = 6;
+
+/// Lower cases the query the Turkish way, squeezes its whitespace and cuts it
+/// to length. Returns `None` for empty queries and for those that look like
+/// they hold contact details.
+fn normalize_query(query: &str) -> Option<String> {
+    let normalized = fold_turkish(query)
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_QUERY_LENGTH)
+        .collect::<String>();
+
+    if normalized.is_empty() || looks_personal(&normalized) {
+        return None;
+    }
+
+    Some(normalized)
+}
+
+/// Whether the query holds an e-mail address or a long number.
+fn looks_personal(query: &str) -> bool {
+    let has_email = query.split(' ').any(|word| {
+        word.find('@')
+            .is_some_and(|at| at > 0 && word[at + 1..].contains('.'))
+    });
+
+    let mut digits = 0;
+    let has_number = query.chars().any(|c| {
+        digits = if c.is_ascii_digit() { digits + 1 } else { 0 };
+        digits >= MAX_DIGIT_RUN
+    });
+
+    has_email || has_number
+}
+
+/// Who searched, as a keyed hash of the user or, for guests, of the address.
+/// The key is derived from the JWT secret, so the hash can't be reversed by
+/// trying every address.
+pub struct Searcher(String);
+
+impl Searcher {
+    /// Returns `None` when neither the user nor the address is known; such
+    /// searches don't count towards the trending list.
+    pub fn new(secret: &str, user_id: Option<i32>, ip: Option<&str>) -> Option<Self> {
+        let client = match (user_id, ip) {
+            (Some(user_id), _) => format!("user:{user_id}"),
+            (None, Some(ip)) => format!("ip:{ip}"),
+            (None, None) => return None,
+        };
+        let key = blake3::derive_key("arpacik search searcher", secret.as_bytes());
+
+        Some(Self(
+            blake3::keyed_hash(&key, client.as_bytes())
+                .to_hex()
+                .to_string(),
+        ))
+    }
+}
+
+/// The start of the hour `time` falls in.
+fn hour_bucket(time: NaiveDateTime) -> NaiveDateTime {
+    time.date().and_hms_opt(time.hour(), 0, 0).unwrap_or(time)
+}
+
+/// Adds a search for `query` that found `result_count` results to the
+/// current hour.
+pub async fn record_search(
+    db: &DbConn,
+    query: &str,
+    result_count: usize,
+    searcher: Option<&Searcher>,
+) -> Result<()> {
+    let Some(query) = normalize_query(query) else {
+        return Ok(());
+    };
+
+    let bucket = hour_bucket(chrono::Utc::now().naive_utc());
+    let is_zero = i32::from(result_count == 0);
+    let result_count = i64::try_from(result_count).unwrap_or(i64::MAX);
+
+    if let Some(Searcher(searcher)) = searcher {
+        SearchQuerySearcher::insert(SearchQuerySearcherActiveModel {
+            bucket: Set(bucket),
+            query: Set(query.clone()),
+            searcher: Set(searcher.clone()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                SearchQuerySearcherColumn::Bucket,
+                SearchQuerySearcherColumn::Query,
+                SearchQuerySearcherColumn::Searcher,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .map_err(|_| Error::InternalError("Arama kaydedilemedi.".to_string()))?;
+    }
+
+    SearchQueryStat::insert(SearchQueryStatActiveModel {
+        bucket: Set(bucket),
+        query: Set(query),
+        search_count: Set(1),
+        zero_result_count: Set(is_zero),
+        result_count: Set(result_count),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([SearchQueryStatColumn::Bucket, SearchQueryStatColumn::Query])
+            .value(
+                SearchQueryStatColumn::SearchCount,
+                Expr::col((SearchQueryStat, SearchQueryStatColumn::SearchCount)).add(1),
+            )
+            .value(
+                SearchQueryStatColumn::ZeroResultCount,
+                Expr::col((SearchQueryStat, SearchQueryStatColumn::ZeroResultCount)).add(is_zero),
+            )
+            .value(
+                SearchQueryStatColumn::ResultCount,
+                Expr::col((SearchQueryStat, SearchQueryStatColumn::ResultCount)).add(result_count),
+            )
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await
+    .map_err(|_| Error::InternalError("Arama kaydedilemedi.".to_string()))?;
+
+    Ok(())
+}
+
+/// Forgets the searchers of the hours no trending list looks at anymore.
+pub async fn prune_searchers(db: &DbConn) -> Result<u64> {
+    let before =
+        hour_bucket(chrono::Utc::now().naive_utc()) - Duration::hours(MAX_TRENDING_HOURS - 1);
+
+    SearchQuerySearcher::delete_many()
+        .filter(SearchQuerySearcherColumn::Bucket.lt(before))
+        .exec(db)
+        .await
+        .map(|result| result.rows_affected)
+        .map_err(|_| Error::InternalError("Aramacılar silinemedi.".to_string()))
+}
+
+/// Keeps queries holding any of the `denylist` words off a list.
+fn not_denied(denylist: &[String]) -> Condition {
+    denylist
+        .iter()
+        .filter_map(|word| normalize_query(word))
+        .fold(Condition::all(), |condition, word| {
+            let pattern = word
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+
+            condition.add(
+                Expr::col(SearchQuerySearcherColumn::Query)
+                    .not_like(LikeExpr::new(format!("%{pattern}%")).escape('\\')),
+            )
+        })
+}
+
+/// Lists the queries the most people searched for in the last hours, leaving
+/// out those holding a word of `denylist`.
+pub async fn trending(
+    db: &DbConn,
+    denylist: &[String],
+    query: TrendingSearchQuery,
+) -> Result<Vec<TrendingSearchDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let since = hour_bucket(chrono::Utc::now().naive_utc())
+        - Duration::hours(i64::from(query.hours.unwrap_or(24)) - 1);
+    let searchers = Expr::col(SearchQuerySearcherColumn::Searcher).count_distinct();
+
+    let rows: Vec<(String, i64)> = SearchQuerySearcher::find()
+        .select_only()
+        .column(SearchQuerySearcherColumn::Query)
+        .expr(searchers.clone())
+        .filter(SearchQuerySearcherColumn::Bucket.gte(since))
+        .filter(not_denied(denylist))
+        .group_by(SearchQuerySearcherColumn::Query)
+        .having(Expr::expr(searchers.clone()).gte(MIN_TRENDING_SEARCHERS))
+        .order_by(searchers, Order::Desc)
+        .order_by_asc(SearchQuerySearcherColumn::Query)
+        .limit(u64::from(query.limit.unwrap_or(10)))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Popüler aramalar getirilemedi.".to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(query, search_count)| TrendingSearchDto {
+            query,
+            search_count,
+        })
+        .collect())
+}
+
+/// Lists the queries that most often found nothing in the last days, which
+/// are titles people look for but can't find.
+pub async fn zero_result_searches(
+    db: &DbConn,
+    query: ZeroResultSearchQuery,
+) -> Result<Vec<ZeroResultSearchDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let since = chrono::Utc::now().naive_utc() - Duration::days(i64::from(query.days.unwrap_or(7)));
+    let zero_results = Expr::col(SearchQueryStatColumn::ZeroResultCount).sum();
+
+    let rows: Vec<(String, i64, i64, NaiveDateTime)> = SearchQueryStat::find()
+        .select_only()
+        .column(SearchQueryStatColumn::Query)
+        .expr(zero_results.clone())
+        .expr(Expr::col(SearchQueryStatColumn::SearchCount).sum())
+        .expr(Expr::col(SearchQueryStatColumn::Bucket).max())
+        .filter(SearchQueryStatColumn::Bucket.gte(hour_bucket(since)))
+        .group_by(SearchQueryStatColumn::Query)
+        .having(Expr::expr(zero_results.clone()).gt(0))
+        .order_by(zero_results, Order::Desc)
+        .order_by_asc(SearchQueryStatColumn::Query)
+        .limit(u64::from(query.limit.unwrap_or(50)))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Sonuçsuz aramalar getirilemedi.".to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(query, zero_result_count, search_count, last_searched_at)| ZeroResultSearchDto {
+                query,
+                zero_result_count,
+                search_count,
+                last_searched_at: timestamp(last_searched_at),
+            },
+        )
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("  IŞIK   İstanbul\t"),
+            Some("ışık istanbul".to_string())
+        );
+        assert_eq!(normalize_query("@SSG"), Some("@ssg".to_string()));
+        assert_eq!(normalize_query("   "), None);
+        assert_eq!(
+            normalize_query(&"a".repeat(150)).map(|query| query.chars().count()),
+            Some(MAX_QUERY_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_personal_queries_are_dropped() {
+        assert_eq!(normalize_query("ali@example.com"), None);
+        assert_eq!(normalize_query("0532 1234567"), None);
+        assert!(normalize_query("1984 kitabı").is_some());
+        assert!(normalize_query("@ssg entryleri").is_some());
+    }
+
+    #[test]
+    fn test_searchers_are_hashed_with_the_secret() {
+        let hash = |secret, user_id, ip| Searcher::new(secret, user_id, ip).map(|s| s.0);
+
+        assert_eq!(
+            hash("a", Some(1), Some("10.0.0.1")),
+            hash("a", Some(1), None)
+        );
+        assert_ne!(hash("a", Some(1), None), hash("b", Some(1), None));
+        assert_ne!(
+            hash("a", None, Some("10.0.0.1")),
+            hash("a", None, Some("10.0.0.2"))
+        );
+        assert!(!hash("a", None, Some("10.0.0.1"))
+            .unwrap()
+            .contains("10.0.0.1"));
+        assert_eq!(hash("a", None, None), None);
+    }
+
+    #[test]
+    fn test_hour_bucket() {
+        let time = chrono::NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_micro_opt(14, 35, 12, 500)
+            .unwrap();
+
+        assert_eq!(
+            hour_bucket(time),
+            time.date().and_hms_opt(14, 0, 0).unwrap()
+        );
+    }
+}
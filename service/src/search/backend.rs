@@ -0,0 +1,174 @@
+//! Where entries are searched. The database searches itself by default;
+//! deployments that would rather keep full-text search off it use the
+//! embedded index in [`super::index`] instead.
+
+use ::entity::prelude::*;
+use futures::future::BoxFuture;
+use sea_orm::*;
+use sea_query::{Expr, SimpleExpr};
+
+use super::{query::ParsedQuery, MATCH_END, MATCH_START};
+use crate::{dto::search::EntrySearchQuery, time::Tz, Error, Result};
+
+pub struct SearchHit {
+    pub id: i32,
+    pub rank: f32,
+    /// Parts of the content around the matches, as written, with the matches
+    /// between `MATCH_START` and `MATCH_END`.
+    pub snippet: String,
+}
+
+/// A page of hits, best first.
+pub struct SearchHits {
+    pub total: u64,
+    pub hits: Vec<SearchHit>,
+}
+
+pub trait SearchBackend: Send + Sync {
+    /// Finds the entries matching `parsed` and the filters of `query`, and
+    /// returns the page `query` asks for. Only entries of visible titles by
+    /// authors who are neither deleted nor faded are found.
+    fn search<'a>(
+        &'a self,
+        db: &'a DbConn,
+        tz: Tz,
+        query: &'a EntrySearchQuery,
+        parsed: &'a ParsedQuery,
+    ) -> BoxFuture<'a, Result<SearchHits>>;
+
+    /// Brings the given entries up to date with the database, adding,
+    /// changing or removing them. Called after every change to an entry, its
+    /// title or its counters.
+    fn update_entries<'a>(&'a self, db: &'a DbConn, ids: Vec<i32>) -> BoxFuture<'a, Result<()>>;
+
+    /// Builds the whole index again from the database. Returns how many
+    /// entries were indexed.
+    fn rebuild<'a>(&'a self, db: &'a DbConn) -> BoxFuture<'a, Result<u64>>;
+}
+
+/// Brings the given entries up to date after a change that already went
+/// through. A failure only leaves the index behind until the next change or
+/// rebuild, so it is logged instead of failing the request.
+pub async fn reindex(backend: &dyn SearchBackend, db: &DbConn, ids: Vec<i32>) {
+    if let Err(err) = backend.update_entries(db, ids).await {
+        tracing::warn!("Could not update the search index: {err}");
+    }
+}
+
+/// Brings all entries of a title up to date, for example after it was hidden.
+pub async fn update_title(backend: &dyn SearchBackend, db: &DbConn, title_id: i32) -> Result<()> {
+    let ids: Vec<i32> = Entry::find()
+        .select_only()
+        .column(EntryColumn::Id)
+        .filter(EntryColumn::TitleId.eq(title_id))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?;
+
+    backend.update_entries(db, ids).await
+}
+
+/// Searches with PostgreSQL full-text search, over the `search_vector`
+/// column the database keeps up to date by itself.
+pub struct PostgresSearch;
+
+impl SearchBackend for PostgresSearch {
+    fn search<'a>(
+        &'a self,
+        db: &'a DbConn,
+        tz: Tz,
+        query: &'a EntrySearchQuery,
+        parsed: &'a ParsedQuery,
+    ) -> BoxFuture<'a, Result<SearchHits>> {
+        Box::pin(async move {
+            let (rank, snippet): (SimpleExpr, SimpleExpr) = match parsed.text_query() {
+                Some(text) => (
+                    Expr::cust_with_values(
+                        r#"ts_rank_cd("entry"."search_vector", websearch_to_tsquery('turkish', $1))"#,
+                        [text.clone()],
+                    ),
+                    // The headline is taken from the content as written, where a
+                    // capital I reads as a dotted i, so words with a dotless i are
+                    // looked for both ways.
+                    Expr::cust_with_values(
+                        r#"ts_headline('turkish', "entry"."content", websearch_to_tsquery('turkish', $1) || websearch_to_tsquery('turkish', $2), $3)"#,
+                        [
+                            text.clone(),
+                            text.replace('ı', "i"),
+                            format!(
+                                "StartSel={MATCH_START}, StopSel={MATCH_END}, MaxWords=35, MinWords=15, MaxFragments=2"
+                            ),
+                        ],
+                    ),
+                ),
+                // Only filters: all hits rank the same, and start the snippet.
+                None => (
+                    Expr::val(0f32).into(),
+                    Expr::cust(r#"left("entry"."content", 200)"#),
+                ),
+            };
+
+            let mut select = Entry::find()
+                .filter(parsed.condition(tz))
+                .filter(EntryColumn::DeletedAt.is_null())
+                .inner_join(Title)
+                .filter(TitleColumn::IsVisible.eq(true))
+                .inner_join(User)
+                .filter(UserColumn::DeletedAt.is_null())
+                .filter(UserColumn::IsFaded.eq(false));
+
+            if let Some(author) = query.author.clone() {
+                select = select.filter(UserColumn::Nickname.eq(author));
+            }
+            if let Some(title) = query.title.clone() {
+                select = select.filter(TitleColumn::Name.eq(title));
+            }
+            if let Some(from) = query.from {
+                select = select.filter(EntryColumn::CreatedAt.gte(from.naive_utc()));
+            }
+            if let Some(to) = query.to {
+                select = select.filter(EntryColumn::CreatedAt.lte(to.naive_utc()));
+            }
+            if let Some(min_net_votes) = query.min_net_votes {
+                select = select.filter(EntryColumn::NetVotes.gte(min_net_votes));
+            }
+
+            let total = select
+                .clone()
+                .count(db)
+                .await
+                .map_err(|_| Error::InternalError("Girdi sayısı getirilemedi.".to_string()))?;
+
+            let hits: Vec<(i32, f32, String)> = select
+                .select_only()
+                .column(EntryColumn::Id)
+                .expr(rank.clone())
+                .expr(snippet)
+                .order_by(rank, Order::Desc)
+                .order_by_desc(EntryColumn::Id)
+                .offset(u64::from(query.page - 1) * u64::from(query.per_page))
+                .limit(u64::from(query.per_page))
+                .into_tuple()
+                .all(db)
+                .await
+                .map_err(|_| Error::InternalError("Girdiler aranamadı.".to_string()))?;
+
+            Ok(SearchHits {
+                total,
+                hits: hits
+                    .into_iter()
+                    .map(|(id, rank, snippet)| SearchHit { id, rank, snippet })
+                    .collect(),
+            })
+        })
+    }
+
+    fn update_entries<'a>(&'a self, _: &'a DbConn, _: Vec<i32>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn rebuild<'a>(&'a self, _: &'a DbConn) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(async { Ok(0) })
+    }
+}
@@ -0,0 +1,857 @@
+//! An embedded [Tantivy](https://github.com/quickwit-oss/tantivy) index of
+//! the searchable entries, kept next to the server on disk. It holds a copy
+//! of what searches filter on, and is brought up to date by the services
+//! that change entries, their titles and their counters. Changes are
+//! committed together in the background, so a burst of votes costs a single
+//! commit.
+
+use std::{
+    ops::Bound,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Duration,
+};
+
+use ::entity::prelude::*;
+use chrono::NaiveDateTime;
+use futures::future::BoxFuture;
+use sea_orm::{ColumnTrait, Condition, DbConn, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use tantivy::{
+    collector::{Count, TopDocs},
+    directory::MmapDirectory,
+    query::{
+        AllQuery, BooleanQuery, ConstScoreQuery, Occur, PhraseQuery, Query, RangeQuery, TermQuery,
+    },
+    schema::{
+        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED,
+        STORED, STRING,
+    },
+    snippet::SnippetGenerator,
+    tokenizer::{
+        Language, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer, Token, TokenFilter,
+        TokenStream, Tokenizer,
+    },
+    DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument,
+};
+
+use super::{
+    backend::{SearchBackend, SearchHit, SearchHits},
+    fold_turkish,
+    query::{Comparison, ParsedQuery},
+    MATCH_END, MATCH_START,
+};
+use crate::{
+    dto::search::EntrySearchQuery,
+    time::{self, Tz},
+    Error, Result,
+};
+
+const TOKENIZER: &str = "turkish";
+
+/// Memory the writer may use before it flushes a segment.
+const WRITER_MEMORY: usize = 50_000_000;
+
+/// How long changes wait before searches see them. They are committed
+/// together, as each commit writes a segment and reloads the reader.
+const COMMIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many entries are read from the database at once while rebuilding.
+const REBUILD_BATCH: u64 = 1000;
+
+/// The longest snippet, in bytes.
+const SNIPPET_LENGTH: usize = 300;
+
+/// Lower cases tokens the Turkish way, as `lower` in PostgreSQL doesn't: a
+/// capital I becomes a dotless ı and a dotted İ becomes an i.
+#[derive(Clone)]
+struct TurkishLowerCaser;
+
+impl TokenFilter for TurkishLowerCaser {
+    type Tokenizer<T: Tokenizer> = TurkishLowerCaserFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        TurkishLowerCaserFilter(tokenizer)
+    }
+}
+
+#[derive(Clone)]
+struct TurkishLowerCaserFilter<T>(T);
+
+impl<T: Tokenizer> Tokenizer for TurkishLowerCaserFilter<T> {
+    type TokenStream<'a> = TurkishLowerCaserStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        TurkishLowerCaserStream(self.0.token_stream(text))
+    }
+}
+
+struct TurkishLowerCaserStream<T>(T);
+
+impl<T: TokenStream> TokenStream for TurkishLowerCaserStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.0.advance() {
+            return false;
+        }
+
+        let token = self.0.token_mut();
+        token.text = fold_turkish(&token.text).to_lowercase();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.0.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.0.token_mut()
+    }
+}
+
+fn analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(TurkishLowerCaser)
+        .filter(Stemmer::new(Language::Turkish))
+        .build()
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    id: Field,
+    content: Field,
+    /// The nickname of the author.
+    author: Field,
+    /// The name of the title.
+    title: Field,
+    /// Microseconds since the epoch.
+    created_at: Field,
+    net_votes: Field,
+    total_favorites: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+
+    let content = TextOptions::default()
+        .set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        )
+        .set_stored();
+
+    let fields = Fields {
+        id: builder.add_i64_field("id", INDEXED | STORED | FAST),
+        content: builder.add_text_field("content", content),
+        author: builder.add_text_field("author", STRING),
+        title: builder.add_text_field("title", STRING),
+        created_at: builder.add_i64_field("created_at", INDEXED | FAST),
+        net_votes: builder.add_i64_field("net_votes", INDEXED | FAST),
+        total_favorites: builder.add_i64_field("total_favorites", INDEXED | FAST),
+    };
+
+    (builder.build(), fields)
+}
+
+/// What the index keeps of an entry.
+struct IndexedEntry {
+    id: i32,
+    content: String,
+    created_at: NaiveDateTime,
+    net_votes: i32,
+    total_favorites: i32,
+    title: String,
+    author: String,
+}
+
+impl IndexedEntry {
+    fn document(&self, fields: &Fields) -> TantivyDocument {
+        let mut document = TantivyDocument::default();
+        document.add_i64(fields.id, i64::from(self.id));
+        document.add_text(fields.content, &self.content);
+        document.add_text(fields.author, &self.author);
+        document.add_text(fields.title, &self.title);
+        document.add_i64(fields.created_at, micros(self.created_at));
+        document.add_i64(fields.net_votes, i64::from(self.net_votes));
+        document.add_i64(fields.total_favorites, i64::from(self.total_favorites));
+        document
+    }
+}
+
+fn micros(time: NaiveDateTime) -> i64 {
+    time.and_utc().timestamp_micros()
+}
+
+/// Reads the entries matching `condition` that searches can find: not
+/// deleted, in a visible title, by an author who is neither deleted nor
+/// faded.
+async fn searchable_entries(
+    db: &DbConn,
+    condition: Condition,
+    limit: Option<u64>,
+) -> Result<Vec<IndexedEntry>> {
+    let rows: Vec<(i32, String, NaiveDateTime, i32, i32, String, String)> = Entry::find()
+        .select_only()
+        .column(EntryColumn::Id)
+        .column(EntryColumn::Content)
+        .column(EntryColumn::CreatedAt)
+        .column(EntryColumn::NetVotes)
+        .column(EntryColumn::TotalFavorites)
+        .column(TitleColumn::Name)
+        .column(UserColumn::Nickname)
+        .filter(condition)
+        .filter(EntryColumn::DeletedAt.is_null())
+        .inner_join(Title)
+        .filter(TitleColumn::IsVisible.eq(true))
+        .inner_join(User)
+        .filter(UserColumn::DeletedAt.is_null())
+        .filter(UserColumn::IsFaded.eq(false))
+        .order_by_asc(EntryColumn::Id)
+        .limit(limit)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(id, content, created_at, net_votes, total_favorites, title, author)| IndexedEntry {
+                id,
+                content,
+                created_at,
+                net_votes,
+                total_favorites,
+                title,
+                author,
+            },
+        )
+        .collect())
+}
+
+fn index_error(error: impl std::fmt::Display) -> Error {
+    Error::InternalError(format!("Arama dizini kullanılamadı: {error}"))
+}
+
+struct Inner {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+    /// Whether the writer holds changes that weren't committed yet.
+    dirty: AtomicBool,
+}
+
+impl Inner {
+    /// Commits the pending changes and makes them visible to searches.
+    fn commit(&self) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(index_error)?;
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        writer.commit().map_err(index_error)?;
+        self.reader.reload().map_err(index_error)
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if !*self.dirty.get_mut() {
+            return;
+        }
+
+        if let Ok(writer) = self.writer.get_mut() {
+            if let Err(err) = writer.commit() {
+                tracing::warn!("Could not commit the search index: {err}");
+            }
+        }
+    }
+}
+
+/// Commits the changes of the index every `interval`, until the index is
+/// closed.
+fn committer(inner: Weak<Inner>, interval: Duration) {
+    loop {
+        std::thread::sleep(interval);
+
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        if let Err(err) = inner.commit() {
+            tracing::warn!("Could not commit the search index: {err}");
+        }
+    }
+}
+
+/// Searches an embedded Tantivy index.
+#[derive(Clone)]
+pub struct TantivySearch {
+    inner: Arc<Inner>,
+}
+
+impl TantivySearch {
+    /// Opens the index in `path`, or creates an empty one there. Only one
+    /// process can have an index open at a time.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with(path, COMMIT_INTERVAL)
+    }
+
+    fn open_with(path: impl AsRef<Path>, commit_interval: Duration) -> Result<Self> {
+        std::fs::create_dir_all(&path).map_err(index_error)?;
+
+        let (schema, fields) = schema();
+        let directory = MmapDirectory::open(path).map_err(index_error)?;
+        let index = Index::open_or_create(directory, schema).map_err(index_error)?;
+        index.tokenizers().register(TOKENIZER, analyzer());
+
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(index_error)?;
+        let writer = index.writer(WRITER_MEMORY).map_err(index_error)?;
+
+        let inner = Arc::new(Inner {
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            fields,
+            dirty: AtomicBool::new(false),
+        });
+
+        let weak = Arc::downgrade(&inner);
+        std::thread::Builder::new()
+            .name("search-index-committer".to_string())
+            .spawn(move || committer(weak, commit_interval))
+            .map_err(index_error)?;
+
+        Ok(Self { inner })
+    }
+
+    /// How many entries are in the index.
+    pub fn num_entries(&self) -> u64 {
+        self.inner.reader.searcher().num_docs()
+    }
+
+    /// Runs `write` with the writer on a blocking thread. The changes are
+    /// committed with the next ones, within `COMMIT_INTERVAL`.
+    async fn write<F>(&self, write: F) -> Result<()>
+    where
+        F: FnOnce(&IndexWriter, &Fields) -> tantivy::Result<()> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let writer = inner.writer.lock().map_err(index_error)?;
+            write(&writer, &inner.fields).map_err(index_error)?;
+            inner.dirty.store(true, Ordering::Release);
+            Ok(())
+        })
+        .await
+        .map_err(index_error)?
+    }
+
+    /// Commits the pending changes right away.
+    async fn commit(&self) -> Result<()> {
+        let inner = self.inner.clone();
+
+        tokio::task::spawn_blocking(move || inner.commit())
+            .await
+            .map_err(index_error)?
+    }
+
+    /// Splits `text` into the terms the content is indexed with.
+    fn terms(&self, text: &str) -> Vec<tantivy::Term> {
+        let mut analyzer = self
+            .inner
+            .index
+            .tokenizers()
+            .get(TOKENIZER)
+            .unwrap_or_else(analyzer);
+        let mut stream = analyzer.token_stream(text);
+
+        let mut terms = Vec::new();
+        while stream.advance() {
+            terms.push(tantivy::Term::from_field_text(
+                self.inner.fields.content,
+                &stream.token().text,
+            ));
+        }
+
+        terms
+    }
+
+    /// A word or phrase of the query. Words the tokenizer splits, such as
+    /// `e-posta`, are looked for as phrases.
+    fn term_query(&self, text: &str) -> Option<Box<dyn Query>> {
+        let mut terms = self.terms(text);
+
+        match terms.len() {
+            0 => None,
+            1 => Some(Box::new(TermQuery::new(
+                terms.remove(0),
+                IndexRecordOption::WithFreqs,
+            ))),
+            _ => Some(Box::new(PhraseQuery::new(terms))),
+        }
+    }
+
+    /// The words and phrases of the query, as `websearch_to_tsquery` reads
+    /// them: all have to match, except that `or` between two of them lets
+    /// either match, and excluded ones must not. `None` when the query only
+    /// has filters.
+    fn text_query(&self, parsed: &ParsedQuery) -> Option<Box<dyn Query>> {
+        if parsed.terms.is_empty() {
+            return None;
+        }
+
+        let mut groups: Vec<Vec<Box<dyn Query>>> = Vec::new();
+        let mut excluded = Vec::new();
+        let mut or_next = false;
+
+        for term in &parsed.terms {
+            if !term.phrase && !term.excluded && term.text.eq_ignore_ascii_case("or") {
+                or_next = !groups.is_empty();
+                continue;
+            }
+
+            let Some(query) = self.term_query(&term.text) else {
+                continue;
+            };
+
+            match groups.last_mut() {
+                _ if term.excluded => excluded.push(query),
+                Some(group) if or_next => group.push(query),
+                _ => groups.push(vec![query]),
+            }
+            or_next = false;
+        }
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = groups
+            .into_iter()
+            .map(|mut group| {
+                let query: Box<dyn Query> = if group.len() == 1 {
+                    group.remove(0)
+                } else {
+                    Box::new(BooleanQuery::union(group))
+                };
+                (Occur::Must, query)
+            })
+            .collect();
+
+        if clauses.is_empty() {
+            clauses.push((
+                Occur::Must,
+                Box::new(ConstScoreQuery::new(Box::new(AllQuery), 0.0)),
+            ));
+        }
+        clauses.extend(excluded.into_iter().map(|query| (Occur::MustNot, query)));
+
+        Some(Box::new(BooleanQuery::new(clauses)))
+    }
+
+    /// The filters of the query and of the request, as in
+    /// [`ParsedQuery::condition`].
+    fn filters(
+        &self,
+        tz: Tz,
+        query: &EntrySearchQuery,
+        parsed: &ParsedQuery,
+    ) -> Vec<(Occur, Box<dyn Query>)> {
+        let fields = self.inner.fields;
+        // Filters only narrow the hits down, they don't add to the rank.
+        let text = |field, value: &str| -> Box<dyn Query> {
+            Box::new(ConstScoreQuery::new(
+                Box::new(TermQuery::new(
+                    tantivy::Term::from_field_text(field, value),
+                    IndexRecordOption::Basic,
+                )),
+                0.0,
+            ))
+        };
+        let range = |name: &str, lower, upper| -> Box<dyn Query> {
+            Box::new(ConstScoreQuery::new(
+                Box::new(RangeQuery::new_i64_bounds(name.to_string(), lower, upper)),
+                0.0,
+            ))
+        };
+
+        let mut filters = Vec::new();
+
+        for author in parsed.author.iter().chain(&query.author) {
+            filters.push((Occur::Must, text(fields.author, author)));
+        }
+        for author in &parsed.excluded_authors {
+            filters.push((Occur::MustNot, text(fields.author, author)));
+        }
+        for title in parsed.title.iter().chain(&query.title) {
+            filters.push((Occur::Must, text(fields.title, title)));
+        }
+        for title in &parsed.excluded_titles {
+            filters.push((Occur::MustNot, text(fields.title, title)));
+        }
+
+        let after = parsed
+            .after
+            .and_then(|after| after.succ_opt())
+            .map(|after| Bound::Included(micros(time::day_start(after, tz))));
+        let before = parsed
+            .before
+            .map(|before| Bound::Excluded(micros(time::day_start(before, tz))));
+        let from = query
+            .from
+            .map(|from| Bound::Included(micros(from.naive_utc())));
+        let to = query.to.map(|to| Bound::Included(micros(to.naive_utc())));
+
+        for lower in after.into_iter().chain(from) {
+            filters.push((Occur::Must, range("created_at", lower, Bound::Unbounded)));
+        }
+        for upper in before.into_iter().chain(to) {
+            filters.push((Occur::Must, range("created_at", Bound::Unbounded, upper)));
+        }
+
+        if let Some(favorites) = parsed.favorites {
+            let (lower, upper) = bounds(favorites);
+            filters.push((Occur::Must, range("total_favorites", lower, upper)));
+        }
+        if let Some(votes) = parsed.votes {
+            let (lower, upper) = bounds(votes);
+            filters.push((Occur::Must, range("net_votes", lower, upper)));
+        }
+        if let Some(min_net_votes) = query.min_net_votes {
+            filters.push((
+                Occur::Must,
+                range(
+                    "net_votes",
+                    Bound::Included(i64::from(min_net_votes)),
+                    Bound::Unbounded,
+                ),
+            ));
+        }
+
+        filters
+    }
+}
+
+fn bounds((comparison, value): (Comparison, i32)) -> (Bound<i64>, Bound<i64>) {
+    let value = i64::from(value);
+
+    match comparison {
+        Comparison::Eq => (Bound::Included(value), Bound::Included(value)),
+        Comparison::Gt => (Bound::Excluded(value), Bound::Unbounded),
+        Comparison::Gte => (Bound::Included(value), Bound::Unbounded),
+        Comparison::Lt => (Bound::Unbounded, Bound::Excluded(value)),
+        Comparison::Lte => (Bound::Unbounded, Bound::Included(value)),
+    }
+}
+
+/// Reads the id and the snippet of a hit. Without words to look for, the
+/// snippet is the start of the content.
+fn hit(
+    searcher: &Searcher,
+    fields: &Fields,
+    snippets: Option<&SnippetGenerator>,
+    address: DocAddress,
+    rank: f32,
+) -> tantivy::Result<SearchHit> {
+    let document: TantivyDocument = searcher.doc(address)?;
+    let id = document
+        .get_first(fields.id)
+        .and_then(|value| value.as_i64())
+        .unwrap_or_default();
+    let content = document
+        .get_first(fields.content)
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+
+    let snippet = snippets
+        .map(|snippets| snippets.snippet(content))
+        .filter(|snippet| !snippet.is_empty())
+        .map(|snippet| {
+            let fragment = snippet.fragment();
+            let mut marked = String::with_capacity(fragment.len());
+            let mut end = 0;
+
+            for range in snippet.highlighted() {
+                marked.push_str(&fragment[end..range.start]);
+                marked.push(MATCH_START);
+                marked.push_str(&fragment[range.clone()]);
+                marked.push(MATCH_END);
+                end = range.end;
+            }
+            marked.push_str(&fragment[end..]);
+
+            marked
+        })
+        .unwrap_or_else(|| content.chars().take(200).collect());
+
+    Ok(SearchHit {
+        id: i32::try_from(id).unwrap_or_default(),
+        rank,
+        snippet,
+    })
+}
+
+impl SearchBackend for TantivySearch {
+    fn search<'a>(
+        &'a self,
+        _: &'a DbConn,
+        tz: Tz,
+        query: &'a EntrySearchQuery,
+        parsed: &'a ParsedQuery,
+    ) -> BoxFuture<'a, Result<SearchHits>> {
+        Box::pin(async move {
+            let text = self.text_query(parsed);
+            let mut clauses = self.filters(tz, query, parsed);
+            let has_text = text.is_some();
+            clauses.push((Occur::Must, text.unwrap_or_else(|| Box::new(AllQuery))));
+            let search = BooleanQuery::new(clauses);
+
+            let offset = (query.page as usize - 1) * usize::from(query.per_page);
+            let limit = usize::from(query.per_page);
+            let inner = self.inner.clone();
+
+            tokio::task::spawn_blocking(move || -> tantivy::Result<SearchHits> {
+                let searcher = inner.reader.searcher();
+                let fields = &inner.fields;
+                let top = TopDocs::with_limit(limit).and_offset(offset);
+
+                // Ranked by relevance, or like the database by newest first
+                // when there is nothing to rank by.
+                let (total, addresses): (usize, Vec<(f32, DocAddress)>) = if has_text {
+                    searcher.search(&search, &(Count, top))?
+                } else {
+                    let (total, addresses) = searcher.search(
+                        &search,
+                        &(
+                            Count,
+                            top.order_by_fast_field::<i64>("id", tantivy::Order::Desc),
+                        ),
+                    )?;
+                    let addresses = addresses
+                        .into_iter()
+                        .map(|(_, address)| (0.0, address))
+                        .collect();
+                    (total, addresses)
+                };
+
+                let snippets = if has_text {
+                    let mut snippets =
+                        SnippetGenerator::create(&searcher, &search, fields.content)?;
+                    snippets.set_max_num_chars(SNIPPET_LENGTH);
+                    Some(snippets)
+                } else {
+                    None
+                };
+
+                let hits = addresses
+                    .into_iter()
+                    .map(|(rank, address)| hit(&searcher, fields, snippets.as_ref(), address, rank))
+                    .collect::<tantivy::Result<_>>()?;
+
+                Ok(SearchHits {
+                    total: total as u64,
+                    hits,
+                })
+            })
+            .await
+            .map_err(index_error)?
+            .map_err(index_error)
+        })
+    }
+
+    fn update_entries<'a>(&'a self, db: &'a DbConn, ids: Vec<i32>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if ids.is_empty() {
+                return Ok(());
+            }
+
+            let entries = searchable_entries(
+                db,
+                Condition::all().add(EntryColumn::Id.is_in(ids.clone())),
+                None,
+            )
+            .await?;
+
+            self.write(move |writer, fields| {
+                for id in ids {
+                    writer.delete_term(tantivy::Term::from_field_i64(fields.id, i64::from(id)));
+                }
+                for entry in entries {
+                    writer.add_document(entry.document(fields))?;
+                }
+                Ok(())
+            })
+            .await
+        })
+    }
+
+    fn rebuild<'a>(&'a self, db: &'a DbConn) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(async move {
+            let inner = self.inner.clone();
+            tokio::task::spawn_blocking(move || {
+                let writer = inner.writer.lock().map_err(index_error)?;
+                writer
+                    .delete_all_documents()
+                    .map(|_| ())
+                    .map_err(index_error)
+            })
+            .await
+            .map_err(index_error)??;
+
+            // The documents are added in batches and committed at the end,
+            // so that searches see the old index until the new one is done.
+            let mut count = 0;
+            let mut last_id = 0;
+            loop {
+                let entries = searchable_entries(
+                    db,
+                    Condition::all().add(EntryColumn::Id.gt(last_id)),
+                    Some(REBUILD_BATCH),
+                )
+                .await?;
+                let Some(last) = entries.last() else {
+                    break;
+                };
+                last_id = last.id;
+                count += entries.len() as u64;
+
+                let inner = self.inner.clone();
+                tokio::task::spawn_blocking(move || -> tantivy::Result<()> {
+                    let writer = inner.writer.lock().map_err(|_| {
+                        tantivy::TantivyError::InternalError("Yazıcı kilitli.".to_string())
+                    })?;
+                    for entry in entries {
+                        writer.add_document(entry.document(&inner.fields))?;
+                    }
+                    Ok(())
+                })
+                .await
+                .map_err(index_error)?
+                .map_err(index_error)?;
+            }
+
+            self.inner.dirty.store(true, Ordering::Release);
+            self.commit().await?;
+
+            Ok(count)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<String> {
+        let mut analyzer = analyzer();
+        let mut stream = analyzer.token_stream(text);
+
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_turkish_lower_case() {
+        assert_eq!(tokens("IŞIK"), tokens("ışık"));
+        assert_eq!(tokens("İSTANBUL"), tokens("istanbul"));
+        assert_ne!(tokens("IŞIK"), tokens("işik"));
+    }
+
+    #[test]
+    fn test_turkish_stemming() {
+        assert_eq!(tokens("kitapları"), tokens("kitap"));
+        assert_eq!(tokens("e-posta"), tokens("e posta"));
+        assert_eq!(tokens("e-posta").len(), 2);
+    }
+
+    fn entry(id: i32) -> IndexedEntry {
+        IndexedEntry {
+            id,
+            content: format!("girdi {id}"),
+            created_at: NaiveDateTime::default(),
+            net_votes: 0,
+            total_favorites: 0,
+            title: "başlık".to_string(),
+            author: "yazar".to_string(),
+        }
+    }
+
+    async fn add(index: &TantivySearch, id: i32) {
+        index
+            .write(move |writer, fields| {
+                writer.add_document(entry(id).document(fields)).map(|_| ())
+            })
+            .await
+            .unwrap();
+    }
+
+    fn index_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("arpacik-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_committed_together() {
+        let path = index_path("index-commit");
+        let index = TantivySearch::open_with(&path, Duration::from_secs(3600)).unwrap();
+
+        add(&index, 1).await;
+        add(&index, 2).await;
+        assert_eq!(index.num_entries(), 0);
+
+        index.commit().await.unwrap();
+        assert_eq!(index.num_entries(), 2);
+
+        // Closing the index commits what is left.
+        add(&index, 3).await;
+        drop(index);
+        let index = TantivySearch::open(&path).unwrap();
+        assert_eq!(index.num_entries(), 3);
+
+        drop(index);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_committed_in_the_background() {
+        let path = index_path("index-committer");
+        let index = TantivySearch::open_with(&path, Duration::from_millis(20)).unwrap();
+
+        add(&index, 1).await;
+        for _ in 0..100 {
+            if index.num_entries() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(index.num_entries(), 1);
+
+        drop(index);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(
+            bounds((Comparison::Gte, 10)),
+            (Bound::Included(10), Bound::Unbounded)
+        );
+        assert_eq!(
+            bounds((Comparison::Eq, -2)),
+            (Bound::Included(-2), Bound::Included(-2))
+        );
+        assert_eq!(
+            bounds((Comparison::Lt, 3)),
+            (Bound::Unbounded, Bound::Excluded(3))
+        );
+    }
+}
@@ -0,0 +1,193 @@
+pub mod analytics;
+pub mod backend;
+pub mod index;
+pub mod query;
+pub mod title;
+
+use std::collections::HashMap;
+
+use ::entity::prelude::*;
+use sea_orm::*;
+use validator::Validate;
+
+use self::backend::{SearchBackend, SearchHits};
+use crate::{
+    dto::{
+        pagination::PaginationResponse,
+        search::{
+            EntrySearchHitDto, EntrySearchQuery, SearchItem, SearchQuery, TitleAutocompleteQuery,
+        },
+    },
+    loader::entry_dtos,
+    time::Tz,
+    Error, Result,
+};
+
+/// Wrap the matches in snippets from the backends. Private use characters
+/// can't clash with the content, and are replaced after the snippet is
+/// escaped.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+pub async fn search(
+    db: &DbConn,
+    sq: SearchQuery,
+    searcher: Option<analytics::Searcher>,
+) -> Result<Vec<SearchItem>> {
+    let items = find(db, sq.query.clone()).await?;
+
+    // Counting is best effort, a search doesn't fail because of it.
+    analytics::record_search(db, &sq.query, items.len(), searcher.as_ref())
+        .await
+        .ok();
+
+    Ok(items)
+}
+
+/// Finds users by `@nickname`, and titles by anything else.
+async fn find(db: &DbConn, query: String) -> Result<Vec<SearchItem>> {
+    if query.starts_with('@') {
+        let users: Vec<SearchItem> = User::find()
+            .filter(UserColumn::Nickname.contains(query.trim_start_matches('@')))
+            .filter(UserColumn::DeletedAt.is_null())
+            .offset(0)
+            .limit(10)
+            .all(db)
+            .await
+            .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+            .map(|users| {
+                users
+                    .into_iter()
+                    .map(|user| SearchItem {
+                        id: user.id,
+                        name: user.nickname,
+                    })
+                    .collect()
+            })?;
+
+        Ok(users)
+    } else {
+        let titles: Vec<SearchItem> = title::autocomplete(
+            db,
+            TitleAutocompleteQuery {
+                query,
+                limit: Some(10),
+            },
+        )
+        .await?
+        .into_iter()
+        .map(|title| SearchItem {
+            id: title.id,
+            name: title.name,
+        })
+        .collect();
+
+        Ok(titles)
+    }
+}
+
+/// Folds the dotted and dotless capital I the way the search index does, see
+/// the `entry_search` migration.
+pub fn fold_turkish(text: &str) -> String {
+    text.replace('I', "ı").replace('İ', "i")
+}
+
+/// Escapes a snippet from `ts_headline` and marks its matches.
+fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+
+    for c in snippet.chars() {
+        match c {
+            MATCH_START => html.push_str("<mark>"),
+            MATCH_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+
+    html
+}
+
+/// Searches the content of entries, ranking the best matches first.
+///
+/// The query is written in the language of [`query`]. Dates in it are days in
+/// `tz`.
+pub async fn search_entries(
+    db: &DbConn,
+    backend: &dyn SearchBackend,
+    tz: Tz,
+    query: EntrySearchQuery,
+    viewer_id: Option<i32>,
+) -> Result<PaginationResponse<EntrySearchHitDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let parsed = query::parse(&query.query)?;
+
+    let SearchHits { total, hits } = backend.search(db, tz, &query, &parsed).await?;
+
+    let entries = Entry::find()
+        .filter(EntryColumn::Id.is_in(hits.iter().map(|hit| hit.id)))
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdiler getirilemedi.".to_string()))?;
+
+    let mut entries: HashMap<i32, _> = entry_dtos(db, entries, viewer_id)
+        .await?
+        .into_iter()
+        .map(|entry| (entry.id, entry))
+        .collect();
+
+    let items = hits
+        .into_iter()
+        .filter_map(|hit| {
+            entries.remove(&hit.id).map(|entry| EntrySearchHitDto {
+                entry,
+                rank: hit.rank,
+                snippet: highlight(&hit.snippet),
+            })
+        })
+        .collect();
+
+    Ok(PaginationResponse {
+        total: Some(total),
+        page: Some(query.page),
+        per_page: query.per_page,
+        items,
+        next: None,
+        prev: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_turkish() {
+        assert_eq!(fold_turkish("IŞIK İSTANBUL"), "ıŞıK iSTANBUL");
+        assert_eq!(fold_turkish("ılık izmir"), "ılık izmir");
+    }
+
+    #[test]
+    fn test_highlight_marks_matches() {
+        let snippet = format!("bir {MATCH_START}kitap{MATCH_END} okudum");
+
+        assert_eq!(highlight(&snippet), "bir <mark>kitap</mark> okudum");
+    }
+
+    #[test]
+    fn test_highlight_escapes_content() {
+        let snippet = format!("<script>{MATCH_START}a & b{MATCH_END}</script> \"'");
+
+        assert_eq!(
+            highlight(&snippet),
+            "&lt;script&gt;<mark>a &amp; b</mark>&lt;/script&gt; &quot;&#39;"
+        );
+    }
+}
@@ -0,0 +1,631 @@
+//! The search query language:
+//!
+//! ```text
+//! @ssg "tam ifade" -hariç title:film after:2024-01-01 fav:>10
+//! ```
+//!
+//! - Words and `"quoted phrases"` are looked up in the content. A leading `-`
+//!   excludes them, and `or` between two of them matches either.
+//! - `@nickname` and `title:name` keep the entries of one author or title.
+//!   With a leading `-` they leave them out instead. Names with spaces are
+//!   quoted, as in `title:"iki kelime"`.
+//! - `after:` and `before:` take a `YYYY-MM-DD` date, both exclusive.
+//! - `fav:` and `votes:` compare the favorites and net votes with `>`, `>=`,
+//!   `<`, `<=` or `=`, which is the default.
+
+use ::entity::prelude::*;
+use chrono::NaiveDate;
+use sea_orm::*;
+use sea_query::{Expr, SimpleExpr};
+use thiserror::Error;
+
+use super::fold_turkish;
+use crate::time::{self, Tz};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub text: String,
+    pub phrase: bool,
+    pub excluded: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedQuery {
+    pub terms: Vec<Term>,
+    pub author: Option<String>,
+    pub excluded_authors: Vec<String>,
+    pub title: Option<String>,
+    pub excluded_titles: Vec<String>,
+    pub after: Option<NaiveDate>,
+    pub before: Option<NaiveDate>,
+    pub favorites: Option<(Comparison, i32)>,
+    pub votes: Option<(Comparison, i32)>,
+}
+
+/// Why a query couldn't be parsed. Positions count characters from 1.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum QueryError {
+    #[error("Arama sorgusu boş.")]
+    Empty,
+    #[error("{position}. karakterdeki tırnak kapatılmamış.")]
+    UnclosedQuote { position: usize },
+    #[error("{position}. karakterdeki `{field}` için bir değer girilmeli.")]
+    EmptyValue { field: String, position: usize },
+    #[error("{position}. karakterde bilinmeyen alan: `{field}`.")]
+    UnknownField { field: String, position: usize },
+    #[error("{position}. karakterdeki `{field}` alanı birden fazla kez kullanılmış.")]
+    DuplicateField { field: String, position: usize },
+    #[error("{position}. karakterdeki `{field}` alanı dışlanamaz.")]
+    CannotExclude { field: String, position: usize },
+    #[error(
+        "{position}. karakterdeki tarih geçersiz: `{value}`. Tarihler YYYY-AA-GG biçiminde olmalı."
+    )]
+    InvalidDate { value: String, position: usize },
+    #[error("{position}. karakterdeki sayı geçersiz: `{value}`.")]
+    InvalidNumber { value: String, position: usize },
+}
+
+impl From<QueryError> for crate::Error {
+    fn from(error: QueryError) -> Self {
+        crate::Error::InvalidRequest(error.to_string())
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::iter::Enumerate<std::str::Chars<'a>>>,
+}
+
+impl Parser<'_> {
+    /// The position of the next character, counted from 1.
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(usize::MAX, |&(i, _)| i + 1)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.chars.next();
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek().filter(|&c| predicate(c)) {
+            taken.push(c);
+            self.chars.next();
+        }
+
+        taken
+    }
+
+    /// Reads a quoted string, without the quotes, or a string up to the next
+    /// whitespace.
+    fn value(&mut self) -> Result<String, QueryError> {
+        if self.peek() != Some('"') {
+            return Ok(self.take_while(|c| !c.is_whitespace()));
+        }
+
+        let position = self.position();
+        self.chars.next();
+        let value = self.take_while(|c| c != '"');
+        if self.chars.next().is_none() {
+            return Err(QueryError::UnclosedQuote { position });
+        }
+
+        Ok(value)
+    }
+}
+
+fn set_once<T>(
+    slot: &mut Option<T>,
+    value: T,
+    field: &str,
+    position: usize,
+) -> Result<(), QueryError> {
+    if slot.is_some() {
+        return Err(QueryError::DuplicateField {
+            field: field.to_string(),
+            position,
+        });
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
+
+fn parse_date(value: &str, position: usize) -> Result<NaiveDate, QueryError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| QueryError::InvalidDate {
+        value: value.to_string(),
+        position,
+    })
+}
+
+fn parse_comparison(value: &str, position: usize) -> Result<(Comparison, i32), QueryError> {
+    let (comparison, number) = [
+        (">=", Comparison::Gte),
+        ("<=", Comparison::Lte),
+        (">", Comparison::Gt),
+        ("<", Comparison::Lt),
+        ("=", Comparison::Eq),
+    ]
+    .into_iter()
+    .find_map(|(prefix, comparison)| Some((comparison, value.strip_prefix(prefix)?)))
+    .unwrap_or((Comparison::Eq, value));
+
+    let number = number.parse().map_err(|_| QueryError::InvalidNumber {
+        value: value.to_string(),
+        position,
+    })?;
+
+    Ok((comparison, number))
+}
+
+pub fn parse(query: &str) -> Result<ParsedQuery, QueryError> {
+    let mut parser = Parser {
+        chars: query.chars().enumerate().peekable(),
+    };
+    let mut parsed = ParsedQuery::default();
+
+    loop {
+        parser.skip_whitespace();
+        let start = parser.position();
+        let Some(c) = parser.peek() else {
+            break;
+        };
+
+        let excluded = c == '-';
+        if excluded {
+            parser.chars.next();
+            if parser.peek().is_none_or(char::is_whitespace) {
+                continue;
+            }
+        }
+
+        if parser.peek() == Some('@') {
+            parser.chars.next();
+            let nickname = parser.value()?;
+            if nickname.is_empty() {
+                return Err(QueryError::EmptyValue {
+                    field: "@".to_string(),
+                    position: start,
+                });
+            }
+
+            if excluded {
+                parsed.excluded_authors.push(nickname);
+            } else {
+                set_once(&mut parsed.author, nickname, "@", start)?;
+            }
+            continue;
+        }
+
+        if parser.peek() == Some('"') {
+            let text = parser.value()?;
+            if !text.trim().is_empty() {
+                parsed.terms.push(Term {
+                    text,
+                    phrase: true,
+                    excluded,
+                });
+            }
+            continue;
+        }
+
+        let name = parser.take_while(|c| c.is_ascii_alphabetic());
+        if !name.is_empty() && parser.peek() == Some(':') {
+            parser.chars.next();
+            let value_position = parser.position();
+            let value = parser.value()?;
+            if value.is_empty() {
+                return Err(QueryError::EmptyValue {
+                    field: name,
+                    position: start,
+                });
+            }
+
+            match name.as_str() {
+                "title" if excluded => parsed.excluded_titles.push(value),
+                "title" => set_once(&mut parsed.title, value, &name, start)?,
+                "after" | "before" | "fav" | "votes" if excluded => {
+                    return Err(QueryError::CannotExclude {
+                        field: name,
+                        position: start,
+                    })
+                }
+                "after" => {
+                    let date = parse_date(&value, value_position)?;
+                    set_once(&mut parsed.after, date, &name, start)?
+                }
+                "before" => {
+                    let date = parse_date(&value, value_position)?;
+                    set_once(&mut parsed.before, date, &name, start)?
+                }
+                "fav" => {
+                    let comparison = parse_comparison(&value, value_position)?;
+                    set_once(&mut parsed.favorites, comparison, &name, start)?
+                }
+                "votes" => {
+                    let comparison = parse_comparison(&value, value_position)?;
+                    set_once(&mut parsed.votes, comparison, &name, start)?
+                }
+                _ => {
+                    return Err(QueryError::UnknownField {
+                        field: name,
+                        position: start,
+                    })
+                }
+            }
+            continue;
+        }
+
+        // A quote inside a word would start a phrase in the text query.
+        let rest = parser.take_while(|c| !c.is_whitespace());
+        let text: String = format!("{name}{rest}").replace('"', "");
+        if !text.is_empty() {
+            parsed.terms.push(Term {
+                text,
+                phrase: false,
+                excluded,
+            });
+        }
+    }
+
+    if parsed == ParsedQuery::default() {
+        return Err(QueryError::Empty);
+    }
+
+    Ok(parsed)
+}
+
+fn compare(column: EntryColumn, (comparison, value): (Comparison, i32)) -> SimpleExpr {
+    match comparison {
+        Comparison::Eq => column.eq(value),
+        Comparison::Gt => column.gt(value),
+        Comparison::Gte => column.gte(value),
+        Comparison::Lt => column.lt(value),
+        Comparison::Lte => column.lte(value),
+    }
+}
+
+impl ParsedQuery {
+    /// The words and phrases in the syntax of `websearch_to_tsquery`, or
+    /// `None` when the query only has filters.
+    pub fn text_query(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| {
+                let text = if term.phrase {
+                    format!("\"{}\"", term.text)
+                } else {
+                    term.text.clone()
+                };
+
+                if term.excluded {
+                    format!("-{text}")
+                } else {
+                    text
+                }
+            })
+            .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(fold_turkish(&parts.join(" ")))
+        }
+    }
+
+    /// Compiles the query to a condition over entries joined with their
+    /// titles and authors. Dates are days in `tz`.
+    pub fn condition(&self, tz: Tz) -> Condition {
+        let mut condition = Condition::all();
+
+        if let Some(text) = self.text_query() {
+            condition = condition.add(Expr::cust_with_values(
+                r#""entry"."search_vector" @@ websearch_to_tsquery('turkish', $1)"#,
+                [text],
+            ));
+        }
+        if let Some(author) = &self.author {
+            condition = condition.add(UserColumn::Nickname.eq(author));
+        }
+        if !self.excluded_authors.is_empty() {
+            condition = condition.add(UserColumn::Nickname.is_not_in(&self.excluded_authors));
+        }
+        if let Some(title) = &self.title {
+            condition = condition.add(TitleColumn::Name.eq(title));
+        }
+        if !self.excluded_titles.is_empty() {
+            condition = condition.add(TitleColumn::Name.is_not_in(&self.excluded_titles));
+        }
+        if let Some(after) = self.after.and_then(|after| after.succ_opt()) {
+            condition = condition.add(EntryColumn::CreatedAt.gte(time::day_start(after, tz)));
+        }
+        if let Some(before) = self.before {
+            condition = condition.add(EntryColumn::CreatedAt.lt(time::day_start(before, tz)));
+        }
+        if let Some(favorites) = self.favorites {
+            condition = condition.add(compare(EntryColumn::TotalFavorites, favorites));
+        }
+        if let Some(votes) = self.votes {
+            condition = condition.add(compare(EntryColumn::NetVotes, votes));
+        }
+
+        condition
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str) -> Term {
+        Term {
+            text: text.to_string(),
+            phrase: false,
+            excluded: false,
+        }
+    }
+
+    fn phrase(text: &str) -> Term {
+        Term {
+            phrase: true,
+            ..word(text)
+        }
+    }
+
+    fn excluded(term: Term) -> Term {
+        Term {
+            excluded: true,
+            ..term
+        }
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    /// Returns the `WHERE` clause the query compiles to.
+    fn sql(query: &str, tz: Tz) -> String {
+        let sql = Entry::find()
+            .filter(parse(query).unwrap().condition(tz))
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        sql.split_once(" WHERE ").unwrap().1.to_string()
+    }
+
+    #[test]
+    fn test_parse_full_example() {
+        assert_eq!(
+            parse(r#"@ssg "tam ifade" -hariç title:film after:2024-01-01 fav:>10"#),
+            Ok(ParsedQuery {
+                terms: vec![phrase("tam ifade"), excluded(word("hariç"))],
+                author: Some("ssg".to_string()),
+                title: Some("film".to_string()),
+                after: Some(date("2024-01-01")),
+                favorites: Some((Comparison::Gt, 10)),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_words() {
+        assert_eq!(
+            parse("  güzel   kitap or dergi "),
+            Ok(ParsedQuery {
+                terms: vec![word("güzel"), word("kitap"), word("or"), word("dergi")],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_excluded_phrases_and_filters() {
+        assert_eq!(
+            parse(r#"-"kötü film" -@troll -title:spoiler -title:"uzun başlık" film"#),
+            Ok(ParsedQuery {
+                terms: vec![excluded(phrase("kötü film")), word("film")],
+                excluded_authors: vec!["troll".to_string()],
+                excluded_titles: vec!["spoiler".to_string(), "uzun başlık".to_string()],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_title() {
+        assert_eq!(
+            parse(r#"title:"yüzüklerin efendisi""#).unwrap().title,
+            Some("yüzüklerin efendisi".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_comparisons() {
+        for (value, expected) in [
+            ("10", (Comparison::Eq, 10)),
+            ("=10", (Comparison::Eq, 10)),
+            (">10", (Comparison::Gt, 10)),
+            (">=10", (Comparison::Gte, 10)),
+            ("<-2", (Comparison::Lt, -2)),
+            ("<=0", (Comparison::Lte, 0)),
+        ] {
+            let parsed = parse(&format!("fav:{value} votes:{value}")).unwrap();
+
+            assert_eq!(parsed.favorites, Some(expected), "fav:{value}");
+            assert_eq!(parsed.votes, Some(expected), "votes:{value}");
+        }
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let parsed = parse("after:2024-01-01 before:2024-02-01").unwrap();
+
+        assert_eq!(parsed.after, Some(date("2024-01-01")));
+        assert_eq!(parsed.before, Some(date("2024-02-01")));
+    }
+
+    #[test]
+    fn test_parse_keeps_other_words_with_colons_and_dashes() {
+        assert_eq!(
+            parse(r#"saat 12:30 çay-kahve wo"rd -"#).unwrap().terms,
+            vec![word("saat"), word("12:30"), word("çay-kahve"), word("word")]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (query, error) in [
+            ("", QueryError::Empty),
+            ("  - ", QueryError::Empty),
+            (r#""" " ""#, QueryError::Empty),
+            (r#"""#, QueryError::UnclosedQuote { position: 1 }),
+            (
+                r#"kitap "tam ifade"#,
+                QueryError::UnclosedQuote { position: 7 },
+            ),
+            (r#"title:"film"#, QueryError::UnclosedQuote { position: 7 }),
+            (
+                "kitap @",
+                QueryError::EmptyValue {
+                    field: "@".to_string(),
+                    position: 7,
+                },
+            ),
+            (
+                "title: film",
+                QueryError::EmptyValue {
+                    field: "title".to_string(),
+                    position: 1,
+                },
+            ),
+            (
+                r#"title:"""#,
+                QueryError::EmptyValue {
+                    field: "title".to_string(),
+                    position: 1,
+                },
+            ),
+            (
+                "yazar:ssg",
+                QueryError::UnknownField {
+                    field: "yazar".to_string(),
+                    position: 1,
+                },
+            ),
+            (
+                "@a @b",
+                QueryError::DuplicateField {
+                    field: "@".to_string(),
+                    position: 4,
+                },
+            ),
+            (
+                "fav:1 fav:2",
+                QueryError::DuplicateField {
+                    field: "fav".to_string(),
+                    position: 7,
+                },
+            ),
+            (
+                "-after:2024-01-01",
+                QueryError::CannotExclude {
+                    field: "after".to_string(),
+                    position: 1,
+                },
+            ),
+            (
+                "after:2024-13-01",
+                QueryError::InvalidDate {
+                    value: "2024-13-01".to_string(),
+                    position: 7,
+                },
+            ),
+            (
+                "film fav:>on",
+                QueryError::InvalidNumber {
+                    value: ">on".to_string(),
+                    position: 10,
+                },
+            ),
+        ] {
+            assert_eq!(parse(query), Err(error), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_errors_are_invalid_requests() {
+        let error: crate::Error = parse("kitap @").unwrap_err().into();
+
+        assert!(matches!(
+            error,
+            crate::Error::InvalidRequest(message) if message == "7. karakterdeki `@` için bir değer girilmeli."
+        ));
+    }
+
+    #[test]
+    fn test_text_query() {
+        let parsed = parse(r#"Işık "İyi gün" -kötü -"çok kötü" @ssg"#).unwrap();
+
+        assert_eq!(
+            parsed.text_query(),
+            Some(r#"ışık "iyi gün" -kötü -"çok kötü""#.to_string())
+        );
+        assert_eq!(parse("@ssg fav:>1").unwrap().text_query(), None);
+    }
+
+    #[test]
+    fn test_condition_of_full_example() {
+        assert_eq!(
+            sql(
+                r#"@ssg "tam ifade" -hariç title:film after:2024-01-01 fav:>10"#,
+                Tz::UTC
+            ),
+            [
+                r#"("entry"."search_vector" @@ websearch_to_tsquery('turkish', E'\"tam ifade\" -hariç'))"#,
+                r#" AND "user"."nickname" = 'ssg'"#,
+                r#" AND "title"."name" = 'film'"#,
+                r#" AND "entry"."created_at" >= '2024-01-02 00:00:00'"#,
+                r#" AND "entry"."total_favorites" > 10"#,
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_condition_of_exclusions_and_ranges() {
+        assert_eq!(
+            sql("-@a -@b -title:x before:2024-03-01 votes:<=-1", Tz::UTC),
+            [
+                r#""user"."nickname" NOT IN ('a', 'b')"#,
+                r#" AND "title"."name" NOT IN ('x')"#,
+                r#" AND "entry"."created_at" < '2024-03-01 00:00:00'"#,
+                r#" AND "entry"."net_votes" <= -1"#,
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_condition_counts_days_in_timezone() {
+        assert_eq!(
+            sql("after:2024-01-01", crate::time::DEFAULT_TIMEZONE),
+            r#""entry"."created_at" >= '2024-01-01 21:00:00'"#
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Finds titles by a part of their name, forgiving typos and Turkish letters
+//! typed without their marks. Names are compared in the folded form kept in
+//! `title.name_folded`, by prefix and by trigram similarity.
+
+use ::entity::prelude::*;
+use sea_orm::*;
+use sea_query::{Expr, Query, SimpleExpr};
+use validator::Validate;
+
+use crate::{
+    dto::search::{DidYouMeanQuery, TitleAutocompleteQuery, TitleSuggestionDto},
+    Error, Result,
+};
+
+const DEFAULT_LIMIT: u8 = 10;
+
+/// How many titles are scored for one autocompletion.
+const CANDIDATES: u64 = 50;
+
+/// How similar a title must be to be suggested instead of a new one.
+const DID_YOU_MEAN_SIMILARITY: f32 = 0.5;
+
+const DID_YOU_MEAN_LIMIT: u64 = 5;
+
+/// Folds a name the way the `title_search` migration does.
+pub fn fold_title(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'Ç' | 'ç' => 'c',
+            'Ğ' | 'ğ' => 'g',
+            'İ' | 'I' | 'ı' | 'Î' | 'î' => 'i',
+            'Ö' | 'ö' => 'o',
+            'Ş' | 'ş' => 's',
+            'Ü' | 'ü' | 'Û' | 'û' => 'u',
+            'Â' | 'â' => 'a',
+            c => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Escapes the wildcards of `LIKE`.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Ranks a candidate: the trigram similarity of the names, a bonus when the
+/// name starts with the query, and a little for every entry in the title, so
+/// that busy titles come before forgotten ones of the same name.
+fn score(similarity: f32, is_prefix: bool, entry_count: i64) -> f32 {
+    let prefix = if is_prefix { 0.5 } else { 0.0 };
+    let activity = 0.05 * (1.0 + entry_count.max(0) as f32).ln();
+
+    similarity + prefix + activity
+}
+
+fn similarity(folded: &str) -> SimpleExpr {
+    Expr::cust_with_values(
+        r#"public.similarity("title"."name_folded", $1)"#,
+        [folded.to_string()],
+    )
+}
+
+fn is_similar(folded: &str) -> SimpleExpr {
+    Expr::cust_with_values(
+        r#""title"."name_folded" OPERATOR(public.%) $1"#,
+        [folded.to_string()],
+    )
+}
+
+fn entry_count() -> SimpleExpr {
+    SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            Query::select()
+                .expr(Expr::col((Entry, EntryColumn::Id)).count())
+                .from(Entry)
+                .and_where(
+                    Expr::col((Entry, EntryColumn::TitleId)).equals((Title, TitleColumn::Id)),
+                )
+                .and_where(Expr::col((Entry, EntryColumn::DeletedAt)).is_null())
+                .to_owned()
+                .into_sub_query_statement(),
+        ),
+    )
+}
+
+/// Suggests visible titles for a partly typed name, best first.
+pub async fn autocomplete(
+    db: &DbConn,
+    query: TitleAutocompleteQuery,
+) -> Result<Vec<TitleSuggestionDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let folded = fold_title(query.query.trim());
+    if folded.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let is_prefix = Expr::cust_with_values(
+        r#""title"."name_folded" LIKE $1"#,
+        [format!("{}%", escape_like(&folded))],
+    );
+
+    let candidates: Vec<(i32, String, f32, bool, i64)> = Title::find()
+        .select_only()
+        .column(TitleColumn::Id)
+        .column(TitleColumn::Name)
+        .expr(similarity(&folded))
+        .expr(is_prefix.clone())
+        .expr(entry_count())
+        .filter(TitleColumn::IsVisible.eq(true))
+        .filter(
+            Condition::any()
+                .add(is_prefix.clone())
+                .add(is_similar(&folded)),
+        )
+        .order_by(is_prefix, Order::Desc)
+        .order_by(similarity(&folded), Order::Desc)
+        .order_by_desc(TitleColumn::LastEntryAt)
+        .limit(CANDIDATES)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Başlıklar aranamadı.".to_string()))?;
+
+    let mut suggestions: Vec<TitleSuggestionDto> = candidates
+        .into_iter()
+        .map(
+            |(id, name, similarity, is_prefix, entry_count)| TitleSuggestionDto {
+                id,
+                name,
+                entry_count,
+                score: score(similarity, is_prefix, entry_count),
+            },
+        )
+        .collect();
+
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    suggestions.truncate(usize::from(query.limit.unwrap_or(DEFAULT_LIMIT)));
+
+    Ok(suggestions)
+}
+
+/// Lists the visible titles whose names are close to `name` but not the
+/// same, to be offered before a new title is opened.
+pub async fn did_you_mean(
+    db: &impl ConnectionTrait,
+    query: DidYouMeanQuery,
+) -> Result<Vec<TitleSuggestionDto>> {
+    query.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let folded = fold_title(query.name.trim());
+
+    let similar: Vec<(i32, String, f32, i64)> = Title::find()
+        .select_only()
+        .column(TitleColumn::Id)
+        .column(TitleColumn::Name)
+        .expr(similarity(&folded))
+        .expr(entry_count())
+        .filter(TitleColumn::IsVisible.eq(true))
+        .filter(TitleColumn::Name.ne(query.name.as_str()))
+        .filter(
+            Condition::any()
+                .add(Expr::cust_with_values(
+                    r#""title"."name_folded" = $1"#,
+                    [folded.clone()],
+                ))
+                .add(
+                    Condition::all()
+                        .add(is_similar(&folded))
+                        .add(Expr::expr(similarity(&folded)).gte(DID_YOU_MEAN_SIMILARITY)),
+                ),
+        )
+        .order_by(similarity(&folded), Order::Desc)
+        .order_by_desc(TitleColumn::LastEntryAt)
+        .limit(DID_YOU_MEAN_LIMIT)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Başlıklar aranamadı.".to_string()))?;
+
+    Ok(similar
+        .into_iter()
+        .map(|(id, name, similarity, entry_count)| TitleSuggestionDto {
+            id,
+            name,
+            entry_count,
+            score: score(similarity, false, entry_count),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_title() {
+        assert_eq!(fold_title("Soğan"), "sogan");
+        assert_eq!(fold_title("IŞIK İSTANBUL"), "isik istanbul");
+        assert_eq!(fold_title("çğıöşü ÇĞİÖŞÜ"), "cgiosu cgiosu");
+        assert_eq!(fold_title("Kâğıt Hâlâ Rûh"), "kagit hala ruh");
+        assert_eq!(fold_title("sogan"), fold_title("SOĞAN"));
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("100%_a\\b"), "100\\%\\_a\\\\b");
+        assert_eq!(escape_like("sogan"), "sogan");
+    }
+
+    #[test]
+    fn test_prefix_matches_rank_first() {
+        assert!(score(0.3, true, 0) > score(0.6, false, 0));
+    }
+
+    #[test]
+    fn test_activity_breaks_ties() {
+        assert!(score(0.5, false, 100) > score(0.5, false, 1));
+        assert_eq!(score(0.5, false, 0), 0.5);
+        // A busy title doesn't outrank a much closer one.
+        assert!(score(0.9, false, 0) > score(0.5, false, 1000));
+    }
+}
@@ -0,0 +1,102 @@
+//! Silenced users can still read and manage their own entries, but can't
+//! write, edit, vote or favorite until their silence ends.
+
+use ::entity::prelude::*;
+use sea_orm::*;
+use validator::Validate;
+
+use crate::{
+    dto::silence::SilenceUserRequest,
+    time::{today, Tz},
+    Error, Result,
+};
+
+/// Silences `user_id` until the end of `request.until`, replacing an earlier
+/// silence.
+pub async fn silence_user(
+    db: &DbConn,
+    tz: Tz,
+    user_id: i32,
+    request: SilenceUserRequest,
+) -> Result<()> {
+    request.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    if request.until < today(tz) {
+        return Err(Error::InvalidRequest(
+            "Susturma bitişi geçmiş bir gün olamaz.".to_string(),
+        ));
+    }
+
+    User::find()
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı susturulamadı.".to_string()))?;
+
+    SilencedUser::delete_many()
+        .filter(SilencedUserColumn::UserId.eq(user_id))
+        .exec(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı susturulamadı.".to_string()))?;
+
+    SilencedUserActiveModel {
+        user_id: Set(user_id),
+        reason: Set(request.reason.trim().to_string()),
+        end_date: Set(request.until),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|_| Error::InternalError("Kullanıcı susturulamadı.".to_string()))?;
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı susturulamadı.".to_string()))
+}
+
+/// Ends the silence of `user_id` early.
+pub async fn unsilence_user(db: &DbConn, user_id: i32) -> Result<()> {
+    let result = SilencedUser::delete_many()
+        .filter(SilencedUserColumn::UserId.eq(user_id))
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Susturma kaldırılamadı.".to_string()))?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound("Kullanıcı susturulmamış.".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Fails with the reason when `user_id` is silenced today.
+pub(crate) async fn check_not_silenced(
+    db: &impl ConnectionTrait,
+    tz: Tz,
+    user_id: i32,
+) -> Result<()> {
+    let silence = SilencedUser::find()
+        .filter(SilencedUserColumn::UserId.eq(user_id))
+        .filter(SilencedUserColumn::EndDate.gte(today(tz)))
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))?;
+
+    match silence {
+        Some(silence) => Err(Error::Forbidden(format!(
+            "{} tarihine kadar susturuldunuz: {}",
+            silence.end_date.format("%d.%m.%Y"),
+            silence.reason
+        ))),
+        None => Ok(()),
+    }
+}
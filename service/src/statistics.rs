@@ -1,9 +1,16 @@
 use ::entity::prelude::*;
 use sea_orm::*;
 
-use crate::{dto::statistics::StatisticsResponse, Error, Result};
+use crate::{
+    dto::statistics::StatisticsResponse,
+    time::{self, Tz},
+    Error, Result,
+};
+
+/// Counts everything, and what was added since midnight in `tz`.
+pub async fn statistics(db: &DbConn, tz: Tz) -> Result<StatisticsResponse> {
+    let last_midnight = time::day_start(time::today(tz), tz);
 
-pub async fn statistics(db: &DbConn) -> Result<StatisticsResponse> {
     let title_count = Title::find()
         .count(db)
         .await
@@ -16,20 +23,31 @@ pub async fn statistics(db: &DbConn) -> Result<StatisticsResponse> {
         .count(db)
         .await
         .map_err(|_| Error::InternalError("Kullanıcı sayısı getirilemedi.".to_string()))?;
-    let crew_count = User::find()
-        .filter(
-            UserColumn::IsModerator
-                .eq(true)
-                .or(UserColumn::IsAdmin.eq(true)),
-        )
+    let crew_count = UserRole::find()
+        .select_only()
+        .column(UserRoleColumn::UserId)
+        .distinct()
         .count(db)
         .await
         .map_err(|_| Error::InternalError("Ekip sayısı getirilemedi.".to_string()))?;
 
+    let today_entry_count = Entry::find()
+        .filter(EntryColumn::CreatedAt.gte(last_midnight))
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Girdi sayısı getirilemedi.".to_string()))?;
+    let today_user_count = User::find()
+        .filter(UserColumn::CreatedAt.gte(last_midnight))
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı sayısı getirilemedi.".to_string()))?;
+
     Ok(StatisticsResponse {
         title_count,
         entry_count,
         user_count,
         crew_count,
+        today_entry_count,
+        today_user_count,
     })
 }
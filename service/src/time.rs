@@ -0,0 +1,89 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone};
+
+pub use chrono_tz::Tz;
+
+/// The timezone days are counted in when none is configured.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Istanbul;
+
+/// Returns the current date in `tz`.
+pub fn today(tz: Tz) -> NaiveDate {
+    chrono::Utc::now().with_timezone(&tz).date_naive()
+}
+
+/// Returns the UTC time at which `date` starts in `tz`. When midnight is
+/// skipped by a clock change, the day starts at the first hour after it.
+pub fn day_start(date: NaiveDate, tz: Tz) -> NaiveDateTime {
+    (0..24)
+        .find_map(|hour| {
+            tz.from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(hour, 0, 0)?))
+                .earliest()
+        })
+        .map(|start| start.naive_utc())
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN))
+}
+
+/// Returns the UTC times at which `date` starts and ends in `tz`.
+pub fn day_bounds(date: NaiveDate, tz: Tz) -> (NaiveDateTime, NaiveDateTime) {
+    let next = date.succ_opt().unwrap_or(date);
+
+    (day_start(date, tz), day_start(next, tz))
+}
+
+/// Formats a UTC time from the database as RFC 3339 with an offset.
+pub fn timestamp(time: NaiveDateTime) -> String {
+    time.and_utc().to_rfc3339_opts(SecondsFormat::AutoSi, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn time(date: NaiveDate, hour: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_istanbul_day_starts_at_21_utc() {
+        let (start, end) = day_bounds(date(2026, 10, 19), DEFAULT_TIMEZONE);
+
+        assert_eq!(start, time(date(2026, 10, 18), 21));
+        assert_eq!(end, time(date(2026, 10, 19), 21));
+    }
+
+    #[test]
+    fn test_utc_day_starts_at_midnight() {
+        let (start, end) = day_bounds(date(2026, 10, 19), Tz::UTC);
+
+        assert_eq!(start, time(date(2026, 10, 19), 0));
+        assert_eq!(end, time(date(2026, 10, 20), 0));
+    }
+
+    #[test]
+    fn test_days_around_clock_changes() {
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+
+        // Clocks went forward at 02:00 on 2026-03-29, making the day 23 hours.
+        let (start, end) = day_bounds(date(2026, 3, 29), tz);
+        assert_eq!(end - start, chrono::Duration::hours(23));
+
+        // Midnight was skipped in São Paulo on 2018-11-04.
+        let tz: Tz = "America/Sao_Paulo".parse().unwrap();
+        assert_eq!(day_start(date(2018, 11, 4), tz), time(date(2018, 11, 4), 3));
+    }
+
+    #[test]
+    fn test_timestamp_is_rfc3339_with_offset() {
+        let at = date(2026, 10, 19).and_hms_micro_opt(7, 5, 3, 120).unwrap();
+
+        assert_eq!(
+            timestamp(time(date(2026, 10, 19), 7)),
+            "2026-10-19T07:00:00+00:00"
+        );
+        assert_eq!(timestamp(at), "2026-10-19T07:05:03.000120+00:00");
+        assert!(chrono::DateTime::parse_from_rfc3339(&timestamp(at)).is_ok());
+    }
+}
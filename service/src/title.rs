@@ -1,34 +1,52 @@
-use crate::{dto::title::TitleDto, Error, Result};
+use crate::{
+    dto::title::TitleDto,
+    search::backend::{update_title, SearchBackend},
+    Error, Result,
+};
 use ::entity::prelude::*;
 use sea_orm::*;
+use sea_query::{Expr, OnConflict};
 
-pub async fn title_id_by_name(db: &DbConn, name: &str) -> Option<i32> {
-    // TODO: Do not use variable for title_id
-    let title_id: Option<i32> = Title::find()
-        .filter(TitleColumn::Name.eq(name))
-        .filter(TitleColumn::IsVisible.eq(true))
-        .select_only()
-        .column(TitleColumn::Id)
-        .into_tuple()
-        .one(db)
-        .await
-        .ok()?;
-
-    title_id
-}
-
-pub async fn create_title(db: &DbConn, name: &str) -> Result<TitleActiveModel> {
-    TitleActiveModel {
+/// Creates the title, or moves its `last_entry_at` to now if it already
+/// exists. Safe to run concurrently for the same name.
+pub async fn upsert_title(db: &impl ConnectionTrait, name: &str) -> Result<TitleModel> {
+    Title::insert(TitleActiveModel {
         name: Set(name.to_string()),
+        last_entry_at: Set(chrono::Utc::now().naive_utc()),
         is_visible: Set(true),
         ..Default::default()
-    }
-    .save(db)
+    })
+    .on_conflict(
+        OnConflict::column(TitleColumn::Name)
+            .update_column(TitleColumn::LastEntryAt)
+            .to_owned(),
+    )
+    .exec_with_returning(db)
     .await
     .map_err(|_| Error::InternalError("Başlık oluşturulamadı.".to_string()))
 }
 
-pub async fn set_title_visibility(db: &DbConn, id: i32, is_visible: bool) -> Result<TitleDto> {
+/// Moves the `last_entry_at` of an existing title to now. Returns `None` if
+/// there is no title with that name.
+pub async fn touch_title(db: &impl ConnectionTrait, name: &str) -> Result<Option<TitleModel>> {
+    Title::update_many()
+        .col_expr(
+            TitleColumn::LastEntryAt,
+            Expr::value(chrono::Utc::now().naive_utc()),
+        )
+        .filter(TitleColumn::Name.eq(name))
+        .exec_with_returning(db)
+        .await
+        .map(|titles| titles.into_iter().next())
+        .map_err(|_| Error::InternalError("Başlık güncellenemedi.".to_string()))
+}
+
+pub async fn set_title_visibility(
+    db: &DbConn,
+    search: &dyn SearchBackend,
+    id: i32,
+    is_visible: bool,
+) -> Result<TitleDto> {
     let mut title = Title::find()
         .filter(TitleColumn::Id.eq(id))
         .one(db)
@@ -45,5 +63,9 @@ pub async fn set_title_visibility(db: &DbConn, id: i32, is_visible: bool) -> Res
         .await
         .map_err(|_| Error::InternalError("Başlık güncellenemedi.".to_string()))?;
 
+    if let Err(err) = update_title(search, db, id).await {
+        tracing::warn!("Could not update the search index for title {id}: {err}");
+    }
+
     Ok(TitleDto::from(title))
 }
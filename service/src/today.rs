@@ -4,29 +4,31 @@ use validator::Validate;
 
 use crate::{
     dto::{
-        pagination::{PaginationQuery, PaginationResponse},
-        today::TodayTitleDto,
+        pagination::PaginationResponse,
+        today::{TodayQuery, TodayTitleDto},
     },
+    time::{self, Tz},
     Error, Result,
 };
 
+/// Lists the titles with entries written since midnight in `query.tz`, or in
+/// `tz` when the query has none.
 pub async fn today(
     db: &DbConn,
-    query: PaginationQuery,
+    tz: Tz,
+    query: TodayQuery,
 ) -> Result<PaginationResponse<TodayTitleDto>> {
     query.validate().map_err(|_| {
         Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
     })?;
 
-    let last_midnight = chrono::Utc::now()
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap();
+    let tz = query.tz.unwrap_or(tz);
+    let last_midnight = time::day_start(time::today(tz), tz);
 
     let base_query = Title::find()
         .filter(TitleColumn::IsVisible.eq(true))
         .inner_join(Entry)
-        .filter(EntryColumn::CreatedAt.gt(last_midnight))
+        .filter(EntryColumn::CreatedAt.gte(last_midnight))
         .filter(EntryColumn::DeletedAt.is_null())
         .group_by(TitleColumn::Id)
         .order_by_desc(TitleColumn::LastEntryAt);
@@ -34,7 +36,7 @@ pub async fn today(
     let title_pages = base_query.clone().paginate(db, query.per_page.into());
 
     let titles = title_pages
-        .fetch_page(query.page as u64 - 1)
+        .fetch_page(u64::from(query.page - 1))
         .await
         .map_err(|_| Error::InternalError("Başlıklar getirilemedi.".to_string()))?;
 
@@ -42,7 +44,7 @@ pub async fn today(
         let entry_count = Entry::find()
             .filter(EntryColumn::TitleId.eq(title.id))
             .filter(EntryColumn::DeletedAt.is_null())
-            .filter(EntryColumn::CreatedAt.gt(last_midnight))
+            .filter(EntryColumn::CreatedAt.gte(last_midnight))
             .count(db)
             .await;
 
@@ -71,9 +73,11 @@ pub async fn today(
         .map_err(|_| Error::InternalError("Başlık sayısı getirilemedi.".to_string()))?;
 
     Ok(PaginationResponse {
-        total,
+        total: Some(total),
         per_page: query.per_page,
-        page: query.page,
+        page: Some(query.page),
         items: today_dtos,
+        next: None,
+        prev: None,
     })
 }
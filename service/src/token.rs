@@ -1,3 +1,4 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -5,13 +6,26 @@ pub struct UserClaims {
     pub id: i32,
     pub nickname: String,
     pub email: String,
-    pub is_admin: bool,
-    pub is_moderator: bool,
     pub is_faded: bool,
+    /// Whether the session passed a second factor check on login.
+    #[serde(default)]
+    pub two_factor: bool,
     pub iat: i64,
     pub exp: i64,
 }
 
+/// Generates a random hex encoded secret for one-time links and codes.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn hash_token(token: &str) -> String {
+    blake3::hash(token.as_bytes()).to_hex().to_string()
+}
+
 pub fn validate_token(token: &str, key: &str) -> bool {
     let key = jsonwebtoken::DecodingKey::from_secret(key.as_bytes());
     let validation = jsonwebtoken::Validation::default();
@@ -24,9 +38,8 @@ pub enum TokenField {
     Id,
     Nickname,
     Email,
-    IsAdmin,
-    IsModerator,
     IsAuthor,
+    TwoFactor,
     Iat,
     Exp,
 }
@@ -41,9 +54,8 @@ pub fn get_field_from_token(token: &str, field: TokenField, key: &str) -> Option
             TokenField::Id => Some(token.claims.id.to_string()),
             TokenField::Nickname => Some(token.claims.nickname),
             TokenField::Email => Some(token.claims.email),
-            TokenField::IsAdmin => Some(token.claims.is_admin.to_string()),
-            TokenField::IsModerator => Some(token.claims.is_moderator.to_string()),
             TokenField::IsAuthor => Some(token.claims.is_faded.to_string()),
+            TokenField::TwoFactor => Some(token.claims.two_factor.to_string()),
             TokenField::Iat => Some(token.claims.iat.to_string()),
             TokenField::Exp => Some(token.claims.exp.to_string()),
         },
@@ -60,29 +72,20 @@ pub fn get_id(token: &str, key: &str) -> Option<i32> {
     }
 }
 
-pub fn is_admin(token: &str, key: &str) -> bool {
-    let is_admin = get_field_from_token(token, TokenField::IsAdmin, key);
-
-    match is_admin {
-        Some(is_admin) => is_admin == "true",
-        None => false,
-    }
-}
-
-pub fn is_moderator(token: &str, key: &str) -> bool {
-    let is_moderator = get_field_from_token(token, TokenField::IsModerator, key);
+pub fn is_author(token: &str, key: &str) -> bool {
+    let is_author = get_field_from_token(token, TokenField::IsAuthor, key);
 
-    match is_moderator {
-        Some(is_moderator) => is_moderator == "true",
+    match is_author {
+        Some(is_author) => is_author == "true",
         None => false,
     }
 }
 
-pub fn is_author(token: &str, key: &str) -> bool {
-    let is_author = get_field_from_token(token, TokenField::IsAuthor, key);
+pub fn has_two_factor(token: &str, key: &str) -> bool {
+    let two_factor = get_field_from_token(token, TokenField::TwoFactor, key);
 
-    match is_author {
-        Some(is_author) => is_author == "true",
+    match two_factor {
+        Some(two_factor) => two_factor == "true",
         None => false,
     }
 }
@@ -98,9 +101,8 @@ mod tests {
             id: 1,
             nickname: "test".to_string(),
             email: "john.doe@example.com".to_string(),
-            is_admin: false,
-            is_moderator: false,
             is_faded: false,
+            two_factor: false,
             iat: chrono::Utc::now().timestamp(),
             exp: chrono::Utc::now().timestamp() + 60 * 60 * 24,
         };
@@ -122,9 +124,8 @@ mod tests {
             id: 1,
             nickname: "test".to_string(),
             email: "john.doe@example.com".to_string(),
-            is_admin: false,
-            is_moderator: false,
             is_faded: false,
+            two_factor: false,
             iat: chrono::Utc::now().timestamp(),
             exp: chrono::Utc::now().timestamp() + 60 * 60 * 24,
         };
@@ -146,9 +147,8 @@ mod tests {
             id: 1,
             nickname: "test".to_string(),
             email: "jane.doe@example.com".to_string(),
-            is_admin: false,
-            is_moderator: true,
             is_faded: true,
+            two_factor: true,
             iat: chrono::Utc::now().timestamp(),
             exp: chrono::Utc::now().timestamp() + 60 * 60 * 24,
         };
@@ -172,18 +172,14 @@ mod tests {
             get_field_from_token(&token, TokenField::Email, key),
             Some(claims.email)
         );
-        assert_eq!(
-            get_field_from_token(&token, TokenField::IsAdmin, key),
-            Some(claims.is_admin.to_string())
-        );
-        assert_eq!(
-            get_field_from_token(&token, TokenField::IsModerator, key),
-            Some(claims.is_moderator.to_string())
-        );
         assert_eq!(
             get_field_from_token(&token, TokenField::IsAuthor, key),
             Some(claims.is_faded.to_string())
         );
+        assert_eq!(
+            get_field_from_token(&token, TokenField::TwoFactor, key),
+            Some(claims.two_factor.to_string())
+        );
         assert_eq!(
             get_field_from_token(&token, TokenField::Iat, key),
             Some(claims.iat.to_string())
@@ -200,18 +196,27 @@ mod tests {
     }
 
     #[test]
-    fn test_is_admin() {
+    fn test_generate_token() {
+        let token = generate_token();
+
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn test_has_two_factor_defaults_to_false() {
         let key = "test123";
-        let claims = UserClaims {
-            id: 1,
-            nickname: "test".to_string(),
-            email: "john.doe@example.com".to_string(),
-            is_admin: true,
-            is_moderator: false,
-            is_faded: false,
-            iat: chrono::Utc::now().timestamp(),
-            exp: chrono::Utc::now().timestamp() + 60 * 60 * 24,
-        };
+        let claims = serde_json::json!({
+            "id": 1,
+            "nickname": "test",
+            "email": "john.doe@example.com",
+            "is_admin": true,
+            "is_moderator": false,
+            "is_faded": false,
+            "iat": chrono::Utc::now().timestamp(),
+            "exp": chrono::Utc::now().timestamp() + 60 * 60 * 24,
+        });
 
         let token = jsonwebtoken::encode(
             &jsonwebtoken::Header::default(),
@@ -220,6 +225,7 @@ mod tests {
         )
         .unwrap();
 
-        assert!(is_admin(&token, key));
+        assert!(validate_token(&token, key));
+        assert!(!has_two_factor(&token, key));
     }
 }
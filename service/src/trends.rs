@@ -1,73 +1,370 @@
+use std::collections::HashMap;
+
 use ::entity::prelude::*;
+use chrono::{Duration, NaiveDateTime};
 use sea_orm::*;
-use sea_query::{Alias, Expr};
+use sea_query::{Alias, Expr, Query, SelectStatement, UnionType};
+use serde::Deserialize;
 use validator::Validate;
 
 use crate::{
     dto::{
-        pagination::{PaginationQuery, PaginationResponse},
-        trends::TrendTitleDto,
+        pagination::PaginationResponse,
+        trends::{TrendTitleDto, TrendsQuery},
     },
     Error, Result,
 };
 
+/// How much each kind of activity in a title adds to its trend score.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrendWeights {
+    /// Per entry written in the window.
+    pub entry: f64,
+    /// Per distinct author who wrote in the window.
+    pub author: f64,
+    /// Per up vote given in the window. Down votes take as much away.
+    pub vote: f64,
+    /// Per favorite added in the window.
+    pub favorite: f64,
+    /// The time after which activity counts half, as a share of the window.
+    pub half_life: f64,
+    /// Multiplies everything a faded author writes.
+    pub faded_author: f64,
+    /// Multiplies each further entry of the same author in the same title,
+    /// so that a burst of entries counts less than the same number of
+    /// authors.
+    pub repeat_entry: f64,
+}
+
+impl Default for TrendWeights {
+    fn default() -> Self {
+        Self {
+            entry: 1.0,
+            author: 2.0,
+            vote: 0.5,
+            favorite: 1.0,
+            half_life: 0.25,
+            faded_author: 0.25,
+            repeat_entry: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TrendRow {
+    title_id: i32,
+    score: f64,
+    entry_count: i64,
+    total: i64,
+}
+
+/// How much of the activity at `at` still counts at the time bound to
+/// `$now`: half as much after each half-life, in seconds bound to
+/// `$half_life`.
+fn decay(at: &str, now: usize, half_life: usize) -> String {
+    format!("power(0.5, greatest(extract(epoch from (${now} - {at})), 0) / ${half_life})")
+}
+
+/// The query that scores the titles with entries in the `window` before
+/// `now` and returns one page of them, with the number of such titles in
+/// every row.
+fn ranking(
+    weights: &TrendWeights,
+    now: NaiveDateTime,
+    window: Duration,
+    per_page: u8,
+    offset: u64,
+) -> SelectStatement {
+    let since = now - window;
+    let half_life = window.num_seconds() as f64 * weights.half_life;
+
+    let listed_entries = Entry::find()
+        .select_only()
+        .column(EntryColumn::Id)
+        .filter(EntryColumn::DeletedAt.is_null())
+        .inner_join(Title)
+        .filter(TitleColumn::IsVisible.eq(true))
+        .into_query();
+
+    // The latest entry of an author in a title counts in full and brings the
+    // author weight; each earlier one counts less, so that a burst of
+    // entries counts less than the same number of authors.
+    let repeats = concat!(
+        r#"row_number() OVER (PARTITION BY "entry"."title_id", "entry"."user_id" "#,
+        r#"ORDER BY "entry"."created_at" DESC)"#,
+    );
+    let mut activity = Entry::find()
+        .select_only()
+        .column(EntryColumn::TitleId)
+        .column_as(
+            Expr::cust_with_values(
+                format!(
+                    concat!(
+                        "($1 * power($2, {repeats} - 1) + CASE WHEN {repeats} = 1 THEN $3 ELSE 0 END)",
+                        r#" * CASE WHEN "user"."is_faded" THEN $4 ELSE 1 END * {decay}"#,
+                    ),
+                    repeats = repeats,
+                    decay = decay(r#""entry"."created_at""#, 5, 6),
+                ),
+                [
+                    weights.entry.into(),
+                    weights.repeat_entry.into(),
+                    weights.author.into(),
+                    weights.faded_author.into(),
+                    Value::from(now),
+                    half_life.into(),
+                ],
+            ),
+            "score",
+        )
+        .column_as(Expr::val(1), "entries")
+        .inner_join(User)
+        .filter(UserColumn::DeletedAt.is_null())
+        .filter(EntryColumn::CreatedAt.gt(since))
+        .filter(EntryColumn::Id.in_subquery(listed_entries.clone()))
+        .into_query();
+
+    // Down votes take as much away as up votes add.
+    let votes = Vote::find()
+        .select_only()
+        .column(EntryColumn::TitleId)
+        .column_as(
+            Expr::cust_with_values(
+                format!(
+                    r#"$1 * CASE WHEN "vote"."rating" = 'Up' THEN 1 ELSE -1 END * {}"#,
+                    decay(
+                        r#"coalesce("vote"."updated_at", "vote"."created_at")"#,
+                        2,
+                        3
+                    )
+                ),
+                [weights.vote.into(), Value::from(now), half_life.into()],
+            ),
+            "score",
+        )
+        .column_as(Expr::val(0), "entries")
+        .inner_join(Entry)
+        .filter(
+            Condition::any()
+                .add(VoteColumn::CreatedAt.gt(since))
+                .add(VoteColumn::UpdatedAt.gt(since)),
+        )
+        .filter(VoteColumn::EntryId.in_subquery(listed_entries.clone()))
+        .into_query();
+
+    let favorites = Favorite::find()
+        .select_only()
+        .column(EntryColumn::TitleId)
+        .column_as(
+            Expr::cust_with_values(
+                format!("$1 * {}", decay(r#""favorite"."created_at""#, 2, 3)),
+                [weights.favorite.into(), Value::from(now), half_life.into()],
+            ),
+            "score",
+        )
+        .column_as(Expr::val(0), "entries")
+        .inner_join(Entry)
+        .filter(FavoriteColumn::CreatedAt.gt(since))
+        .filter(FavoriteColumn::EntryId.in_subquery(listed_entries))
+        .into_query();
+
+    activity
+        .union(UnionType::All, votes)
+        .union(UnionType::All, favorites);
+
+    Query::select()
+        .column(Alias::new("title_id"))
+        .expr_as(Expr::cust(r#"sum("score")"#), Alias::new("score"))
+        .expr_as(Expr::cust(r#"sum("entries")"#), Alias::new("entry_count"))
+        .expr_as(Expr::cust("count(*) OVER ()"), Alias::new("total"))
+        .from_subquery(activity, Alias::new("activity"))
+        .group_by_col(Alias::new("title_id"))
+        .and_having(Expr::cust(r#"sum("entries") > 0"#))
+        .order_by(Alias::new("score"), Order::Desc)
+        .order_by(Alias::new("entry_count"), Order::Desc)
+        .order_by(Alias::new("title_id"), Order::Asc)
+        .limit(per_page.into())
+        .offset(offset)
+        .to_owned()
+}
+
+/// Ranks the titles that had entries in the window in the database, so that
+/// only one page of titles is read however busy the window was. Votes and
+/// favorites only add to those titles.
 pub async fn trends(
     db: &DbConn,
-    query: PaginationQuery,
+    weights: &TrendWeights,
+    query: TrendsQuery,
 ) -> Result<PaginationResponse<TrendTitleDto>> {
     query.validate().map_err(|_| {
         Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
     })?;
 
-    let two_days_ago = chrono::Utc::now()
-        .naive_utc()
-        .checked_sub_signed(chrono::Duration::days(2))
-        .unwrap();
+    let offset = u64::from(query.page - 1) * u64::from(query.per_page);
+    let ranked = ranking(
+        weights,
+        chrono::Utc::now().naive_utc(),
+        query.window.unwrap_or_default().duration(),
+        query.per_page,
+        offset,
+    );
 
-    let base_query = Title::find()
-        .filter(TitleColumn::IsVisible.eq(true))
+    let page = TrendRow::find_by_statement(db.get_database_backend().build(&ranked))
+        .all(db)
+        .await
+        .map_err(|_| Error::InternalError("Gündem getirilemedi.".to_string()))?;
+
+    // The total comes with the rows, so a page past the end needs a count of
+    // its own.
+    let total = match page.first() {
+        Some(row) => row.total as u64,
+        None if offset > 0 => {
+            let mut count = ranked.clone();
+            count.limit(1).offset(0);
+            TrendRow::find_by_statement(db.get_database_backend().build(&count))
+                .one(db)
+                .await
+                .map_err(|_| Error::InternalError("Gündem getirilemedi.".to_string()))?
+                .map_or(0, |row| row.total as u64)
+        }
+        None => 0,
+    };
+
+    let names: HashMap<i32, String> = Title::find()
         .select_only()
         .column(TitleColumn::Id)
         .column(TitleColumn::Name)
-        .column_as(EntryColumn::Id.count(), "entry_count")
-        .join_rev(
-            JoinType::InnerJoin,
-            Entry::belongs_to(Title)
-                .from(EntryColumn::TitleId)
-                .to(TitleColumn::Id)
-                .into(),
-        )
-        .filter(EntryColumn::DeletedAt.is_null())
-        .group_by(TitleColumn::Id)
-        .filter(EntryColumn::CreatedAt.gt(two_days_ago))
-        .order_by_desc(Expr::col(Alias::new("entry_count")))
-        .into_model::<TrendTitleDto>();
-
-    let title_pages = base_query.paginate(db, query.per_page.into());
-
-    let titles = title_pages
-        .fetch_page(query.page as u64 - 1)
+        .filter(TitleColumn::Id.is_in(page.iter().map(|row| row.title_id)))
+        .into_tuple()
+        .all(db)
         .await
-        .map_err(|_| Error::InternalError("Başlıklar getirilemedi.".to_string()))?;
+        .map_err(|_| Error::InternalError("Başlıklar getirilemedi.".to_string()))?
+        .into_iter()
+        .collect();
 
-    let trend_dtos = titles
+    let trend_dtos = page
         .into_iter()
-        .map(|title| TrendTitleDto {
-            id: title.id,
-            name: title.name,
-            entry_count: title.entry_count,
+        .filter_map(|row| {
+            Some(TrendTitleDto {
+                id: row.title_id,
+                name: names.get(&row.title_id)?.clone(),
+                entry_count: row.entry_count,
+                score: row.score,
+            })
         })
         .collect();
 
-    let total = title_pages
-        .num_items()
-        .await
-        .map_err(|_| Error::InternalError("Başlıklar sayılamadı.".to_string()))?;
-
     Ok(PaginationResponse {
-        total,
-        page: query.page,
+        total: Some(total),
+        page: Some(query.page),
         per_page: query.per_page,
         items: trend_dtos,
+        next: None,
+        prev: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decay() {
+        assert_eq!(
+            decay(r#""vote"."created_at""#, 2, 3),
+            r#"power(0.5, greatest(extract(epoch from ($2 - "vote"."created_at")), 0) / $3)"#
+        );
+    }
+
+    /// What the ranking query adds for an entry written just now: `repeat`
+    /// later entries of the same author in the same title make it count less.
+    fn entry_score(weights: &TrendWeights, repeat: i32, faded: bool) -> f64 {
+        let author = if repeat == 0 { weights.author } else { 0.0 };
+        let faded = if faded { weights.faded_author } else { 1.0 };
+
+        (weights.entry * weights.repeat_entry.powi(repeat) + author) * faded
+    }
+
+    #[test]
+    fn test_default_weights() {
+        let weights = TrendWeights::default();
+
+        // A burst of entries of one author counts less than many authors.
+        let burst: f64 = (0..3)
+            .map(|repeat| entry_score(&weights, repeat, false))
+            .sum();
+        let crowd = 3.0 * entry_score(&weights, 0, false);
+        assert!(burst < crowd, "{burst} >= {crowd}");
+
+        // Faded authors count less, but still count.
+        assert!(entry_score(&weights, 0, true) < entry_score(&weights, 0, false));
+        assert!(entry_score(&weights, 0, true) > 0.0);
+
+        // A single vote or favorite weighs less than an entry.
+        assert!(weights.vote < weights.entry);
+        assert!(weights.favorite <= weights.entry);
+
+        // Activity fades within the window, but isn't gone by its end.
+        assert!(weights.half_life > 0.0 && weights.half_life < 1.0);
+    }
+
+    #[test]
+    fn test_ranking_sql() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let sql = DbBackend::Postgres
+            .build(&ranking(
+                &TrendWeights::default(),
+                now,
+                Duration::hours(24),
+                10,
+                20,
+            ))
+            .to_string();
+
+        assert_eq!(
+            sql,
+            concat!(
+                r#"SELECT "title_id", sum("score")"#,
+                r#" AS "score", sum("entries") AS "entry_count", count(*) OVER () AS "total""#,
+                r#" FROM (SELECT "entry"."title_id""#,
+                r#", (1 * power(0.5, row_number() OVER (PARTITION BY "entry"."title_id", "entry"."user_id""#,
+                r#" ORDER BY "entry"."created_at" DESC) - 1)"#,
+                r#" + CASE WHEN row_number() OVER (PARTITION BY "entry"."title_id", "entry"."user_id""#,
+                r#" ORDER BY "entry"."created_at" DESC) = 1 THEN 2 ELSE 0 END)"#,
+                r#" * CASE WHEN "user"."is_faded" THEN 0.25 ELSE 1 END"#,
+                r#" * power(0.5, greatest(extract(epoch from ('2026-10-19 12:00:00' - "entry"."created_at")), 0) / 21600)"#,
+                r#" AS "score", 1 AS "entries" FROM "entry" INNER JOIN "user" ON "entry"."user_id" = "user"."id""#,
+                r#" WHERE "user"."deleted_at" IS NULL"#,
+                r#" AND "entry"."created_at" > '2026-10-18 12:00:00'"#,
+                r#" AND "entry"."id" IN (SELECT "entry"."id" FROM "entry""#,
+                r#" INNER JOIN "title" ON "entry"."title_id" = "title"."id""#,
+                r#" WHERE "entry"."deleted_at" IS NULL"#,
+                r#" AND "title"."is_visible" = TRUE)"#,
+                r#" UNION ALL (SELECT "entry"."title_id""#,
+                r#", 0.5 * CASE WHEN "vote"."rating" = 'Up' THEN 1 ELSE -1 END"#,
+                r#" * power(0.5, greatest(extract(epoch from ('2026-10-19 12:00:00' - coalesce("vote"."updated_at", "vote"."created_at"))), 0) / 21600)"#,
+                r#" AS "score", 0 AS "entries" FROM "vote" INNER JOIN "entry" ON "vote"."entry_id" = "entry"."id""#,
+                r#" WHERE ("vote"."created_at" > '2026-10-18 12:00:00' OR "vote"."updated_at" > '2026-10-18 12:00:00')"#,
+                r#" AND "vote"."entry_id" IN (SELECT "entry"."id" FROM "entry""#,
+                r#" INNER JOIN "title" ON "entry"."title_id" = "title"."id""#,
+                r#" WHERE "entry"."deleted_at" IS NULL"#,
+                r#" AND "title"."is_visible" = TRUE))"#,
+                r#" UNION ALL (SELECT "entry"."title_id""#,
+                r#", 1"#,
+                r#" * power(0.5, greatest(extract(epoch from ('2026-10-19 12:00:00' - "favorite"."created_at")), 0) / 21600)"#,
+                r#" AS "score", 0 AS "entries" FROM "favorite" INNER JOIN "entry" ON "favorite"."entry_id" = "entry"."id""#,
+                r#" WHERE "favorite"."created_at" > '2026-10-18 12:00:00'"#,
+                r#" AND "favorite"."entry_id" IN (SELECT "entry"."id" FROM "entry""#,
+                r#" INNER JOIN "title" ON "entry"."title_id" = "title"."id""#,
+                r#" WHERE "entry"."deleted_at" IS NULL"#,
+                r#" AND "title"."is_visible" = TRUE)))"#,
+                r#" AS "activity" GROUP BY "title_id" HAVING sum("entries") > 0"#,
+                r#" ORDER BY "score" DESC, "entry_count" DESC, "title_id" ASC LIMIT 10 OFFSET 20"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use ::entity::prelude::*;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use totp_rs::{Algorithm, Secret, TOTP};
+use validator::Validate;
+
+use crate::{
+    dto::two_factor::{EnrollTwoFactorResponse, RecoveryCodesResponse, TwoFactorCodeRequest},
+    token::{generate_token, hash_token},
+    Error, Result,
+};
+
+const ISSUER: &str = "Soğan Sözlük";
+const DIGITS: usize = 6;
+const STEP: u64 = 30;
+const SKEW: u64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChallengeClaims {
+    id: i32,
+    two_factor_challenge: bool,
+    iat: i64,
+    exp: i64,
+}
+
+fn totp(secret: &str, account_name: &str) -> Result<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|_| Error::InternalError("Geçersiz 2FA anahtarı.".to_string()))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        DIGITS,
+        SKEW as u8,
+        STEP,
+        secret,
+        Some(ISSUER.to_string()),
+        account_name.replace(':', "-"),
+    )
+    .map_err(|_| Error::InternalError("Geçersiz 2FA anahtarı.".to_string()))
+}
+
+/// Generates a 160 bit base32 encoded secret as recommended by RFC 4226.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+
+    Secret::Raw(bytes.to_vec()).to_encoded().to_string()
+}
+
+/// Checks `code` against the steps around `time` and returns the matching
+/// step. Steps at or before `last_step` are rejected so that a code can't be
+/// replayed.
+pub fn verify_code(secret: &str, code: &str, last_step: Option<i64>, time: u64) -> Option<i64> {
+    let totp = totp(secret, "").ok()?;
+    let current = time / STEP;
+
+    (current.saturating_sub(SKEW)..=current + SKEW)
+        .filter(|step| last_step.is_none_or(|last| *step as i64 > last))
+        .find(|step| totp.generate(step * STEP) == code)
+        .map(|step| step as i64)
+}
+
+fn generate_recovery_code() -> String {
+    let token = generate_token();
+
+    token.as_bytes()[..16]
+        .chunks(4)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.trim().replace('-', "").to_lowercase()
+}
+
+fn now_unix() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+async fn find_user(db: &DbConn, user_id: i32) -> Result<UserModel> {
+    User::find()
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))
+}
+
+pub async fn enroll(db: &DbConn, user_id: i32) -> Result<EnrollTwoFactorResponse> {
+    let user = find_user(db, user_id).await?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(Error::InvalidRequest(
+            "İki adımlı doğrulama zaten etkin.".to_string(),
+        ));
+    }
+
+    let secret = generate_secret();
+    let otpauth_uri = totp(&secret, &user.nickname)?.get_url();
+
+    let mut user = user.into_active_model();
+    user.totp_secret = Set(Some(secret.clone()));
+    user.update(db)
+        .await
+        .map_err(|_| Error::InternalError("İki adımlı doğrulama başlatılamadı.".to_string()))?;
+
+    Ok(EnrollTwoFactorResponse {
+        secret,
+        otpauth_uri,
+    })
+}
+
+pub async fn confirm(
+    db: &DbConn,
+    user_id: i32,
+    request: TwoFactorCodeRequest,
+) -> Result<RecoveryCodesResponse> {
+    request.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let user = find_user(db, user_id).await?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(Error::InvalidRequest(
+            "İki adımlı doğrulama zaten etkin.".to_string(),
+        ));
+    }
+
+    let secret = user.totp_secret.clone().ok_or(Error::InvalidRequest(
+        "Önce iki adımlı doğrulama kaydı başlatılmalı.".to_string(),
+    ))?;
+
+    let step = verify_code(&secret, request.code.trim(), None, now_unix())
+        .ok_or(Error::Unauthorized("Geçersiz doğrulama kodu.".to_string()))?;
+
+    let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+
+    let txn = db.begin().await.map_err(|_| {
+        Error::InternalError("İki adımlı doğrulama etkinleştirilemedi.".to_string())
+    })?;
+
+    RecoveryCode::delete_many()
+        .filter(RecoveryCodeColumn::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Kurtarma kodları silinemedi.".to_string()))?;
+
+    RecoveryCode::insert_many(recovery_codes.iter().map(|code| RecoveryCodeActiveModel {
+        user_id: Set(user.id),
+        hash: Set(hash_token(&normalize_recovery_code(code))),
+        ..Default::default()
+    }))
+    .exec(&txn)
+    .await
+    .map_err(|_| Error::InternalError("Kurtarma kodları oluşturulamadı.".to_string()))?;
+
+    let mut user = user.into_active_model();
+    user.totp_enabled_at = Set(Some(chrono::Utc::now().naive_utc()));
+    user.totp_last_step = Set(Some(step));
+    user.update(&txn).await.map_err(|_| {
+        Error::InternalError("İki adımlı doğrulama etkinleştirilemedi.".to_string())
+    })?;
+
+    txn.commit().await.map_err(|_| {
+        Error::InternalError("İki adımlı doğrulama etkinleştirilemedi.".to_string())
+    })?;
+
+    Ok(RecoveryCodesResponse { recovery_codes })
+}
+
+pub async fn disable(db: &DbConn, user_id: i32, request: TwoFactorCodeRequest) -> Result<()> {
+    request.validate().map_err(|_| {
+        Error::InvalidRequest("Geçersiz istek. Lütfen girilen bilgileri kontrol edin.".to_string())
+    })?;
+
+    let user = find_user(db, user_id).await?;
+
+    if user.totp_enabled_at.is_none() {
+        return Err(Error::InvalidRequest(
+            "İki adımlı doğrulama etkin değil.".to_string(),
+        ));
+    }
+
+    check_code(db, &user, &request.code).await?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("İki adımlı doğrulama kapatılamadı.".to_string()))?;
+
+    RecoveryCode::delete_many()
+        .filter(RecoveryCodeColumn::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|_| Error::InternalError("Kurtarma kodları silinemedi.".to_string()))?;
+
+    let mut user = user.into_active_model();
+    user.totp_secret = Set(None);
+    user.totp_enabled_at = Set(None);
+    user.totp_last_step = Set(None);
+    user.update(&txn)
+        .await
+        .map_err(|_| Error::InternalError("İki adımlı doğrulama kapatılamadı.".to_string()))?;
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("İki adımlı doğrulama kapatılamadı.".to_string()))
+}
+
+/// Accepts either a TOTP code or one of the unused recovery codes of the user
+/// and marks it as used.
+pub async fn check_code(db: &DbConn, user: &UserModel, code: &str) -> Result<()> {
+    let invalid = || Error::Unauthorized("Geçersiz doğrulama kodu.".to_string());
+    let secret = user.totp_secret.as_deref().ok_or_else(invalid)?;
+    let code = code.trim();
+
+    if let Some(step) = verify_code(secret, code, user.totp_last_step, now_unix()) {
+        // Only move forward so that concurrent logins can't reuse the step.
+        let result = User::update_many()
+            .col_expr(UserColumn::TotpLastStep, step.into())
+            .filter(UserColumn::Id.eq(user.id))
+            .filter(
+                Condition::any()
+                    .add(UserColumn::TotpLastStep.is_null())
+                    .add(UserColumn::TotpLastStep.lt(step)),
+            )
+            .exec(db)
+            .await
+            .map_err(|_| Error::InternalError("Doğrulama kodu kaydedilemedi.".to_string()))?;
+
+        return match result.rows_affected {
+            0 => Err(invalid()),
+            _ => Ok(()),
+        };
+    }
+
+    let result = RecoveryCode::update_many()
+        .col_expr(
+            RecoveryCodeColumn::UsedAt,
+            chrono::Utc::now().naive_utc().into(),
+        )
+        .filter(RecoveryCodeColumn::UserId.eq(user.id))
+        .filter(RecoveryCodeColumn::Hash.eq(hash_token(&normalize_recovery_code(code))))
+        .filter(RecoveryCodeColumn::UsedAt.is_null())
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Kurtarma kodu kullanılamadı.".to_string()))?;
+
+    match result.rows_affected {
+        0 => Err(invalid()),
+        _ => Ok(()),
+    }
+}
+
+/// Creates a short lived token that proves the password step of a login was
+/// passed. It can't be used as a session token.
+pub fn create_challenge(user_id: i32, key: &[u8], lifetime_seconds: i64) -> Result<String> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = ChallengeClaims {
+        id: user_id,
+        two_factor_challenge: true,
+        iat: now,
+        exp: now + lifetime_seconds,
+    };
+
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(key),
+    )
+    .map_err(|_| Error::InternalError("Token oluşturulamadı.".to_string()))
+}
+
+pub fn challenge_user_id(challenge: &str, key: &[u8]) -> Option<i32> {
+    let key = jsonwebtoken::DecodingKey::from_secret(key);
+    let validation = jsonwebtoken::Validation::default();
+    let claims = jsonwebtoken::decode::<ChallengeClaims>(challenge, &key, &validation)
+        .ok()?
+        .claims;
+
+    claims.two_factor_challenge.then_some(claims.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B test secret ("12345678901234567890").
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_verify_code() {
+        assert_eq!(verify_code(RFC_SECRET, "287082", None, 59), Some(1));
+        assert_eq!(
+            verify_code(RFC_SECRET, "081804", None, 1111111109),
+            Some(37037036)
+        );
+        assert_eq!(verify_code(RFC_SECRET, "000000", None, 59), None);
+    }
+
+    #[test]
+    fn test_verify_code_accepts_adjacent_steps() {
+        assert_eq!(verify_code(RFC_SECRET, "287082", None, 59 + 30), Some(1));
+        assert_eq!(verify_code(RFC_SECRET, "287082", None, 59 + 60), None);
+    }
+
+    #[test]
+    fn test_verify_code_rejects_replay() {
+        assert_eq!(verify_code(RFC_SECRET, "287082", Some(0), 59), Some(1));
+        assert_eq!(verify_code(RFC_SECRET, "287082", Some(1), 59), None);
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret();
+
+        assert_eq!(secret.len(), 32);
+        assert!(totp(&secret, "test").is_ok());
+    }
+
+    #[test]
+    fn test_recovery_code() {
+        let code = generate_recovery_code();
+
+        assert_eq!(code.len(), 19);
+        assert_eq!(normalize_recovery_code(&code).len(), 16);
+        assert_eq!(
+            normalize_recovery_code(" ABCD-ef01-2345-6789 "),
+            "abcdef0123456789"
+        );
+    }
+
+    #[test]
+    fn test_challenge() {
+        let key = b"test123";
+        let challenge = create_challenge(42, key, 60).unwrap();
+
+        assert_eq!(challenge_user_id(&challenge, key), Some(42));
+        assert_eq!(challenge_user_id(&challenge, b"invalid_key"), None);
+        assert!(!crate::token::validate_token(&challenge, "test123"));
+    }
+}
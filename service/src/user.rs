@@ -1,9 +1,10 @@
-use crate::{Error, Result};
+use crate::{token::hash_token, Error, Result};
 use ::entity::prelude::*;
 use sea_orm::*;
+use sea_query::Expr;
 
 pub async fn user_by_token(db: &DbConn, token: &str) -> Result<UserModel> {
-    let token_hash = blake3::hash(token.as_bytes()).to_hex().to_string();
+    let token_hash = hash_token(token);
 
     let user_id = Token::find()
         .filter(TokenColumn::Hash.eq(token_hash))
@@ -22,3 +23,21 @@ pub async fn user_by_token(db: &DbConn, token: &str) -> Result<UserModel> {
         .map_err(|_| Error::InvalidToken)
         .and_then(|user| user.ok_or(Error::InvalidToken))
 }
+
+/// Marks an account as run by a script. Bot accounts are flagged on their
+/// entries so that readers can tell them apart from people.
+pub async fn set_bot(db: &DbConn, id: i32, is_bot: bool) -> Result<()> {
+    let result = User::update_many()
+        .col_expr(UserColumn::IsBot, Expr::value(is_bot))
+        .filter(UserColumn::Id.eq(id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .exec(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı güncellenemedi.".to_string()))?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound("Kullanıcı bulunamadı.".to_string()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use ::entity::prelude::*;
+use sea_orm::*;
+use sea_query::Expr;
+
+use crate::{
+    config::MailConfig,
+    mail::{Mail, Mailer},
+    token::{generate_token, hash_token},
+    Error, Result,
+};
+
+const RESEND_INTERVAL_SECONDS: i64 = 60;
+const RESEND_DAILY_LIMIT: u64 = 5;
+
+pub async fn send_verification(
+    db: &DbConn,
+    mailer: &dyn Mailer,
+    config: &MailConfig,
+    user: &UserModel,
+) -> Result<()> {
+    let token = generate_token();
+    let now = chrono::Utc::now().naive_utc();
+
+    EmailVerificationActiveModel {
+        user_id: Set(user.id),
+        hash: Set(hash_token(&token)),
+        expires_at: Set(now + chrono::Duration::hours(config.verification_lifetime_hours)),
+        ..Default::default()
+    }
+    .save(db)
+    .await
+    .map_err(|_| Error::InternalError("Doğrulama kodu oluşturulamadı.".to_string()))?;
+
+    let mail = Mail {
+        to: user.email.clone(),
+        subject: "E-posta adresinizi doğrulayın".to_string(),
+        body: format!(
+            "Merhaba {},\n\nHesabınızı etkinleştirmek için aşağıdaki bağlantıya tıklayın:\n\n{}?token={}\n\nBağlantı {} saat boyunca geçerlidir.",
+            user.nickname, config.verification_url, token, config.verification_lifetime_hours
+        ),
+    };
+
+    mailer.send(mail).await
+}
+
+pub async fn verify_email(db: &DbConn, token: &str) -> Result<()> {
+    let now = chrono::Utc::now().naive_utc();
+
+    let verification = EmailVerification::find()
+        .filter(EmailVerificationColumn::Hash.eq(hash_token(token)))
+        .filter(EmailVerificationColumn::UsedAt.is_null())
+        .filter(EmailVerificationColumn::ExpiresAt.gt(now))
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Doğrulama kodu bulunamadı.".to_string()))
+        .and_then(|verification| verification.ok_or(Error::InvalidToken))?;
+
+    let user = User::find()
+        .filter(UserColumn::Id.eq(verification.user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|_| Error::InternalError("E-posta doğrulanamadı.".to_string()))?;
+
+    // Only the request that marks the token as used goes on, so that a token
+    // can't be spent twice by requests racing each other.
+    let used = EmailVerification::update_many()
+        .col_expr(EmailVerificationColumn::UsedAt, Expr::value(now))
+        .filter(EmailVerificationColumn::Id.eq(verification.id))
+        .filter(EmailVerificationColumn::UsedAt.is_null())
+        .exec(&txn)
+        .await
+        .map_err(|_| Error::InternalError("E-posta doğrulanamadı.".to_string()))?;
+    if used.rows_affected == 0 {
+        return Err(Error::InvalidToken);
+    }
+
+    if user.email_verified_at.is_none() {
+        let mut user = user.into_active_model();
+        user.email_verified_at = Set(Some(now));
+        user.update(&txn)
+            .await
+            .map_err(|_| Error::InternalError("E-posta doğrulanamadı.".to_string()))?;
+    }
+
+    txn.commit()
+        .await
+        .map_err(|_| Error::InternalError("E-posta doğrulanamadı.".to_string()))
+}
+
+pub async fn resend_verification(
+    db: &DbConn,
+    mailer: &dyn Mailer,
+    config: &MailConfig,
+    user_id: i32,
+) -> Result<()> {
+    let user = User::find()
+        .filter(UserColumn::Id.eq(user_id))
+        .filter(UserColumn::DeletedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Kullanıcı bulunamadı.".to_string()))
+        .and_then(|user| user.ok_or(Error::NotFound("Kullanıcı bulunamadı.".to_string())))?;
+
+    if user.email_verified_at.is_some() {
+        return Err(Error::InvalidRequest(
+            "E-posta adresi zaten doğrulanmış.".to_string(),
+        ));
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+
+    let last_sent_at: Option<chrono::NaiveDateTime> = EmailVerification::find()
+        .filter(EmailVerificationColumn::UserId.eq(user.id))
+        .select_only()
+        .column(EmailVerificationColumn::CreatedAt)
+        .order_by_desc(EmailVerificationColumn::CreatedAt)
+        .into_tuple()
+        .one(db)
+        .await
+        .map_err(|_| Error::InternalError("Doğrulama kodu bulunamadı.".to_string()))?;
+
+    if let Some(last_sent_at) = last_sent_at {
+        if now - last_sent_at < chrono::Duration::seconds(RESEND_INTERVAL_SECONDS) {
+            return Err(Error::TooManyRequests(format!(
+                "Yeni bir doğrulama e-postası için {} saniye bekleyin.",
+                RESEND_INTERVAL_SECONDS
+            )));
+        }
+    }
+
+    let sent_today = EmailVerification::find()
+        .filter(EmailVerificationColumn::UserId.eq(user.id))
+        .filter(EmailVerificationColumn::CreatedAt.gt(now - chrono::Duration::days(1)))
+        .count(db)
+        .await
+        .map_err(|_| Error::InternalError("Doğrulama kodu bulunamadı.".to_string()))?;
+
+    if sent_today >= RESEND_DAILY_LIMIT {
+        return Err(Error::TooManyRequests(
+            "Günlük doğrulama e-postası sınırına ulaşıldı.".to_string(),
+        ));
+    }
+
+    send_verification(db, mailer, config, &user).await
+}
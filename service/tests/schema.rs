@@ -0,0 +1,145 @@
+//! Runs the migrations and the service in a schema other than the default
+//! one, as an instance sharing its database with another would.
+//!
+//! Needs a PostgreSQL database: set `DATABASE_URL` to run it. The schema is
+//! dropped afterwards.
+
+use migration::{Migrator, MigratorTrait};
+use service::{
+    config::{AuthConfig, MailConfig},
+    dto::{
+        auth::{LoginRequest, LoginResult, RegisterRequest},
+        entry::CreateEntryRequest,
+        search::EntrySearchQuery,
+    },
+    mail::LogMailer,
+    sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, Statement},
+    search::backend::PostgresSearch,
+    time::{Tz, DEFAULT_TIMEZONE},
+};
+
+const SCHEMA: &str = "arpacik_schema_test";
+
+async fn count(db: &DatabaseConnection, table: &str) -> i64 {
+    db.query_one(Statement::from_string(
+        db.get_database_backend(),
+        format!(r#"SELECT COUNT(*) AS "count" FROM "{SCHEMA}"."{table}""#),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "count")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_service_runs_in_another_schema() {
+    let Ok(db_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set; skipping the schema check.");
+        return;
+    };
+
+    let admin = Database::connect(&db_url).await.unwrap();
+    admin
+        .execute_unprepared(&format!(
+            "DROP SCHEMA IF EXISTS {SCHEMA} CASCADE; CREATE SCHEMA {SCHEMA}"
+        ))
+        .await
+        .unwrap();
+
+    let mut opt = ConnectOptions::new(db_url);
+    opt.set_schema_search_path(SCHEMA);
+    let db = Database::connect(opt).await.unwrap();
+
+    Migrator::up(&db, None).await.unwrap();
+
+    let mail = MailConfig {
+        verification_url: "http://localhost/verify-email".to_string(),
+        ..Default::default()
+    };
+    let auth = AuthConfig {
+        jwt_secret: "VcHCJhYoXGGL7awzIL6woA==".to_string(),
+        ..Default::default()
+    };
+
+    let user = service::auth::register(
+        &db,
+        &LogMailer,
+        &mail,
+        RegisterRequest {
+            nickname: "sema".to_string(),
+            email: "sema@example.com".to_string(),
+            password: "Passw0rd!".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let login = service::auth::login(
+        &db,
+        &auth,
+        LoginRequest {
+            nickname: "sema".to_string(),
+            password: "Passw0rd!".to_string(),
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(matches!(login, LoginResult::Success(_)));
+
+    // New users are faded and can't open titles.
+    db.execute_unprepared(r#"UPDATE "user" SET "is_faded" = false"#)
+        .await
+        .unwrap();
+
+    let entry = service::entry::create_entry(
+        &db,
+        &PostgresSearch,
+        DEFAULT_TIMEZONE,
+        user.id,
+        CreateEntryRequest {
+            title: "şema".to_string(),
+            content: "ayrı bir şemada yazılmış kitaplar".to_string(),
+            force_new_title: false,
+        },
+        false,
+    )
+    .await
+    .unwrap();
+
+    let hits = service::search::search_entries(
+        &db,
+        &PostgresSearch,
+        Tz::UTC,
+        EntrySearchQuery {
+            query: "kitap".to_string(),
+            page: 1,
+            per_page: 10,
+            author: None,
+            title: None,
+            from: None,
+            to: None,
+            min_net_votes: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    let found: Vec<i32> = hits.items.iter().map(|hit| hit.entry.id).collect();
+
+    let migrations = count(&admin, "seaql_migrations").await;
+    let entries = count(&admin, "entry").await;
+    let tokens = count(&admin, "token").await;
+
+    db.close().await.unwrap();
+    admin
+        .execute_unprepared(&format!("DROP SCHEMA {SCHEMA} CASCADE"))
+        .await
+        .unwrap();
+
+    assert_eq!(migrations, Migrator::migrations().len() as i64);
+    assert_eq!(entries, 1);
+    assert_eq!(tokens, 1);
+    assert_eq!(found, vec![entry.id]);
+}